@@ -0,0 +1,470 @@
+// This is free and unencumbered software released into the public domain.
+
+//! A signing agent that holds unlocked account keys and signs on behalf of
+//! other processes, in the manner of `ssh-agent`.
+//!
+//! The agent listens on a Unix domain socket, which clients find through the
+//! `ASIMOV_ACCOUNT_AGENT_SOCK` environment variable. Each request and response
+//! is a JSON object on a single line, tagged by its `type`:
+//!
+//! ```text
+//! → {"type":"list_identities"}
+//! ← {"type":"identities","identities":[{"accountId":"alice.testnet","publicKey":"ed25519:..."}]}
+//!
+//! → {"type":"sign_transaction","transaction":"<base64 Borsh transaction>"}
+//! ← {"type":"signature","signature":"ed25519:..."}
+//!
+//! → {"type":"sign_message","accountId":"alice.testnet","publicKey":"ed25519:...",
+//!    "message":"...","recipient":"...","nonce":"<base64>","callbackUrl":null}
+//! ← {"type":"signature","signature":"ed25519:..."}
+//!
+//! ← {"type":"error","message":"..."}
+//! ```
+//!
+//! A transaction is signed with the key named by its signer and public key;
+//! a message is signed as specified by NEP-413.
+
+use crate::{nep413, rpc::parse_duration, tx};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use clientele::crates::clap::{Args, ValueEnum};
+use color_print::{ceprintln, cprintln};
+use near_api::{
+    errors::SignerError,
+    near_primitives::{
+        hash::CryptoHash,
+        transaction::{SignedTransaction, Transaction},
+        types::Nonce,
+    },
+    signer::NEP413Payload,
+    types::transactions::PrepopulateTransaction,
+    AccountId, SignerTrait,
+};
+use near_crypto::{PublicKey, SecretKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::IsTerminal as _,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::Mutex,
+};
+
+/// The environment variable holding the path of the agent's socket.
+pub const SOCKET_VAR: &str = "ASIMOV_ACCOUNT_AGENT_SOCK";
+
+/// Which requests the agent asks to confirm on its terminal before signing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Confirm {
+    /// Sign every request without asking.
+    #[default]
+    Never,
+    /// Ask before signing a transaction, but not a message.
+    Transactions,
+    /// Ask before signing anything.
+    Always,
+}
+
+/// Options for running the agent.
+#[derive(Clone, Debug, Default, Args)]
+pub struct AgentOptions {
+    /// The path of the socket to listen on. Defaults to `~/.asimov/accounts/agent.sock`.
+    #[clap(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+
+    /// Which requests to confirm on the terminal before signing. Defaults to `never`.
+    #[clap(long, value_name = "POLICY", value_enum)]
+    pub confirm: Option<Confirm>,
+
+    /// How long to hold the keys before forgetting them and exiting. For example `1h`.
+    #[clap(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub lifetime: Option<Duration>,
+}
+
+/// An account key held by the agent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum Request {
+    ListIdentities,
+    SignTransaction {
+        transaction: String,
+    },
+    SignMessage {
+        account_id: AccountId,
+        public_key: PublicKey,
+        message: String,
+        recipient: String,
+        nonce: String,
+        #[serde(default)]
+        callback_url: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum Response {
+    Identities { identities: Vec<Identity> },
+    Signature { signature: Signature },
+    Error { message: String },
+}
+
+/// Returns the default path of the agent's socket.
+pub fn default_socket() -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".asimov")
+            .join("accounts")
+            .join("agent.sock"),
+    )
+}
+
+/// Returns the path of the agent's socket if one is configured.
+pub fn socket_from_env() -> Option<PathBuf> {
+    std::env::var_os(SOCKET_VAR)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Sends a request to the agent listening on the socket.
+pub async fn request(socket: &Path, request: &Request) -> std::io::Result<Response> {
+    let mut stream = BufReader::new(UnixStream::connect(socket).await?);
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.get_mut().write_all(line.as_bytes()).await?;
+
+    let mut line = String::new();
+    if stream.read_line(&mut line).await? == 0 {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Lists the identities held by the agent listening on the socket.
+pub async fn identities(socket: &Path) -> std::io::Result<Vec<Identity>> {
+    match request(socket, &Request::ListIdentities).await? {
+        Response::Identities { identities } => Ok(identities),
+        response => Err(unexpected(response)),
+    }
+}
+
+fn unexpected(response: Response) -> std::io::Error {
+    match response {
+        Response::Error { message } => std::io::Error::other(message),
+        _ => std::io::Error::other("unexpected response from agent"),
+    }
+}
+
+/// A signer that forwards signing requests to the agent.
+#[derive(Clone, Debug)]
+pub struct AgentSigner {
+    socket: PathBuf,
+    identity: Identity,
+}
+
+impl AgentSigner {
+    /// Returns a signer for a key of the account held by the agent, if any.
+    pub async fn connect(
+        socket: &Path,
+        account_id: &AccountId,
+        public_key: Option<&PublicKey>,
+    ) -> std::io::Result<Option<Self>> {
+        let identity = identities(socket).await?.into_iter().find(|identity| {
+            identity.account_id == *account_id
+                && public_key.is_none_or(|public_key| identity.public_key == *public_key)
+        });
+        Ok(identity.map(|identity| Self {
+            socket: socket.into(),
+            identity,
+        }))
+    }
+
+    async fn signature(&self, request: &Request) -> Result<Signature, SignerError> {
+        request_signature(&self.socket, request)
+            .await
+            .map_err(SignerError::IO)
+    }
+}
+
+async fn request_signature(socket: &Path, request: &Request) -> std::io::Result<Signature> {
+    match self::request(socket, request).await? {
+        Response::Signature { signature } => Ok(signature),
+        response => Err(unexpected(response)),
+    }
+}
+
+#[async_trait::async_trait]
+impl SignerTrait for AgentSigner {
+    async fn sign(
+        &self,
+        tr: PrepopulateTransaction,
+        public_key: PublicKey,
+        nonce: Nonce,
+        block_hash: near_api::types::CryptoHash,
+    ) -> Result<SignedTransaction, SignerError> {
+        let mut transaction = Transaction::new_v0(
+            tr.signer_id,
+            public_key,
+            tr.receiver_id,
+            nonce,
+            block_hash.into(),
+        );
+        *transaction.actions_mut() = tr.actions;
+        let signature = self
+            .signature(&Request::SignTransaction {
+                transaction: tx::encode(&transaction),
+            })
+            .await?;
+        Ok(SignedTransaction::new(signature, transaction))
+    }
+
+    async fn sign_message_nep413(
+        &self,
+        signer_id: AccountId,
+        public_key: PublicKey,
+        payload: NEP413Payload,
+    ) -> Result<Signature, SignerError> {
+        self.signature(&Request::SignMessage {
+            account_id: signer_id,
+            public_key,
+            message: payload.message,
+            recipient: payload.recipient,
+            nonce: BASE64.encode(payload.nonce),
+            callback_url: payload.callback_url,
+        })
+        .await
+    }
+
+    async fn get_secret_key(
+        &self,
+        _signer_id: &AccountId,
+        _public_key: &PublicKey,
+    ) -> Result<SecretKey, SignerError> {
+        // The agent never hands out its keys:
+        Err(SignerError::SecretKeyIsNotAvailable)
+    }
+
+    fn get_public_key(&self) -> Result<PublicKey, SignerError> {
+        Ok(self.identity.public_key.clone())
+    }
+}
+
+/// The agent's keys and policy.
+pub struct Agent {
+    keys: BTreeMap<(AccountId, PublicKey), SecretKey>,
+    confirm: Confirm,
+    verbose: u8,
+    /// Serializes confirmation prompts on the terminal.
+    prompt: Mutex<()>,
+}
+
+impl Agent {
+    pub fn new(confirm: Confirm, verbose: u8) -> Self {
+        Self {
+            keys: BTreeMap::new(),
+            confirm,
+            verbose,
+            prompt: Mutex::new(()),
+        }
+    }
+
+    pub fn add_key(&mut self, account_id: AccountId, secret_key: SecretKey) {
+        self.keys
+            .insert((account_id, secret_key.public_key()), secret_key);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Accepts connections on the socket from processes of the given user
+    /// until the lifetime (if any) expires or the process is interrupted.
+    pub async fn serve(self, listener: UnixListener, owner: u32, lifetime: Option<Duration>) {
+        let agent = Arc::new(self);
+        let expired = async {
+            match lifetime {
+                Some(lifetime) => tokio::time::sleep(lifetime).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(expired);
+
+        loop {
+            tokio::select! {
+                connection = listener.accept() => match connection {
+                    Ok((stream, _)) => match stream.peer_cred() {
+                        Ok(peer) if peer.uid() == owner => {
+                            tokio::spawn(agent.clone().handle_connection(stream));
+                        }
+                        Ok(peer) => {
+                            ceprintln!(
+                                "<s,y>warning:</> refused connection from user {}",
+                                peer.uid()
+                            );
+                        }
+                        Err(error) => {
+                            ceprintln!("<s,y>warning:</> failed to identify peer: {error}");
+                        }
+                    },
+                    Err(error) => {
+                        ceprintln!("<s,y>warning:</> failed to accept connection: {error}");
+                    }
+                },
+                _ = &mut expired => {
+                    if agent.verbose >= 1 {
+                        cprintln!("<s,y>!</> Lifetime expired, forgetting keys");
+                    }
+                    break;
+                }
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        }
+    }
+
+    async fn handle_connection(self: Arc<Self>, stream: UnixStream) {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let response = match serde_json::from_str(&line) {
+                Ok(request) => self.handle(request).await,
+                Err(error) => Response::Error {
+                    message: format!("invalid request: {error}"),
+                },
+            };
+            let mut line = serde_json::to_string(&response).expect("responses should serialize");
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    async fn handle(&self, request: Request) -> Response {
+        let result = match request {
+            Request::ListIdentities => Ok(Response::Identities {
+                identities: self
+                    .keys
+                    .keys()
+                    .map(|(account_id, public_key)| Identity {
+                        account_id: account_id.clone(),
+                        public_key: public_key.clone(),
+                    })
+                    .collect(),
+            }),
+            Request::SignTransaction { transaction } => self.sign_transaction(&transaction).await,
+            Request::SignMessage {
+                account_id,
+                public_key,
+                message,
+                recipient,
+                nonce,
+                callback_url,
+            } => {
+                let payload = nep413::parse_nonce(&nonce).map(|nonce| NEP413Payload {
+                    message,
+                    nonce,
+                    recipient,
+                    callback_url,
+                });
+                match payload {
+                    Ok(payload) => self.sign_message(account_id, public_key, payload).await,
+                    Err(error) => Err(format!("invalid nonce: {error}")),
+                }
+            }
+        };
+        result.unwrap_or_else(|message| Response::Error { message })
+    }
+
+    async fn sign_transaction(&self, transaction: &str) -> Result<Response, String> {
+        let transaction: Transaction = tx::decode(transaction)?;
+        let account_id = transaction.signer_id().clone();
+        let secret_key = self.key(&account_id, transaction.public_key())?;
+
+        let prompt = format!(
+            "Sign transaction from {account_id} to {} with {} action(s)?",
+            transaction.receiver_id(),
+            transaction.actions().len(),
+        );
+        self.confirm(self.confirm != Confirm::Never, &prompt)
+            .await?;
+
+        let hash: CryptoHash = transaction.get_hash_and_size().0;
+        if self.verbose >= 1 {
+            cprintln!("<s,g>✓</> Signed transaction {hash} for <s>{account_id}</>");
+        }
+        Ok(Response::Signature {
+            signature: secret_key.sign(hash.as_ref()),
+        })
+    }
+
+    async fn sign_message(
+        &self,
+        account_id: AccountId,
+        public_key: PublicKey,
+        payload: NEP413Payload,
+    ) -> Result<Response, String> {
+        let secret_key = self.key(&account_id, &public_key)?;
+
+        let prompt = format!(
+            "Sign message {:?} from {account_id} for {}?",
+            payload.message, payload.recipient,
+        );
+        self.confirm(self.confirm == Confirm::Always, &prompt)
+            .await?;
+
+        if self.verbose >= 1 {
+            cprintln!("<s,g>✓</> Signed message for <s>{account_id}</>");
+        }
+        Ok(Response::Signature {
+            signature: secret_key.sign(&nep413::message_hash(&payload)),
+        })
+    }
+
+    fn key(&self, account_id: &AccountId, public_key: &PublicKey) -> Result<&SecretKey, String> {
+        self.keys
+            .get(&(account_id.clone(), public_key.clone()))
+            .ok_or_else(|| format!("no key {public_key} for account {account_id}"))
+    }
+
+    /// Asks on the terminal whether to allow a request, if the policy requires it.
+    async fn confirm(&self, required: bool, prompt: &str) -> Result<(), String> {
+        if !required {
+            return Ok(());
+        }
+        let denied = || "request was denied".to_string();
+        if !std::io::stdin().is_terminal() {
+            return Err(denied());
+        }
+
+        let _guard = self.prompt.lock().await;
+        ceprintln!("<s,c>?</> {prompt} [y/N]");
+        let answer = tokio::task::spawn_blocking(|| {
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer).map(|_| answer)
+        })
+        .await;
+        match answer {
+            Ok(Ok(answer)) if matches!(answer.trim(), "y" | "Y" | "yes") => Ok(()),
+            _ => Err(denied()),
+        }
+    }
+}
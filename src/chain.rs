@@ -0,0 +1,194 @@
+// This is free and unencumbered software released into the public domain.
+
+//! The network and keychain operations the commands depend on.
+//!
+//! Commands take a [`Chain`] rather than calling near-api directly, so that
+//! they can run against [`NearChain`], which talks to the real network and
+//! the OS keychain, or against `FakeChain` (with the `fake-chain` feature),
+//! which keeps accounts, keys and transactions in memory for offline tests.
+
+#[cfg(feature = "fake-chain")]
+mod fake;
+#[cfg(feature = "fake-chain")]
+pub use fake::*;
+
+mod near;
+pub use near::*;
+
+use crate::{
+    keychain::{Credentials, KeychainError},
+    network_name::NetworkName,
+    tx::{TxError, TxOutcome, WaitUntil},
+};
+use near_api::{
+    advanced::ExecuteSignedTransaction,
+    near_primitives::{
+        hash::CryptoHash,
+        transaction::SignedTransaction,
+        views::{AccessKeyInfoView, AccessKeyView, AccountView, FinalExecutionOutcomeView},
+    },
+    AccountId,
+};
+use near_crypto::PublicKey;
+
+/// An error from a query or faucet request.
+pub type ChainError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The error of a query about an account that doesn't exist on the network,
+/// as opposed to one that couldn't be answered.
+#[derive(Debug)]
+pub struct UnknownAccount(pub AccountId);
+
+impl std::fmt::Display for UnknownAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "account {} does not exist", self.0)
+    }
+}
+
+impl std::error::Error for UnknownAccount {}
+
+/// The result of calling a view method of a contract.
+#[derive(Clone, Debug, Default)]
+pub struct ViewResult {
+    /// The value returned by the method, usually JSON but possibly Borsh.
+    pub result: Vec<u8>,
+    /// The lines logged by the method.
+    pub logs: Vec<String>,
+}
+
+#[async_trait::async_trait]
+pub trait Chain: Send + Sync {
+    /// Fetches an account, failing with [`UnknownAccount`] if it doesn't
+    /// exist.
+    async fn view_account(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<AccountView, ChainError>;
+
+    /// Fetches an access key of an account.
+    async fn access_key(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<AccessKeyView, ChainError>;
+
+    /// Fetches all access keys of an account.
+    async fn list_keys(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<Vec<AccessKeyInfoView>, ChainError>;
+
+    /// Calls a view method of a contract with encoded arguments, returning
+    /// its encoded result and logs.
+    async fn call_view(
+        &self,
+        network: NetworkName,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: Vec<u8>,
+    ) -> Result<ViewResult, ChainError>;
+
+    /// Calls a view method of a contract with JSON arguments, returning its
+    /// JSON result.
+    async fn view_function(
+        &self,
+        network: NetworkName,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: &serde_json::Value,
+    ) -> Result<serde_json::Value, ChainError> {
+        let args = serde_json::to_vec(args)?;
+        let view = self
+            .call_view(network, contract_id, method_name, args)
+            .await?;
+        Ok(serde_json::from_slice(&view.result)?)
+    }
+
+    /// Asks the network's faucet to create an account with the given
+    /// full-access key, returning the faucet's transaction.
+    async fn request_faucet(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<FinalExecutionOutcomeView, ChainError>;
+
+    /// Asks the network's faucet to send tokens to an existing account,
+    /// returning the faucet's transaction.
+    async fn request_tokens(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<FinalExecutionOutcomeView, ChainError>;
+
+    /// Signs the transaction using the current nonce and block hash from the network.
+    async fn sign(
+        &self,
+        network: NetworkName,
+        transaction: ExecuteSignedTransaction,
+    ) -> Result<SignedTransaction, TxError>;
+
+    /// Sends a signed transaction, waiting until it reaches the given status.
+    async fn broadcast(
+        &self,
+        network: NetworkName,
+        signed: SignedTransaction,
+        wait_until: WaitUntil,
+    ) -> Result<TxOutcome, TxError>;
+
+    /// Waits until a transaction reaches the given status.
+    async fn tx_status(
+        &self,
+        network: NetworkName,
+        hash: CryptoHash,
+        sender: &AccountId,
+        wait_until: WaitUntil,
+    ) -> Result<TxOutcome, TxError>;
+
+    /// Signs the transaction and sends it, waiting until it reaches the given status.
+    async fn send(
+        &self,
+        network: NetworkName,
+        transaction: ExecuteSignedTransaction,
+        wait_until: WaitUntil,
+    ) -> Result<TxOutcome, TxError> {
+        let signed = self.sign(network, transaction).await?;
+        self.broadcast(network, signed, wait_until).await
+    }
+
+    /// Searches the keychain for credentials matching the account's
+    /// full-access keys on the network.
+    async fn find_credentials(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<Credentials, KeychainError>;
+
+    /// Returns the keychain credentials for the given key, without contacting
+    /// the network.
+    fn credentials_for_key(
+        &self,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<Credentials, KeychainError>;
+
+    /// Saves a key pair, as serialized by near-cli, to the keychain.
+    fn save_credentials(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+        key_pair: &serde_json::Value,
+    ) -> Result<(), ChainError>;
+
+    /// Removes the credentials for a key from the keychain, if present.
+    fn forget_credentials(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    );
+}
@@ -0,0 +1,1142 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{Chain, ChainError, UnknownAccount, ViewResult};
+use crate::{
+    keychain::{Credentials, KeychainError},
+    network_name::NetworkName,
+    rpc::RpcError,
+    tx::{self, TxError, TxOutcome, WaitUntil},
+};
+use near_api::{
+    advanced::{ExecuteSignedTransaction, TransactionableOrSigned},
+    errors::SignerError,
+    near_primitives::{
+        account::{AccessKey, AccessKeyPermission},
+        action::{Action, AddKeyAction, CreateAccountAction, FunctionCallAction, TransferAction},
+        errors::{
+            ActionError, ActionErrorKind, FunctionCallError, InvalidAccessKeyError, InvalidTxError,
+            MethodResolveError, TxExecutionError,
+        },
+        hash::CryptoHash,
+        transaction::{SignedTransaction, Transaction},
+        types::Nonce,
+        views::{
+            AccessKeyInfoView, AccessKeyView, AccountView, ExecutionMetadataView,
+            ExecutionOutcomeView, ExecutionOutcomeWithIdView, ExecutionStatusView,
+            FinalExecutionOutcomeView, FinalExecutionStatus,
+        },
+    },
+    signer::secret_key::SecretKeySigner,
+    AccountId, NearToken,
+};
+use near_crypto::{KeyType, PublicKey, SecretKey, Signature};
+use near_jsonrpc_client::{
+    errors::{JsonRpcError, JsonRpcServerError},
+    methods::tx::RpcTransactionError,
+};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    sync::{Mutex, MutexGuard},
+};
+
+/// The amount the fake faucet funds new accounts with, as on testnet.
+const FAUCET_AMOUNT: NearToken = NearToken::from_near(10);
+
+/// The deposit that registers an account with a fake fungible token, as
+/// with the standard implementation.
+const STORAGE_DEPOSIT: u128 = 1_250_000_000_000_000_000_000;
+
+/// An in-memory network and keychain, for tests.
+///
+/// Accounts on all networks live in one ledger, since their names tell
+/// them apart. Transactions are checked for a valid signature, nonce and
+/// access key, and their actions are applied atomically. Only the
+/// `create_account` method of the `near` and `testnet` registrar accounts
+/// can be called, which is how the faucet and sponsors create accounts,
+/// and the methods of staking pools added with [`FakeChain::add_staking_pool`]
+/// and of fungible tokens added with [`FakeChain::add_fungible_token`].
+/// Non-fungible token contracts added with [`FakeChain::add_nft_contract`]
+/// can only be viewed.
+#[derive(Debug)]
+pub struct FakeChain {
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    accounts: BTreeMap<AccountId, FakeAccount>,
+    keychain: BTreeMap<(AccountId, PublicKey), SecretKey>,
+    transactions: BTreeMap<CryptoHash, FinalExecutionOutcomeView>,
+    block_hash: CryptoHash,
+}
+
+#[derive(Clone, Debug, Default)]
+struct FakeAccount {
+    balance: u128,
+    keys: BTreeMap<PublicKey, AccessKey>,
+    /// The delegations to the account, if it is a staking pool.
+    pool: Option<BTreeMap<AccountId, Delegation>>,
+    /// The fungible token the account is the contract of, if any.
+    token: Option<FungibleToken>,
+    /// The non-fungible tokens the account is the contract of, if any.
+    nfts: Option<NftContract>,
+}
+
+/// A NEP-141 fungible token contract.
+#[derive(Clone, Debug)]
+struct FungibleToken {
+    symbol: String,
+    decimals: u8,
+    /// The balances of the accounts registered with the contract.
+    balances: BTreeMap<AccountId, u128>,
+}
+
+/// A NEP-171 non-fungible token contract.
+#[derive(Clone, Debug)]
+struct NftContract {
+    metadata: serde_json::Value,
+    /// The tokens by ID, in the JSON shape returned by the contract.
+    tokens: BTreeMap<String, serde_json::Value>,
+}
+
+/// The balance of a delegator in a staking pool.
+#[derive(Clone, Debug)]
+struct Delegation {
+    staked: u128,
+    unstaked: u128,
+    /// Whether the unstaked balance can be withdrawn, which it can't until
+    /// some epochs after unstaking.
+    can_withdraw: bool,
+}
+
+impl Default for Delegation {
+    fn default() -> Self {
+        Self {
+            staked: 0,
+            unstaked: 0,
+            can_withdraw: true,
+        }
+    }
+}
+
+impl Default for FakeChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeChain {
+    /// Returns a network with only the registrar accounts.
+    pub fn new() -> Self {
+        let registrar = FakeAccount {
+            balance: NearToken::from_near(1_000_000_000).as_yoctonear(),
+            ..FakeAccount::default()
+        };
+        Self {
+            state: Mutex::new(State {
+                accounts: BTreeMap::from([
+                    ("near".parse().unwrap(), registrar.clone()),
+                    ("testnet".parse().unwrap(), registrar),
+                ]),
+                keychain: BTreeMap::new(),
+                transactions: BTreeMap::new(),
+                block_hash: CryptoHash::hash_bytes(b"asimov-account"),
+            }),
+        }
+    }
+
+    /// Applies a transaction from the faucet, which is the `testnet`
+    /// registrar, with a single action.
+    fn faucet(
+        &self,
+        network: NetworkName,
+        receiver_id: AccountId,
+        action: Action,
+    ) -> Result<FinalExecutionOutcomeView, ChainError> {
+        if network != NetworkName::Testnet {
+            return Err(format!("there is no faucet on {network}").into());
+        }
+
+        // The faucet's transaction isn't signed, as it's applied directly:
+        let registrar: AccountId = "testnet".parse().unwrap();
+        let mut state = self.state();
+        let nonce = state.transactions.len() as Nonce + 1;
+        let mut transaction = Transaction::new_v0(
+            registrar,
+            PublicKey::empty(KeyType::ED25519),
+            receiver_id,
+            nonce,
+            state.block_hash,
+        );
+        *transaction.actions_mut() = vec![action];
+        let signed = SignedTransaction::new(Signature::empty(KeyType::ED25519), transaction);
+
+        let status = match apply(&state.accounts, &signed.transaction) {
+            Ok(accounts) => {
+                state.accounts = accounts;
+                Ok(())
+            }
+            Err(error) => Err(TxExecutionError::ActionError(error)),
+        };
+        let outcome = outcome(&signed, state.block_hash, status);
+        state
+            .transactions
+            .insert(signed.get_hash(), outcome.clone());
+        Ok(outcome)
+    }
+
+    /// Creates an account with a new full-access key, whose credentials are
+    /// added to the keychain, and returns its secret key.
+    pub fn add_account(&self, account_id: &AccountId, balance: NearToken) -> SecretKey {
+        let secret_key = SecretKey::from_random(KeyType::ED25519);
+        let mut state = self.state();
+        state.accounts.insert(
+            account_id.clone(),
+            FakeAccount {
+                balance: balance.as_yoctonear(),
+                keys: BTreeMap::from([(secret_key.public_key(), AccessKey::full_access())]),
+                ..FakeAccount::default()
+            },
+        );
+        state.keychain.insert(
+            (account_id.clone(), secret_key.public_key()),
+            secret_key.clone(),
+        );
+        secret_key
+    }
+
+    /// Creates a staking pool contract account without keys.
+    pub fn add_staking_pool(&self, pool_id: &AccountId) {
+        self.state().accounts.insert(
+            pool_id.clone(),
+            FakeAccount {
+                pool: Some(BTreeMap::new()),
+                ..FakeAccount::default()
+            },
+        );
+    }
+
+    /// Makes the unstaked balances in a staking pool available for
+    /// withdrawal, as the end of the unstaking epochs would.
+    pub fn finish_unstaking(&self, pool_id: &AccountId) {
+        let mut state = self.state();
+        let pool = state
+            .accounts
+            .get_mut(pool_id)
+            .and_then(|account| account.pool.as_mut());
+        for delegation in pool.into_iter().flat_map(BTreeMap::values_mut) {
+            delegation.can_withdraw = true;
+        }
+    }
+
+    /// Creates a NEP-141 fungible token contract account without keys.
+    pub fn add_fungible_token(&self, token_id: &AccountId, symbol: &str, decimals: u8) {
+        self.state().accounts.insert(
+            token_id.clone(),
+            FakeAccount {
+                token: Some(FungibleToken {
+                    symbol: symbol.into(),
+                    decimals,
+                    balances: BTreeMap::new(),
+                }),
+                ..FakeAccount::default()
+            },
+        );
+    }
+
+    /// Registers an account with a fungible token and gives it an amount
+    /// in the smallest units of the token.
+    pub fn mint(&self, token_id: &AccountId, account_id: &AccountId, amount: u128) {
+        let mut state = self.state();
+        let token = state
+            .accounts
+            .get_mut(token_id)
+            .and_then(|account| account.token.as_mut())
+            .expect("the fungible token should exist");
+        *token.balances.entry(account_id.clone()).or_default() += amount;
+    }
+
+    /// Returns the balance of an account in a fungible token, if it is
+    /// registered with it.
+    pub fn token_balance(&self, token_id: &AccountId, account_id: &AccountId) -> Option<u128> {
+        let state = self.state();
+        let token = state.accounts.get(token_id)?.token.as_ref()?;
+        token.balances.get(account_id).copied()
+    }
+
+    /// Creates a NEP-171 non-fungible token contract account without keys.
+    pub fn add_nft_contract(&self, contract_id: &AccountId, name: &str, base_uri: Option<&str>) {
+        self.state().accounts.insert(
+            contract_id.clone(),
+            FakeAccount {
+                nfts: Some(NftContract {
+                    metadata: serde_json::json!({
+                        "spec": "nft-1.0.0",
+                        "name": name,
+                        "symbol": name.to_uppercase(),
+                        "base_uri": base_uri,
+                    }),
+                    tokens: BTreeMap::new(),
+                }),
+                ..FakeAccount::default()
+            },
+        );
+    }
+
+    /// Mints a non-fungible token with a title and media link to an account.
+    pub fn mint_nft(
+        &self,
+        contract_id: &AccountId,
+        owner_id: &AccountId,
+        token_id: &str,
+        title: &str,
+        media: &str,
+    ) {
+        let mut state = self.state();
+        let contract = state
+            .accounts
+            .get_mut(contract_id)
+            .and_then(|account| account.nfts.as_mut())
+            .expect("the non-fungible token contract should exist");
+        contract.tokens.insert(
+            token_id.into(),
+            serde_json::json!({
+                "token_id": token_id,
+                "owner_id": owner_id,
+                "metadata": { "title": title, "media": media },
+            }),
+        );
+    }
+
+    /// Adds credentials to the keychain, without adding the key to the account.
+    pub fn add_credentials(&self, account_id: &AccountId, secret_key: SecretKey) {
+        self.state()
+            .keychain
+            .insert((account_id.clone(), secret_key.public_key()), secret_key);
+    }
+
+    /// Returns the keys the keychain holds credentials for on behalf of an account.
+    pub fn credentials(&self, account_id: &AccountId) -> Vec<PublicKey> {
+        self.state()
+            .keychain
+            .keys()
+            .filter(|(id, _)| id == account_id)
+            .map(|(_, public_key)| public_key.clone())
+            .collect()
+    }
+
+    /// Returns the balance of an account, if it exists.
+    pub fn balance(&self, account_id: &AccountId) -> Option<NearToken> {
+        let state = self.state();
+        let account = state.accounts.get(account_id)?;
+        Some(NearToken::from_yoctonear(account.balance))
+    }
+
+    /// Returns the access keys of an account, if it exists.
+    pub fn keys(&self, account_id: &AccountId) -> Option<BTreeMap<PublicKey, AccessKey>> {
+        Some(self.state().accounts.get(account_id)?.keys.clone())
+    }
+
+    /// Returns the hash of the latest block.
+    pub fn block_hash(&self) -> CryptoHash {
+        self.state().block_hash
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl State {
+    fn account(&self, account_id: &AccountId) -> Result<&FakeAccount, ChainError> {
+        self.accounts
+            .get(account_id)
+            .ok_or_else(|| UnknownAccount(account_id.clone()).into())
+    }
+
+    /// Checks a transaction and applies it, returning its outcome.
+    fn execute(
+        &mut self,
+        signed: &SignedTransaction,
+    ) -> Result<FinalExecutionOutcomeView, InvalidTxError> {
+        let hash = signed.get_hash();
+        if let Some(outcome) = self.transactions.get(&hash) {
+            return Ok(outcome.clone());
+        }
+
+        let transaction = &signed.transaction;
+        let signer_id = transaction.signer_id();
+        let public_key = transaction.public_key();
+        let signer =
+            self.accounts
+                .get(signer_id)
+                .ok_or_else(|| InvalidTxError::SignerDoesNotExist {
+                    signer_id: signer_id.clone(),
+                })?;
+        let access_key = signer.keys.get(public_key).ok_or_else(|| {
+            InvalidTxError::InvalidAccessKeyError(InvalidAccessKeyError::AccessKeyNotFound {
+                account_id: signer_id.clone(),
+                public_key: Box::new(public_key.clone()),
+            })
+        })?;
+        if !signed.signature.verify(hash.as_ref(), public_key) {
+            return Err(InvalidTxError::InvalidSignature);
+        }
+        if transaction.nonce() <= access_key.nonce {
+            return Err(InvalidTxError::InvalidNonce {
+                tx_nonce: transaction.nonce(),
+                ak_nonce: access_key.nonce,
+            });
+        }
+        check_permission(transaction, &access_key.permission)?;
+
+        // The nonce is used up even if an action fails:
+        self.accounts
+            .get_mut(signer_id)
+            .and_then(|signer| signer.keys.get_mut(public_key))
+            .expect("the access key should exist")
+            .nonce = transaction.nonce();
+
+        let status = match apply(&self.accounts, transaction) {
+            Ok(accounts) => {
+                self.accounts = accounts;
+                Ok(())
+            }
+            Err(error) => Err(TxExecutionError::ActionError(error)),
+        };
+        let outcome = outcome(signed, self.block_hash, status);
+        self.transactions.insert(hash, outcome.clone());
+        Ok(outcome)
+    }
+}
+
+/// Checks that a function-call access key permits the transaction.
+fn check_permission(
+    transaction: &Transaction,
+    permission: &AccessKeyPermission,
+) -> Result<(), InvalidTxError> {
+    let AccessKeyPermission::FunctionCall(permission) = permission else {
+        return Ok(());
+    };
+    let [Action::FunctionCall(call)] = transaction.actions() else {
+        return Err(InvalidTxError::InvalidAccessKeyError(
+            InvalidAccessKeyError::RequiresFullAccess,
+        ));
+    };
+    if transaction.receiver_id().as_str() != permission.receiver_id {
+        return Err(InvalidTxError::InvalidAccessKeyError(
+            InvalidAccessKeyError::ReceiverMismatch {
+                tx_receiver: transaction.receiver_id().clone(),
+                ak_receiver: permission.receiver_id.clone(),
+            },
+        ));
+    }
+    if !permission.method_names.is_empty() && !permission.method_names.contains(&call.method_name) {
+        return Err(InvalidTxError::InvalidAccessKeyError(
+            InvalidAccessKeyError::MethodNameMismatch {
+                method_name: call.method_name.clone(),
+            },
+        ));
+    }
+    Ok(())
+}
+
+/// Applies the actions of a transaction to a copy of the accounts.
+fn apply(
+    accounts: &BTreeMap<AccountId, FakeAccount>,
+    transaction: &Transaction,
+) -> Result<BTreeMap<AccountId, FakeAccount>, ActionError> {
+    let mut accounts = accounts.clone();
+    let signer_id = transaction.signer_id();
+    let receiver_id = transaction.receiver_id();
+    let mut created = false;
+
+    for (index, action) in transaction.actions().iter().enumerate() {
+        let error = |kind| ActionError {
+            index: Some(index as u64),
+            kind,
+        };
+        let no_permission = || {
+            error(ActionErrorKind::ActorNoPermission {
+                account_id: receiver_id.clone(),
+                actor_id: signer_id.clone(),
+            })
+        };
+        let receiver_missing = || {
+            error(ActionErrorKind::AccountDoesNotExist {
+                account_id: receiver_id.clone(),
+            })
+        };
+
+        match action {
+            Action::CreateAccount(CreateAccountAction {}) => {
+                if accounts.contains_key(receiver_id) {
+                    return Err(error(ActionErrorKind::AccountAlreadyExists {
+                        account_id: receiver_id.clone(),
+                    }));
+                }
+                if !receiver_id.is_sub_account_of(signer_id) {
+                    return Err(error(ActionErrorKind::CreateAccountNotAllowed {
+                        account_id: receiver_id.clone(),
+                        predecessor_id: signer_id.clone(),
+                    }));
+                }
+                accounts.insert(receiver_id.clone(), FakeAccount::default());
+                created = true;
+            }
+            Action::Transfer(TransferAction { deposit }) => {
+                withdraw(&mut accounts, signer_id, *deposit).map_err(error)?;
+                accounts
+                    .get_mut(receiver_id)
+                    .ok_or_else(receiver_missing)?
+                    .balance += deposit;
+            }
+            Action::AddKey(add_key) => {
+                if receiver_id != signer_id && !created {
+                    return Err(no_permission());
+                }
+                let AddKeyAction {
+                    public_key,
+                    access_key,
+                } = add_key.as_ref();
+                let receiver = accounts.get_mut(receiver_id).ok_or_else(receiver_missing)?;
+                if receiver.keys.contains_key(public_key) {
+                    return Err(error(ActionErrorKind::AddKeyAlreadyExists {
+                        account_id: receiver_id.clone(),
+                        public_key: Box::new(public_key.clone()),
+                    }));
+                }
+                receiver.keys.insert(public_key.clone(), access_key.clone());
+            }
+            Action::DeleteKey(delete_key) => {
+                if receiver_id != signer_id {
+                    return Err(no_permission());
+                }
+                let receiver = accounts.get_mut(receiver_id).ok_or_else(receiver_missing)?;
+                if receiver.keys.remove(&delete_key.public_key).is_none() {
+                    return Err(error(ActionErrorKind::DeleteKeyDoesNotExist {
+                        account_id: receiver_id.clone(),
+                        public_key: Box::new(delete_key.public_key.clone()),
+                    }));
+                }
+            }
+            Action::DeleteAccount(delete_account) => {
+                if receiver_id != signer_id {
+                    return Err(no_permission());
+                }
+                let receiver = accounts.remove(receiver_id).ok_or_else(receiver_missing)?;
+                // Sending the balance to an account that doesn't exist burns it:
+                if let Some(beneficiary) = accounts.get_mut(&delete_account.beneficiary_id) {
+                    beneficiary.balance += receiver.balance;
+                }
+            }
+            Action::FunctionCall(call) => {
+                withdraw(&mut accounts, signer_id, call.deposit).map_err(error)?;
+                if !accounts.contains_key(receiver_id) {
+                    return Err(receiver_missing());
+                }
+                call_function(&mut accounts, signer_id, receiver_id, call).map_err(error)?;
+            }
+            _ => {
+                return Err(error(ActionErrorKind::FunctionCallError(
+                    FunctionCallError::ExecutionError(format!(
+                        "{action:?} is not supported by the fake network"
+                    )),
+                )));
+            }
+        }
+    }
+
+    Ok(accounts)
+}
+
+fn withdraw(
+    accounts: &mut BTreeMap<AccountId, FakeAccount>,
+    account_id: &AccountId,
+    amount: u128,
+) -> Result<(), ActionErrorKind> {
+    let account =
+        accounts
+            .get_mut(account_id)
+            .ok_or_else(|| ActionErrorKind::AccountDoesNotExist {
+                account_id: account_id.clone(),
+            })?;
+    account.balance = account.balance.checked_sub(amount).ok_or_else(|| {
+        ActionErrorKind::LackBalanceForState {
+            account_id: account_id.clone(),
+            amount,
+        }
+    })?;
+    Ok(())
+}
+
+/// Runs the contract methods the fake network knows about.
+fn call_function(
+    accounts: &mut BTreeMap<AccountId, FakeAccount>,
+    predecessor_id: &AccountId,
+    receiver_id: &AccountId,
+    call: &FunctionCallAction,
+) -> Result<(), ActionErrorKind> {
+    let panic = |message: String| {
+        ActionErrorKind::FunctionCallError(FunctionCallError::ExecutionError(format!(
+            "Smart contract panicked: {message}"
+        )))
+    };
+
+    if accounts[receiver_id].pool.is_some() {
+        return call_staking_pool(accounts, predecessor_id, receiver_id, call).map_err(panic);
+    }
+    if accounts[receiver_id].token.is_some() {
+        return call_fungible_token(accounts, predecessor_id, receiver_id, call).map_err(panic);
+    }
+
+    match (receiver_id.as_str(), call.method_name.as_str()) {
+        ("near" | "testnet", "create_account") => {
+            #[derive(serde::Deserialize)]
+            struct Args {
+                new_account_id: AccountId,
+                new_public_key: PublicKey,
+            }
+            let args: Args = serde_json::from_slice(&call.args)
+                .map_err(|error| panic(format!("invalid arguments: {error}")))?;
+            if !args.new_account_id.is_sub_account_of(receiver_id) {
+                return Err(panic(format!(
+                    "{} is not a sub-account of {receiver_id}",
+                    args.new_account_id
+                )));
+            }
+            if accounts.contains_key(&args.new_account_id) {
+                return Err(panic(format!("account {} exists", args.new_account_id)));
+            }
+            accounts.insert(
+                args.new_account_id,
+                FakeAccount {
+                    balance: call.deposit,
+                    keys: BTreeMap::from([(args.new_public_key, AccessKey::full_access())]),
+                    ..FakeAccount::default()
+                },
+            );
+            Ok(())
+        }
+        _ => Err(ActionErrorKind::FunctionCallError(
+            FunctionCallError::MethodResolveError(MethodResolveError::MethodNotFound),
+        )),
+    }
+}
+
+/// Runs a method of the staking pool contract, failing with the message of
+/// its panic.
+fn call_staking_pool(
+    accounts: &mut BTreeMap<AccountId, FakeAccount>,
+    predecessor_id: &AccountId,
+    pool_id: &AccountId,
+    call: &FunctionCallAction,
+) -> Result<(), String> {
+    #[derive(serde::Deserialize)]
+    struct Args {
+        amount: Option<String>,
+    }
+    let args: Args = serde_json::from_slice(&call.args)
+        .map_err(|error| format!("invalid arguments: {error}"))?;
+    let amount = args
+        .amount
+        .map(|amount| amount.parse::<u128>())
+        .transpose()
+        .map_err(|error| format!("invalid amount: {error}"))?;
+
+    let pool = accounts.get_mut(pool_id).expect("the pool should exist");
+    pool.balance += call.deposit;
+    let delegation = pool
+        .pool
+        .as_mut()
+        .expect("the account should be a pool")
+        .entry(predecessor_id.clone())
+        .or_default();
+
+    let withdrawn = match call.method_name.as_str() {
+        "deposit_and_stake" => {
+            delegation.staked += call.deposit;
+            0
+        }
+        "unstake" | "unstake_all" => {
+            let amount = amount.unwrap_or(delegation.staked);
+            if amount == 0 || amount > delegation.staked {
+                return Err("Not enough staked balance to unstake".into());
+            }
+            delegation.staked -= amount;
+            delegation.unstaked += amount;
+            delegation.can_withdraw = false;
+            0
+        }
+        "withdraw" | "withdraw_all" => {
+            let amount = amount.unwrap_or(delegation.unstaked);
+            if amount == 0 || amount > delegation.unstaked {
+                return Err("Not enough unstaked balance to withdraw".into());
+            }
+            if !delegation.can_withdraw {
+                return Err(
+                    "The unstaked balance is not yet available due to unstaking delay".into(),
+                );
+            }
+            delegation.unstaked -= amount;
+            amount
+        }
+        method => return Err(format!("method {method} is not supported by the fake pool")),
+    };
+
+    pool.balance -= withdrawn;
+    accounts
+        .get_mut(predecessor_id)
+        .expect("the predecessor should exist")
+        .balance += withdrawn;
+    Ok(())
+}
+
+/// Runs a view method of the staking pool contract.
+fn view_staking_pool(
+    pool: &BTreeMap<AccountId, Delegation>,
+    method_name: &str,
+    args: &serde_json::Value,
+) -> Result<serde_json::Value, ChainError> {
+    match method_name {
+        "get_account" => {
+            let account_id: AccountId = serde_json::from_value(args["account_id"].clone())?;
+            let delegation = pool.get(&account_id).cloned().unwrap_or_default();
+            Ok(serde_json::json!({
+                "account_id": account_id,
+                "staked_balance": delegation.staked.to_string(),
+                "unstaked_balance": delegation.unstaked.to_string(),
+                "can_withdraw": delegation.can_withdraw,
+            }))
+        }
+        method => Err(format!("method {method} is not supported by the fake pool").into()),
+    }
+}
+
+/// Runs a method of the fungible token contract, failing with the message
+/// of its panic.
+fn call_fungible_token(
+    accounts: &mut BTreeMap<AccountId, FakeAccount>,
+    predecessor_id: &AccountId,
+    token_id: &AccountId,
+    call: &FunctionCallAction,
+) -> Result<(), String> {
+    #[derive(serde::Deserialize)]
+    struct Args {
+        account_id: Option<AccountId>,
+        receiver_id: Option<AccountId>,
+        amount: Option<String>,
+    }
+    let args: Args = serde_json::from_slice(&call.args)
+        .map_err(|error| format!("invalid arguments: {error}"))?;
+
+    let contract = accounts.get_mut(token_id).expect("the token should exist");
+    contract.balance += call.deposit;
+    let token = contract
+        .token
+        .as_mut()
+        .expect("the account should be a fungible token");
+
+    let refund = match call.method_name.as_str() {
+        "storage_deposit" => {
+            let account_id = args.account_id.unwrap_or_else(|| predecessor_id.clone());
+            match token.balances.entry(account_id) {
+                Entry::Occupied(_) => call.deposit,
+                Entry::Vacant(_) if call.deposit < STORAGE_DEPOSIT => {
+                    return Err(
+                        "The attached deposit is less than the minimum storage balance".into(),
+                    );
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(0);
+                    call.deposit - STORAGE_DEPOSIT
+                }
+            }
+        }
+        "ft_transfer" => {
+            if call.deposit != 1 {
+                return Err("Requires attached deposit of exactly 1 yoctoNEAR".into());
+            }
+            let receiver_id = args.receiver_id.ok_or("missing receiver_id")?;
+            let amount = args
+                .amount
+                .ok_or("missing amount")?
+                .parse::<u128>()
+                .map_err(|error| format!("invalid amount: {error}"))?;
+            if &receiver_id == predecessor_id {
+                return Err("Sender and receiver should be different".into());
+            }
+            if !token.balances.contains_key(&receiver_id) {
+                return Err(format!("The account {receiver_id} is not registered"));
+            }
+            let balance = token
+                .balances
+                .get_mut(predecessor_id)
+                .ok_or_else(|| format!("The account {predecessor_id} is not registered"))?;
+            *balance = balance
+                .checked_sub(amount)
+                .ok_or("The account doesn't have enough balance")?;
+            *token
+                .balances
+                .get_mut(&receiver_id)
+                .expect("the receiver should be registered") += amount;
+            0
+        }
+        method => {
+            return Err(format!(
+                "method {method} is not supported by the fake token"
+            ))
+        }
+    };
+
+    contract.balance -= refund;
+    accounts
+        .get_mut(predecessor_id)
+        .expect("the predecessor should exist")
+        .balance += refund;
+    Ok(())
+}
+
+/// Runs a view method of the fungible token contract.
+fn view_fungible_token(
+    token: &FungibleToken,
+    method_name: &str,
+    args: &serde_json::Value,
+) -> Result<serde_json::Value, ChainError> {
+    let account_id = || serde_json::from_value::<AccountId>(args["account_id"].clone());
+    match method_name {
+        "ft_balance_of" => {
+            let balance = token.balances.get(&account_id()?).copied();
+            Ok(serde_json::json!(balance.unwrap_or_default().to_string()))
+        }
+        "ft_metadata" => Ok(serde_json::json!({
+            "spec": "ft-1.0.0",
+            "name": token.symbol,
+            "symbol": token.symbol,
+            "decimals": token.decimals,
+        })),
+        "storage_balance_of" => Ok(match token.balances.get(&account_id()?) {
+            Some(_) => serde_json::json!({
+                "total": STORAGE_DEPOSIT.to_string(),
+                "available": "0",
+            }),
+            None => serde_json::Value::Null,
+        }),
+        "storage_balance_bounds" => Ok(serde_json::json!({
+            "min": STORAGE_DEPOSIT.to_string(),
+            "max": STORAGE_DEPOSIT.to_string(),
+        })),
+        method => Err(format!("method {method} is not supported by the fake token").into()),
+    }
+}
+
+/// Runs a view method of the non-fungible token contract.
+fn view_nft_contract(
+    contract: &NftContract,
+    method_name: &str,
+    args: &serde_json::Value,
+) -> Result<serde_json::Value, ChainError> {
+    match method_name {
+        "nft_metadata" => Ok(contract.metadata.clone()),
+        "nft_tokens_for_owner" => {
+            #[derive(serde::Deserialize)]
+            struct Args {
+                account_id: AccountId,
+                from_index: Option<String>,
+                limit: Option<usize>,
+            }
+            let args: Args = serde_json::from_value(args.clone())?;
+            let from_index = args.from_index.as_deref().unwrap_or("0").parse()?;
+            let tokens = contract
+                .tokens
+                .values()
+                .filter(|token| token["owner_id"] == args.account_id.as_str())
+                .skip(from_index)
+                .take(args.limit.unwrap_or(usize::MAX))
+                .cloned()
+                .collect();
+            Ok(serde_json::Value::Array(tokens))
+        }
+        method => Err(format!("method {method} is not supported by the fake contract").into()),
+    }
+}
+
+fn outcome(
+    signed: &SignedTransaction,
+    block_hash: CryptoHash,
+    status: Result<(), TxExecutionError>,
+) -> FinalExecutionOutcomeView {
+    let (status, execution_status) = match status {
+        Ok(()) => (
+            FinalExecutionStatus::SuccessValue(vec![]),
+            ExecutionStatusView::SuccessValue(vec![]),
+        ),
+        Err(error) => (
+            FinalExecutionStatus::Failure(error.clone()),
+            ExecutionStatusView::Failure(error),
+        ),
+    };
+    FinalExecutionOutcomeView {
+        status,
+        transaction: signed.clone().into(),
+        transaction_outcome: ExecutionOutcomeWithIdView {
+            proof: vec![],
+            block_hash,
+            id: signed.get_hash(),
+            outcome: ExecutionOutcomeView {
+                logs: vec![],
+                receipt_ids: vec![],
+                gas_burnt: 0,
+                tokens_burnt: 0,
+                executor_id: signed.transaction.signer_id().clone(),
+                status: execution_status,
+                metadata: ExecutionMetadataView::default(),
+            },
+        },
+        receipts_outcome: vec![],
+    }
+}
+
+fn tx_outcome(outcome: FinalExecutionOutcomeView, wait_until: WaitUntil) -> TxOutcome {
+    TxOutcome {
+        hash: outcome.transaction_outcome.id,
+        status: wait_until.status(),
+        outcome: (wait_until != WaitUntil::Included).then_some(outcome),
+    }
+}
+
+fn rejected(error: RpcTransactionError) -> TxError {
+    TxError::Send(RpcError::Failed(JsonRpcError::ServerError(
+        JsonRpcServerError::HandlerError(error),
+    )))
+}
+
+#[async_trait::async_trait]
+impl Chain for FakeChain {
+    async fn view_account(
+        &self,
+        _network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<AccountView, ChainError> {
+        let state = self.state();
+        let account = state.account(account_id)?;
+        Ok(AccountView {
+            amount: account.balance,
+            locked: 0,
+            code_hash: CryptoHash::default(),
+            storage_usage: 0,
+            storage_paid_at: 0,
+            global_contract_hash: None,
+            global_contract_account_id: None,
+        })
+    }
+
+    async fn access_key(
+        &self,
+        _network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<AccessKeyView, ChainError> {
+        let state = self.state();
+        let access_key = state
+            .account(account_id)?
+            .keys
+            .get(public_key)
+            .ok_or_else(|| format!("access key {public_key} does not exist"))?;
+        Ok(access_key.clone().into())
+    }
+
+    async fn list_keys(
+        &self,
+        _network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<Vec<AccessKeyInfoView>, ChainError> {
+        let state = self.state();
+        Ok(state
+            .account(account_id)?
+            .keys
+            .iter()
+            .map(|(public_key, access_key)| AccessKeyInfoView {
+                public_key: public_key.clone(),
+                access_key: access_key.clone().into(),
+            })
+            .collect())
+    }
+
+    async fn call_view(
+        &self,
+        _network: NetworkName,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: Vec<u8>,
+    ) -> Result<ViewResult, ChainError> {
+        // The fake contracts only take and return JSON:
+        let args = serde_json::from_slice(&args)?;
+        let state = self.state();
+        let contract = state.account(contract_id)?;
+        let result = match (&contract.pool, &contract.token, &contract.nfts) {
+            (Some(pool), _, _) => view_staking_pool(pool, method_name, &args),
+            (_, Some(token), _) => view_fungible_token(token, method_name, &args),
+            (_, _, Some(nfts)) => view_nft_contract(nfts, method_name, &args),
+            (None, None, None) => Err(format!("account {contract_id} has no contract").into()),
+        }?;
+        Ok(ViewResult {
+            result: serde_json::to_vec(&result)?,
+            logs: vec![],
+        })
+    }
+
+    async fn request_faucet(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<FinalExecutionOutcomeView, ChainError> {
+        let registrar: AccountId = "testnet".parse().unwrap();
+        let args = serde_json::json!({
+            "new_account_id": account_id,
+            "new_public_key": public_key,
+        });
+        let action = Action::FunctionCall(Box::new(FunctionCallAction {
+            method_name: "create_account".into(),
+            args: args.to_string().into_bytes(),
+            gas: 30_000_000_000_000,
+            deposit: FAUCET_AMOUNT.as_yoctonear(),
+        }));
+        self.faucet(network, registrar, action)
+    }
+
+    async fn request_tokens(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<FinalExecutionOutcomeView, ChainError> {
+        let action = Action::Transfer(TransferAction {
+            deposit: FAUCET_AMOUNT.as_yoctonear(),
+        });
+        self.faucet(network, account_id.clone(), action)
+    }
+
+    async fn sign(
+        &self,
+        _network: NetworkName,
+        transaction: ExecuteSignedTransaction,
+    ) -> Result<SignedTransaction, TxError> {
+        let signer_id = match &transaction.tr {
+            TransactionableOrSigned::Transactionable(tr) => tr.prepopulated().signer_id,
+            TransactionableOrSigned::Signed((signed, _)) => return Ok(signed.clone()),
+        };
+        let public_key = transaction
+            .signer
+            .get_public_key()
+            .await
+            .map_err(|error| TxError::Sign(RpcError::Failed(error.into())))?;
+        let (nonce, block_hash) = {
+            let state = self.state();
+            let nonce = state
+                .accounts
+                .get(&signer_id)
+                .and_then(|account| account.keys.get(&public_key))
+                .map_or(0, |access_key| access_key.nonce);
+            (nonce + 1, state.block_hash)
+        };
+        tx::sign_offline(transaction, public_key, nonce, block_hash).await
+    }
+
+    async fn broadcast(
+        &self,
+        _network: NetworkName,
+        signed: SignedTransaction,
+        wait_until: WaitUntil,
+    ) -> Result<TxOutcome, TxError> {
+        let outcome = self
+            .state()
+            .execute(&signed)
+            .map_err(|context| rejected(RpcTransactionError::InvalidTransaction { context }))?;
+        Ok(tx_outcome(outcome, wait_until))
+    }
+
+    async fn tx_status(
+        &self,
+        _network: NetworkName,
+        hash: CryptoHash,
+        sender: &AccountId,
+        wait_until: WaitUntil,
+    ) -> Result<TxOutcome, TxError> {
+        let state = self.state();
+        match state.transactions.get(&hash) {
+            Some(outcome) if outcome.transaction.signer_id == *sender => {
+                Ok(tx_outcome(outcome.clone(), wait_until))
+            }
+            _ => Err(rejected(RpcTransactionError::UnknownTransaction {
+                requested_transaction_hash: hash,
+            })),
+        }
+    }
+
+    async fn find_credentials(
+        &self,
+        _network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<Credentials, KeychainError> {
+        let state = self.state();
+        state
+            .accounts
+            .get(account_id)
+            .into_iter()
+            .flat_map(|account| &account.keys)
+            .filter(|(_, access_key)| access_key.permission == AccessKeyPermission::FullAccess)
+            .find_map(|(public_key, _)| {
+                state
+                    .keychain
+                    .get(&(account_id.clone(), public_key.clone()))
+            })
+            .map(|secret_key| Credentials::SecretKey(SecretKeySigner::new(secret_key.clone())))
+            .ok_or(KeychainError::NotFound(
+                SignerError::PublicKeyIsNotAvailable,
+            ))
+    }
+
+    fn credentials_for_key(
+        &self,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<Credentials, KeychainError> {
+        self.state()
+            .keychain
+            .get(&(account_id.clone(), public_key.clone()))
+            .map(|secret_key| Credentials::SecretKey(SecretKeySigner::new(secret_key.clone())))
+            .ok_or(KeychainError::NotFound(
+                SignerError::SecretKeyIsNotAvailable,
+            ))
+    }
+
+    fn save_credentials(
+        &self,
+        _network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+        key_pair: &serde_json::Value,
+    ) -> Result<(), ChainError> {
+        let secret_key: SecretKey = key_pair["private_key"]
+            .as_str()
+            .ok_or("the key pair has no private key")?
+            .parse()?;
+        if secret_key.public_key() != *public_key {
+            return Err("the private key doesn't match the public key".into());
+        }
+        self.add_credentials(account_id, secret_key);
+        Ok(())
+    }
+
+    fn forget_credentials(
+        &self,
+        _network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) {
+        self.state()
+            .keychain
+            .remove(&(account_id.clone(), public_key.clone()));
+    }
+}
@@ -0,0 +1,301 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{Chain, ChainError, UnknownAccount, ViewResult};
+use crate::{
+    keychain::{Credentials, KeychainError},
+    network_name::NetworkName,
+    rpc::{RpcError, RpcOptions},
+    tx::{self, TxError, TxOutcome, WaitUntil},
+};
+use near_api::{
+    advanced::ExecuteSignedTransaction,
+    errors::{FaucetError, QueryError, RetryError},
+    near_primitives::{
+        hash::CryptoHash,
+        transaction::SignedTransaction,
+        types::BlockReference,
+        views::{
+            AccessKeyInfoView, AccessKeyView, AccountView, FinalExecutionOutcomeView, QueryRequest,
+        },
+    },
+    Account, AccountId, Signer,
+};
+use near_crypto::PublicKey;
+use near_jsonrpc_client::{
+    errors::{JsonRpcError, JsonRpcServerError},
+    methods::query::{RpcQueryError, RpcQueryRequest},
+};
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+
+/// The real network, reached over JSON-RPC, and the OS keychain.
+#[derive(Clone, Debug, Default)]
+pub struct NearChain {
+    rpc: RpcOptions,
+}
+
+impl NearChain {
+    pub fn new(rpc: RpcOptions) -> Self {
+        Self { rpc }
+    }
+}
+
+#[async_trait::async_trait]
+impl Chain for NearChain {
+    async fn view_account(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<AccountView, ChainError> {
+        let network_config = self.rpc.network_config(network);
+        match self
+            .rpc
+            .retry(|| {
+                Account(account_id.clone())
+                    .view()
+                    .fetch_from(&network_config)
+            })
+            .await
+        {
+            Ok(response) => Ok(response.data),
+            Err(error) if is_unknown_account(&error) => {
+                Err(UnknownAccount(account_id.clone()).into())
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn access_key(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<AccessKeyView, ChainError> {
+        let network_config = self.rpc.network_config(network);
+        Ok(self
+            .rpc
+            .retry(|| {
+                Account(account_id.clone())
+                    .access_key(public_key.clone())
+                    .fetch_from(&network_config)
+            })
+            .await?
+            .data)
+    }
+
+    async fn list_keys(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<Vec<AccessKeyInfoView>, ChainError> {
+        let network_config = self.rpc.network_config(network);
+        Ok(self
+            .rpc
+            .retry(|| {
+                Account(account_id.clone())
+                    .list_keys()
+                    .fetch_from(&network_config)
+            })
+            .await?
+            .keys)
+    }
+
+    async fn call_view(
+        &self,
+        network: NetworkName,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: Vec<u8>,
+    ) -> Result<ViewResult, ChainError> {
+        // near-api only returns the decoded JSON result of view calls, so
+        // the query is made directly, keeping the raw result and the logs:
+        let client = tx::client(&self.rpc.network_config(network));
+        let response = self
+            .rpc
+            .retry(|| {
+                client.call(RpcQueryRequest {
+                    block_reference: BlockReference::latest(),
+                    request: QueryRequest::CallFunction {
+                        account_id: contract_id.clone(),
+                        method_name: method_name.into(),
+                        args: args.clone().into(),
+                    },
+                })
+            })
+            .await?;
+        match response.kind {
+            QueryResponseKind::CallResult(result) => Ok(ViewResult {
+                result: result.result,
+                logs: result.logs,
+            }),
+            kind => Err(format!("unexpected response to a view call: {kind:?}").into()),
+        }
+    }
+
+    async fn request_faucet(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<FinalExecutionOutcomeView, ChainError> {
+        let network_config = self.rpc.network_config(network);
+        let Ok(request) = Account::create_account(account_id.clone())
+            .sponsor_by_faucet_service()
+            .public_key(public_key.clone());
+
+        let response = self
+            .rpc
+            .retry(|| async {
+                request
+                    .clone()
+                    .send_to_config_faucet(&network_config)
+                    .await?
+                    .error_for_status()
+                    .map_err(FaucetError::SendError)
+            })
+            .await?;
+        Ok(response.json::<FinalExecutionOutcomeView>().await?)
+    }
+
+    async fn request_tokens(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<FinalExecutionOutcomeView, ChainError> {
+        let network_config = self.rpc.network_config(network);
+        let Some(faucet_url) = network_config.faucet_url else {
+            return Err(FaucetError::FaucetIsNotDefined(network_config.network_name).into());
+        };
+
+        // Once the faucet may have received the request, asking again could
+        // send the tokens twice, so only failures to connect are retried:
+        let client = reqwest::Client::new();
+        let response = self
+            .rpc
+            .retry_if(
+                || async {
+                    client
+                        .post(faucet_url.clone())
+                        .json(&serde_json::json!({ "accountId": account_id }))
+                        .send()
+                        .await
+                        .and_then(reqwest::Response::error_for_status)
+                        .map_err(FaucetError::SendError)
+                },
+                |error| {
+                    matches!(error, RpcError::Failed(FaucetError::SendError(error)) if error.is_connect())
+                },
+            )
+            .await?;
+        Ok(response.json::<FinalExecutionOutcomeView>().await?)
+    }
+
+    async fn sign(
+        &self,
+        network: NetworkName,
+        transaction: ExecuteSignedTransaction,
+    ) -> Result<SignedTransaction, TxError> {
+        tx::sign(&self.rpc, &self.rpc.network_config(network), transaction).await
+    }
+
+    async fn broadcast(
+        &self,
+        network: NetworkName,
+        signed: SignedTransaction,
+        wait_until: WaitUntil,
+    ) -> Result<TxOutcome, TxError> {
+        tx::broadcast(
+            &self.rpc,
+            &self.rpc.network_config(network),
+            signed,
+            wait_until,
+        )
+        .await
+    }
+
+    async fn tx_status(
+        &self,
+        network: NetworkName,
+        hash: CryptoHash,
+        sender: &AccountId,
+        wait_until: WaitUntil,
+    ) -> Result<TxOutcome, TxError> {
+        let network_config = self.rpc.network_config(network);
+        tx::status(&self.rpc, &network_config, hash, sender.clone(), wait_until).await
+    }
+
+    async fn find_credentials(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+    ) -> Result<Credentials, KeychainError> {
+        let network_config = self.rpc.network_config(network);
+        self.rpc
+            .retry(|| {
+                Signer::from_keystore_with_search_for_keys(account_id.clone(), &network_config)
+            })
+            .await
+            .map(Credentials::Keystore)
+            .map_err(KeychainError::Search)
+    }
+
+    fn credentials_for_key(
+        &self,
+        _account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<Credentials, KeychainError> {
+        Ok(Credentials::Keystore(Signer::from_keystore(
+            public_key.clone(),
+        )))
+    }
+
+    fn save_credentials(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+        key_pair: &serde_json::Value,
+    ) -> Result<(), ChainError> {
+        let config = near_cli_rs::config::Config::default();
+        let network_config = config
+            .network_connection
+            .get(network.as_str())
+            .ok_or_else(|| format!("unknown network {network}"))?;
+        near_cli_rs::common::save_access_key_to_keychain(
+            network_config.clone(),
+            &key_pair.to_string(),
+            &public_key.to_string(),
+            account_id.as_str(),
+        )
+        .map_err(|error| error.to_string())?;
+        Ok(())
+    }
+
+    fn forget_credentials(
+        &self,
+        network: NetworkName,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) {
+        let service_name = format!("near-{network}-{account_id}");
+        if let Ok(entry) = keyring::Entry::new(&service_name, &format!("{account_id}:{public_key}"))
+        {
+            entry.delete_credential().ok();
+        }
+    }
+}
+
+/// Whether a query failed because the account doesn't exist.
+fn is_unknown_account(error: &RpcError<QueryError<RpcQueryRequest>>) -> bool {
+    let RpcError::Failed(QueryError::JsonRpcError(error)) = error else {
+        return false;
+    };
+    let (RetryError::RetriesExhausted(error) | RetryError::Critical(error)) = &**error else {
+        return false;
+    };
+    matches!(
+        error,
+        JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcQueryError::UnknownAccount { .. }
+        ))
+    )
+}
@@ -1,16 +1,91 @@
 // This is free and unencumbered software released into the public domain.
 
+mod agent;
+pub use agent::*;
+
+mod alias;
+pub use alias::*;
+
+mod broadcast;
+pub use broadcast::*;
+
+mod call;
+pub use call::*;
+
+mod completions;
+pub use completions::*;
+
+mod config;
+pub use config::*;
+
 mod delete;
 pub use delete::*;
 
 mod find;
 pub use find::*;
 
+mod ft_transfer;
+pub use ft_transfer::*;
+
+mod fund;
+pub use fund::*;
+
+mod grant;
+pub use grant::*;
+
+mod history;
+pub use history::*;
+
 mod import;
 pub use import::*;
 
+mod keys_list;
+pub use keys_list::*;
+
+mod label;
+pub use label::*;
+
 mod list;
 pub use list::*;
 
+mod man;
+pub use man::*;
+
+mod migrate;
+pub use migrate::*;
+
+mod nfts;
+pub use nfts::*;
+
 mod register;
 pub use register::*;
+
+mod registry_migrate;
+pub use registry_migrate::*;
+
+mod revoke;
+pub use revoke::*;
+
+mod sign_message;
+pub use sign_message::*;
+
+mod sign_tx;
+pub use sign_tx::*;
+
+mod stake;
+pub use stake::*;
+
+mod tokens;
+pub use tokens::*;
+
+mod transfer;
+pub use transfer::*;
+
+mod tx_status;
+pub use tx_status::*;
+
+mod verify_message;
+pub use verify_message::*;
+
+mod view;
+pub use view::*;
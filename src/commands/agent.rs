@@ -0,0 +1,138 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    agent::{self, Agent, AgentOptions},
+    chain::Chain,
+    config,
+    network_name::NetworkName,
+    AccountError, StandardOptions,
+};
+use color_print::{ceprintln, cprintln};
+use near_api::{near_primitives::views::AccessKeyPermissionView, AccountId, SignerTrait as _};
+use std::{
+    os::unix::fs::{DirBuilderExt as _, MetadataExt as _, PermissionsExt as _},
+    path::Path,
+};
+use tokio::net::{UnixListener, UnixStream};
+
+#[tokio::main]
+pub async fn agent(
+    account_ids: Vec<AccountId>,
+    options: &AgentOptions,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let socket = options
+        .socket
+        .clone()
+        .or_else(agent::default_socket)
+        .ok_or(AccountError::NoHomeDir)?;
+
+    let confirm = options
+        .confirm
+        .or(config::get().confirm)
+        .unwrap_or_default();
+    let mut agent = Agent::new(confirm, flags.verbose);
+    for account_id in account_ids {
+        let network_name = NetworkName::try_from(&account_id)
+            .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+        if flags.verbose >= 2 {
+            cprintln!("<s,c>»</> Unlocking credentials for <s>{account_id}</>...");
+        }
+
+        let keys = chain
+            .list_keys(network_name, &account_id)
+            .await
+            .map_err(|error| {
+                AccountError::Rpc(format!("failed to fetch keys of {account_id}"), error)
+            })?;
+
+        let mut unlocked = 0;
+        for key in keys {
+            if key.access_key.permission != AccessKeyPermissionView::FullAccess {
+                continue;
+            }
+            let Ok(keystore) = chain.credentials_for_key(&account_id, &key.public_key) else {
+                continue;
+            };
+            if let Ok(secret_key) = keystore.get_secret_key(&account_id, &key.public_key).await {
+                agent.add_key(account_id.clone(), secret_key);
+                unlocked += 1;
+            }
+        }
+
+        if unlocked == 0 {
+            ceprintln!("<s,y>warning:</> no credentials found in keychain for <s>{account_id}</>");
+        } else if flags.verbose >= 1 {
+            cprintln!("<s,g>✓</> Unlocked {unlocked} key(s) for <s>{account_id}</>");
+        }
+    }
+
+    if agent.is_empty() {
+        return Err(AccountError::Usage("no credentials to serve".into()));
+    }
+
+    if let Some(dir) = socket.parent() {
+        std::fs::create_dir_all(dir).map_err(|error| {
+            AccountError::Write("failed to create directory for the socket".into(), error)
+        })?;
+    }
+    if socket.exists() {
+        if UnixStream::connect(&socket).await.is_ok() {
+            return Err(AccountError::Write(
+                format!("failed to listen on {}", socket.display()),
+                std::io::ErrorKind::AddrInUse.into(),
+            ));
+        }
+        // A stale socket left behind by an agent that didn't exit cleanly:
+        std::fs::remove_file(&socket).ok();
+    }
+
+    let listener = bind_private(&socket).map_err(|error| {
+        AccountError::Write(format!("failed to listen on {}", socket.display()), error)
+    })?;
+    let owner = match std::fs::metadata(&socket) {
+        Ok(metadata) => metadata.uid(),
+        Err(error) => {
+            std::fs::remove_file(&socket).ok();
+            return Err(AccountError::Read(
+                format!("failed to read {}", socket.display()),
+                error,
+            ));
+        }
+    };
+
+    println!("export {}={}", agent::SOCKET_VAR, socket.display());
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Agent is listening on {}", socket.display());
+    }
+
+    agent.serve(listener, owner, options.lifetime).await;
+
+    std::fs::remove_file(&socket).ok();
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Agent has stopped");
+    }
+
+    Ok(())
+}
+
+/// Binds a socket that only the owner may connect to. It's bound in a
+/// directory only the owner can enter and moved into place once secured, so
+/// that no one else can connect in between.
+fn bind_private(socket: &Path) -> std::io::Result<UnixListener> {
+    let file_name = socket.file_name().unwrap_or_default().to_string_lossy();
+    let dir = socket.with_file_name(format!(".{file_name}.{}", std::process::id()));
+    std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    let bound = dir.join(&*file_name);
+    let result = UnixListener::bind(&bound).and_then(|listener| {
+        std::fs::set_permissions(&bound, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&bound, socket)?;
+        Ok(listener)
+    });
+    std::fs::remove_dir_all(&dir).ok();
+    result
+}
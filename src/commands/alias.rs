@@ -0,0 +1,139 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    config::OutputFormat,
+    network_name::NetworkName,
+    registry::{self, AccountMetadata, RegistryLock, Scope},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::AccountId;
+use std::path::{Path, PathBuf};
+
+pub fn alias_set(
+    alias: &str,
+    account_id: AccountId,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    registry::check_alias(alias).map_err(AccountError::Usage)?;
+    // The alias may move from an account in the other registry:
+    let _lock = lock_registries()?;
+    let (path, mut metadata) = registered_metadata(&account_id)?;
+
+    // An alias names one account, so setting it moves it from any other:
+    if let Some(previous) = registry::aliases().remove(alias) {
+        if previous == account_id {
+            return Ok(());
+        }
+        remove_alias(&previous, alias)?;
+        if flags.verbose >= 1 {
+            cprintln!("<s,y>!</> Moved alias <s>{alias}</> from <s>{previous}</>");
+        }
+    }
+
+    metadata.aliases.push(alias.into());
+    save_metadata(&path, &metadata)?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> <s>{alias}</> is now an alias of <s>{account_id}</>");
+    }
+
+    Ok(())
+}
+
+pub fn alias_unset(alias: &str, flags: &StandardOptions) -> Result<(), AccountError> {
+    let _lock = lock_registries()?;
+    let Some(account_id) = registry::aliases().remove(alias) else {
+        return Err(AccountError::Usage(format!("there is no alias `{alias}`")));
+    };
+    remove_alias(&account_id, alias)?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Removed alias <s>{alias}</> of <s>{account_id}</>");
+    }
+
+    Ok(())
+}
+
+pub fn alias_list(output: OutputFormat, flags: &StandardOptions) -> Result<(), AccountError> {
+    let aliases = registry::aliases();
+
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&aliases)?),
+        OutputFormat::Csv => {
+            println!("alias,name");
+            for (alias, account_id) in aliases {
+                println!("{alias},{account_id}");
+            }
+        }
+        OutputFormat::Text if aliases.is_empty() => {
+            if flags.verbose >= 1 {
+                cprintln!("No aliases found");
+            }
+        }
+        OutputFormat::Text => {
+            for (alias, account_id) in aliases {
+                cprintln!("<s>{alias}</> → {account_id}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Locks the registry of a scope for a change, until the lock is dropped.
+pub(crate) fn lock_registry(scope: Scope) -> Result<RegistryLock, AccountError> {
+    registry::lock(scope)
+        .map_err(|error| AccountError::write_registry("failed to lock the registry", error))
+}
+
+/// Locks the registry that holds an account, or else the user registry.
+pub(crate) fn lock_registry_of(account_id: &AccountId) -> Result<RegistryLock, AccountError> {
+    let network_name = NetworkName::try_from(account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    lock_registry(registry::account_scope(network_name, account_id).unwrap_or_default())
+}
+
+/// Locks every registry, for a change that may touch more than one.
+pub(crate) fn lock_registries() -> Result<RegistryLock, AccountError> {
+    registry::lock_all()
+        .map_err(|error| AccountError::write_registry("failed to lock the registry", error))
+}
+
+/// Returns the registry file and metadata of an account, which must be in
+/// the registry.
+pub(crate) fn registered_metadata(
+    account_id: &AccountId,
+) -> Result<(PathBuf, AccountMetadata), AccountError> {
+    let network_name = NetworkName::try_from(account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    let path = registry::account_path(network_name, account_id).ok_or(AccountError::NoHomeDir)?;
+    match AccountMetadata::load(&path) {
+        Ok(metadata) => Ok((path, metadata)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Err(AccountError::Usage(
+            format!("{account_id} is not in the registry; import it first"),
+        )),
+        Err(error) => Err(AccountError::Read("failed to read account".into(), error)),
+    }
+}
+
+fn remove_alias(account_id: &AccountId, alias: &str) -> Result<(), AccountError> {
+    let (path, mut metadata) = registered_metadata(account_id)?;
+    metadata.aliases.retain(|other| other != alias);
+    save_metadata(&path, &metadata)
+}
+
+/// Loads the metadata of an account, if it's in the registry.
+pub(crate) fn load_metadata(path: &Path) -> Result<Option<AccountMetadata>, AccountError> {
+    match AccountMetadata::load(path) {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(AccountError::Read("failed to read account".into(), error)),
+    }
+}
+
+pub(crate) fn save_metadata(path: &Path, metadata: &AccountMetadata) -> Result<(), AccountError> {
+    metadata
+        .save(path)
+        .map_err(|error| AccountError::Write("failed to save account".into(), error))
+}
@@ -0,0 +1,72 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{forget_account, read_input};
+use crate::{
+    chain::Chain,
+    journal::{self, JournalEntry},
+    network_name::NetworkName,
+    tx::{self, WaitUntil},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::near_primitives::{action::Action, transaction::SignedTransaction};
+use std::path::Path;
+
+#[tokio::main]
+pub async fn broadcast(
+    file: &Path,
+    wait_until: WaitUntil,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let input = read_input(file)?;
+    let signed: SignedTransaction = tx::decode(&input).map_err(|error| {
+        AccountError::InvalidData(format!("failed to read signed transaction: {error}"))
+    })?;
+
+    let account_id = signed.transaction.signer_id().clone();
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    let deletes_account = signed
+        .transaction
+        .actions()
+        .iter()
+        .any(|action| matches!(action, Action::DeleteAccount(_)));
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Sending transaction {}...", signed.get_hash());
+    }
+
+    let journal_entry = JournalEntry::new("broadcast", &account_id, network_name)
+        .with_tx_hash(Some(signed.get_hash()));
+    journal::record(&journal_entry);
+
+    let outcome = match chain.broadcast(network_name, signed, wait_until).await {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            journal::record(&journal_entry.failed(&error));
+            return Err(AccountError::transaction(
+                "failed to send transaction",
+                error,
+            ));
+        }
+    };
+
+    tx::print_outcome(&outcome);
+
+    if let Some(error) = outcome.failure() {
+        journal::record(&journal_entry.failed(&error));
+        return Err(AccountError::TransactionFailed(
+            "transaction failed".into(),
+            error,
+        ));
+    }
+
+    journal::record(&journal_entry.succeeded());
+
+    if deletes_account && outcome.outcome.is_some() {
+        forget_account(network_name, &account_id)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{MethodArgs, MethodResult};
+use crate::{
+    chain::Chain,
+    config::OutputFormat,
+    journal::{self, JournalEntry},
+    keychain,
+    network_name::NetworkName,
+    tx::{self, SignOptions, WaitUntil},
+    AccountError, StandardOptions,
+};
+use color_print::{ceprintln, cprintln};
+use near_api::{
+    near_primitives::{
+        action::{Action, FunctionCallAction},
+        views::FinalExecutionStatus,
+    },
+    AccountId, NearGas, NearToken, Transaction,
+};
+
+#[tokio::main]
+#[allow(clippy::too_many_arguments)]
+pub async fn call(
+    account_id: AccountId,
+    contract_id: AccountId,
+    method_name: String,
+    args: &MethodArgs,
+    deposit: NearToken,
+    gas: NearGas,
+    wait_until: WaitUntil,
+    sign: &SignOptions,
+    output: OutputFormat,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    let context = format!("failed to call {method_name}");
+    let args = args.encode()?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Checking for credentials in keychain...");
+    }
+
+    let signer = keychain::signer(
+        chain,
+        network_name,
+        &account_id,
+        sign.public_key.as_ref(),
+        flags,
+    )
+    .await
+    .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Found credentials in keychain");
+    }
+
+    let transaction = Transaction::construct(account_id.clone(), contract_id.clone())
+        .add_action(Action::FunctionCall(Box::new(FunctionCallAction {
+            method_name: method_name.clone(),
+            args,
+            gas: gas.as_gas(),
+            deposit: deposit.as_yoctonear(),
+        })))
+        .with_signer(signer);
+    let signed = tx::sign_with(sign, chain, network_name, transaction)
+        .await
+        .map_err(|error| AccountError::transaction(&context, error))?;
+
+    if sign.sign_only {
+        println!("{}", tx::encode(&signed));
+        return Ok(());
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Calling <s>{method_name}</> on <s>{contract_id}</>...");
+    }
+
+    let journal_entry =
+        JournalEntry::new("call", &account_id, network_name).with_tx_hash(Some(signed.get_hash()));
+    journal::record(&journal_entry);
+
+    let outcome = chain.broadcast(network_name, signed, wait_until).await;
+
+    // A failed call is shown too, with its logs:
+    if let Ok(outcome) = &outcome {
+        let (result, logs) = match &outcome.outcome {
+            Some(execution) => {
+                let result = match &execution.status {
+                    FinalExecutionStatus::SuccessValue(value) => MethodResult::decode(value),
+                    _ => MethodResult::None,
+                };
+                let logs = [&execution.transaction_outcome]
+                    .into_iter()
+                    .chain(&execution.receipts_outcome)
+                    .flat_map(|execution| execution.outcome.logs.clone())
+                    .collect();
+                (result, logs)
+            }
+            None => (MethodResult::None, Vec::new()),
+        };
+        match output {
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "transaction": outcome.hash.to_string(),
+                    "status": outcome.status,
+                    "failure": outcome.failure(),
+                    "result": result,
+                    "logs": logs,
+                }))?
+            ),
+            OutputFormat::Text | OutputFormat::Csv => {
+                // The outcome includes the logs, the receipts, and the gas burnt:
+                tx::print_outcome(outcome);
+                if flags.verbose >= 1 {
+                    if let MethodResult::Base64(_) = result {
+                        ceprintln!("<s,y>!</> The result isn't JSON; showing it as base64");
+                    }
+                }
+                result.print()?;
+            }
+        }
+    }
+
+    let outcome = journal::check(outcome, journal_entry, &context)?;
+
+    if flags.verbose >= 1 {
+        cprintln!(
+            "<s,g>✓</> Called <s>{method_name}</> on <s>{contract_id}</> in transaction {}",
+            outcome.hash
+        );
+    }
+
+    Ok(())
+}
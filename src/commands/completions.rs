@@ -0,0 +1,14 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    completions::{Shell, COMPLETE_VAR},
+    AccountError, StandardOptions,
+};
+
+pub fn completions(shell: Shell, _flags: &StandardOptions) -> Result<(), AccountError> {
+    let name = "asimov-account";
+    shell
+        .completer()
+        .write_registration(COMPLETE_VAR, name, name, name, &mut std::io::stdout())
+        .map_err(|error| AccountError::Write("failed to write completions".into(), error))
+}
@@ -0,0 +1,77 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    config::{self, Config},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use std::path::Path;
+
+pub fn config_get(key: &str, flags: &StandardOptions) -> Result<(), AccountError> {
+    match config::get().get(key)? {
+        Some(value) => println!("{value}"),
+        None => {
+            if flags.verbose >= 1 {
+                cprintln!("<s>{key}</> is not set");
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn config_set(
+    path: &Path,
+    key: &str,
+    value: &str,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let mut config = Config::read(path)?;
+    config.set(key, value)?;
+    config.write(path)?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Set <s>{key}</> in {}", path.display());
+    }
+    if std::env::var_os(config::env_var(key)).is_some() && flags.verbose >= 1 {
+        cprintln!(
+            "<s,y>!</> <s>{}</> is set and takes precedence",
+            config::env_var(key)
+        );
+    }
+
+    Ok(())
+}
+
+pub fn config_unset(path: &Path, key: &str, flags: &StandardOptions) -> Result<(), AccountError> {
+    let mut config = Config::read(path)?;
+    config.unset(key)?;
+    config.write(path)?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Unset <s>{key}</> in {}", path.display());
+    }
+
+    Ok(())
+}
+
+pub fn config_list(path: &Path, flags: &StandardOptions) -> Result<(), AccountError> {
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Reading configuration at {}", path.display());
+    }
+
+    let config = config::get();
+    for (key, description) in config::KEYS {
+        let value = config.get(key)?;
+        let env_var = config::env_var(key);
+        match value {
+            Some(value) if std::env::var_os(&env_var).is_some() => {
+                cprintln!("<s>{key}</> = {value}  <dim>(from {env_var})</>");
+            }
+            Some(value) => cprintln!("<s>{key}</> = {value}"),
+            None if flags.verbose >= 1 => cprintln!("<s>{key}</>  <dim># {description}</>"),
+            None => {}
+        }
+    }
+
+    Ok(())
+}
@@ -1,89 +1,76 @@
 // This is free and unencumbered software released into the public domain.
 
+use super::lock_registry_of;
 use crate::{
+    chain::Chain,
+    journal::{self, JournalEntry},
+    keychain,
     network_name::NetworkName,
-    StandardOptions,
-    SysexitsError::{self, *},
+    registry,
+    tx::{self, SignOptions, WaitUntil},
+    AccountError, StandardOptions,
 };
-use color_print::{ceprintln, cprintln};
-use near_api::{Account, AccountId, Signer};
+use color_print::cprintln;
+use near_api::{Account, AccountId};
 
 #[tokio::main]
 pub async fn delete(
     account_id: AccountId,
     beneficiary: AccountId,
+    wait_until: WaitUntil,
+    sign: &SignOptions,
+    chain: &dyn Chain,
     flags: &StandardOptions,
-) -> Result<(), SysexitsError> {
-    let network_name = NetworkName::try_from(&account_id).map_err(|_| {
-        ceprintln!("<s,r>error:</> Unable to determine network name from the account");
-        EX_DATAERR
-    })?;
-    let network_config = network_name.config();
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
 
     if flags.verbose >= 2 {
         cprintln!("<s,c>»</> Checking for credentials in keychain...");
     }
 
-    let signer = Signer::from_keystore_with_search_for_keys(account_id.clone(), &network_config)
-        .await
-        .map_err(|error| {
-            ceprintln!("<s,r>error:</> unable to find keys for the account: {error}");
-            EX_SOFTWARE
-        })
-        .and_then(|signer| {
-            Signer::new(signer).map_err(|error| {
-                ceprintln!("<s,r>error:</> unable to find keys for the account: {error}");
-                EX_SOFTWARE
-            })
-        })?;
+    let signer = keychain::signer(
+        chain,
+        network_name,
+        &account_id,
+        sign.public_key.as_ref(),
+        flags,
+    )
+    .await
+    .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
 
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Found credentials in keychain");
     }
 
+    let transaction = Account(account_id.clone())
+        .delete_account_with_beneficiary(beneficiary)
+        .with_signer(signer);
+    let signed = tx::sign_with(sign, chain, network_name, transaction)
+        .await
+        .map_err(|error| AccountError::transaction("failed to delete account", error))?;
+
+    if sign.sign_only {
+        println!("{}", tx::encode(&signed));
+        return Ok(());
+    }
+
     if flags.verbose >= 2 {
         cprintln!("<s,g>»</> Sending delete request...");
     }
 
-    let outcome = Account(account_id.clone())
-        .delete_account_with_beneficiary(beneficiary)
-        .with_signer(signer)
-        .send_to(&network_config)
-        .await
-        .map_err(|error| {
-            ceprintln!("<s,r>error:</> failed to delete account: {error}");
-            EX_SOFTWARE
-        })?;
+    let journal_entry = JournalEntry::new("delete", &account_id, network_name)
+        .with_tx_hash(Some(signed.get_hash()));
+    journal::record(&journal_entry);
 
-    use near_api::near_primitives::views::FinalExecutionStatus;
-    if let FinalExecutionStatus::Failure(error) = outcome.status {
-        ceprintln!("<s,r>error:</> failed to delete account: {error}");
-        return Err(EX_UNAVAILABLE);
-    }
+    let outcome = chain.broadcast(network_name, signed, wait_until).await;
+    journal::check(outcome, journal_entry, "failed to delete account")?;
 
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Delete request was successful");
     }
 
-    let Some(dir) = dirs::home_dir() else {
-        ceprintln!("Unable to determine home dir");
-        return Err(EX_CONFIG);
-    };
-    let file = dir
-        .join(".asimov")
-        .join("accounts")
-        .join("near")
-        .join(network_name.as_str())
-        .join(account_id.as_str());
-    let moved_file = file.with_file_name(String::from(".") + account_id.as_str());
-    match std::fs::rename(file, moved_file) {
-        Ok(_) => (),
-        Err(error) if error.kind() == std::io::ErrorKind::NotFound => (),
-        Err(error) => {
-            ceprintln!("<s,r>error:</> failed to remove account file: {error}");
-            return Err(EX_SOFTWARE);
-        }
-    }
+    forget_account(network_name, &account_id)?;
 
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Account <s>{account_id}</> has successfully been deleted");
@@ -91,3 +78,20 @@ pub async fn delete(
 
     Ok(())
 }
+
+/// Moves the registry file of a deleted account out of the way.
+pub(crate) fn forget_account(
+    network_name: NetworkName,
+    account_id: &AccountId,
+) -> Result<(), AccountError> {
+    let file = registry::account_path(network_name, account_id).ok_or(AccountError::NoHomeDir)?;
+    let moved_file =
+        registry::deleted_path(network_name, account_id).ok_or(AccountError::NoHomeDir)?;
+    let _lock = lock_registry_of(account_id)?;
+    if !file.exists() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(moved_file.parent().unwrap())
+        .and_then(|_| std::fs::rename(file, moved_file))
+        .map_err(|error| AccountError::Write("failed to remove account file".into(), error))
+}
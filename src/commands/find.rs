@@ -1,36 +1,35 @@
 // This is free and unencumbered software released into the public domain.
 
 use crate::{
-    network_name::NetworkName,
-    StandardOptions,
-    SysexitsError::{self, *},
+    chain::Chain, keychain::KeychainError, network_name::NetworkName, AccountError, StandardOptions,
 };
-use color_print::{ceprintln, cprintln};
-use near_api::{AccountId, Signer, SignerTrait as _};
+use color_print::cprintln;
+use near_api::{AccountId, SignerTrait as _};
 
 #[tokio::main]
-pub async fn find(account_id: AccountId, flags: &StandardOptions) -> Result<(), SysexitsError> {
-    let network_name = NetworkName::try_from(&account_id).map_err(|_| {
-        ceprintln!("<s,r>error:</> unable to determine network name from the account");
-        EX_DATAERR
-    })?;
-    let network_config = network_name.config();
+pub async fn find(
+    account_id: AccountId,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
 
     if flags.verbose >= 2 {
         cprintln!("<s,c>»</> Checking for credentials in keychain...");
     }
 
-    let keychain = Signer::from_keystore_with_search_for_keys(account_id.clone(), &network_config)
+    let keychain = chain
+        .find_credentials(network_name, &account_id)
         .await
-        .map_err(|error| {
-            ceprintln!("<s,r>error:</> unable to find keys for the account: {error}");
-            EX_CONFIG
-        })?;
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
 
-    if let Err(error) = keychain.get_public_key() {
-        ceprintln!("<s,r>error:</> couldn't access credentials in keychain: {error}");
-        return Err(EX_SOFTWARE);
-    };
+    keychain.get_public_key().map_err(|error| {
+        AccountError::keychain(
+            "couldn't access credentials in keychain",
+            KeychainError::NotFound(error),
+        )
+    })?;
 
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Found credentials in keychain");
@@ -40,14 +39,15 @@ pub async fn find(account_id: AccountId, flags: &StandardOptions) -> Result<(),
         cprintln!("<s,g>»</> Checking account exists on the network");
     }
 
-    if let Err(error) = near_api::Account(account_id.clone())
-        .view()
-        .fetch_from(&network_config)
+    chain
+        .view_account(network_name, &account_id)
         .await
-    {
-        ceprintln!("<s,r>error:</> account was found locally but doesn't seem to exist on the network: {error}");
-        return Err(EX_SOFTWARE);
-    }
+        .map_err(|error| {
+            AccountError::Rpc(
+                "account was found locally but doesn't seem to exist on the network".into(),
+                error,
+            )
+        })?;
 
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Confirmed account exists");
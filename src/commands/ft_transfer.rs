@@ -0,0 +1,157 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::token_metadata;
+use crate::{
+    chain::Chain,
+    journal::{self, JournalEntry},
+    keychain,
+    nep141::{self, StorageBalance, StorageBalanceBounds},
+    network_name::NetworkName,
+    tx::{self, SignOptions, WaitUntil},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::{
+    near_primitives::action::{Action, FunctionCallAction},
+    AccountId, NearGas, NearToken, Transaction,
+};
+
+/// The gas attached to each call to the token contract.
+const CALL_GAS: NearGas = NearGas::from_tgas(30);
+
+#[tokio::main]
+#[allow(clippy::too_many_arguments)]
+pub async fn ft_transfer(
+    account_id: AccountId,
+    token_id: AccountId,
+    receiver: AccountId,
+    amount: String,
+    wait_until: WaitUntil,
+    sign: &SignOptions,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    let context = format!("failed to transfer {token_id} tokens");
+
+    let metadata = token_metadata(chain, network_name, &token_id)
+        .await
+        .map_err(|error| {
+            AccountError::Rpc(format!("failed to fetch metadata of {token_id}"), error)
+        })?;
+    let amount = nep141::parse_amount(&amount, metadata.decimals).map_err(AccountError::Usage)?;
+    let display_amount = format!(
+        "{} {}",
+        nep141::format_amount(amount, metadata.decimals),
+        metadata.symbol
+    );
+
+    // The receiver must be registered with the token contract to hold it:
+    let mut actions = Vec::new();
+    let storage_balance = chain
+        .view_function(
+            network_name,
+            &token_id,
+            "storage_balance_of",
+            &serde_json::json!({ "account_id": receiver }),
+        )
+        .await
+        .and_then(|result| Ok(serde_json::from_value::<Option<StorageBalance>>(result)?))
+        .map_err(|error| {
+            AccountError::Rpc(format!("failed to check {receiver} with {token_id}"), error)
+        })?;
+    if storage_balance.is_none() {
+        let bounds = chain
+            .view_function(
+                network_name,
+                &token_id,
+                "storage_balance_bounds",
+                &serde_json::json!({}),
+            )
+            .await
+            .and_then(|result| Ok(serde_json::from_value::<StorageBalanceBounds>(result)?))
+            .map_err(|error| {
+                AccountError::Rpc(
+                    format!("failed to fetch storage bounds of {token_id}"),
+                    error,
+                )
+            })?;
+
+        if flags.verbose >= 1 {
+            cprintln!(
+                "<s,y>!</> <s>{receiver}</> isn't registered with <s>{token_id}</>; registering it for {}",
+                bounds.min
+            );
+        }
+
+        actions.push(function_call(
+            "storage_deposit",
+            serde_json::json!({ "account_id": receiver, "registration_only": true }),
+            bounds.min,
+        ));
+    }
+    actions.push(function_call(
+        "ft_transfer",
+        serde_json::json!({ "receiver_id": receiver, "amount": amount.to_string() }),
+        NearToken::from_yoctonear(1),
+    ));
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Checking for credentials in keychain...");
+    }
+
+    let signer = keychain::signer(
+        chain,
+        network_name,
+        &account_id,
+        sign.public_key.as_ref(),
+        flags,
+    )
+    .await
+    .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Found credentials in keychain");
+    }
+
+    let transaction = Transaction::construct(account_id.clone(), token_id.clone())
+        .add_actions(actions)
+        .with_signer(signer);
+    let signed = tx::sign_with(sign, chain, network_name, transaction)
+        .await
+        .map_err(|error| AccountError::transaction(&context, error))?;
+
+    if sign.sign_only {
+        println!("{}", tx::encode(&signed));
+        return Ok(());
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Sending transfer request...");
+    }
+
+    let journal_entry = JournalEntry::new("ft-transfer", &account_id, network_name)
+        .with_tx_hash(Some(signed.get_hash()));
+    journal::record(&journal_entry);
+
+    let outcome = chain.broadcast(network_name, signed, wait_until).await;
+    journal::check(outcome, journal_entry, &context)?;
+
+    if flags.verbose >= 1 {
+        cprintln!(
+            "<s,g>✓</> Transferred {display_amount} from <s>{account_id}</> to <s>{receiver}</>"
+        );
+    }
+
+    Ok(())
+}
+
+fn function_call(method_name: &str, args: serde_json::Value, deposit: NearToken) -> Action {
+    Action::FunctionCall(Box::new(FunctionCallAction {
+        method_name: method_name.into(),
+        args: args.to_string().into_bytes(),
+        gas: CALL_GAS.as_gas(),
+        deposit: deposit.as_yoctonear(),
+    }))
+}
@@ -0,0 +1,139 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    chain::Chain,
+    config,
+    journal::{self, JournalEntry},
+    keychain,
+    network_name::NetworkName,
+    tx::WaitUntil,
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::{near_primitives::views::AccountView, AccountId, NearToken, Tokens};
+
+#[tokio::main]
+pub async fn fund(
+    account_id: AccountId,
+    sponsor: Option<AccountId>,
+    sponsor_amount: Option<NearToken>,
+    wait_until: WaitUntil,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+    // As with registrations, a configured sponsor only applies on its own network:
+    let sponsor = match sponsor.or_else(|| config::get().sponsor_on(network_name).cloned()) {
+        Some(sponsor) => match sponsor_amount.or(config::get().sponsor_amount) {
+            Some(amount) => Some((sponsor, amount)),
+            None => {
+                return Err(AccountError::Usage(
+                    "funding by a sponsor requires an amount (--sponsor-amount)".into(),
+                ))
+            }
+        },
+        None if network_name == NetworkName::Testnet => None,
+        None => {
+            return Err(AccountError::Usage(
+                "there is no faucet on mainnet, so funding requires a sponsor and an amount (--sponsor and --sponsor-amount)".into(),
+            ))
+        }
+    };
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Checking that the account exists...");
+    }
+
+    let balance = |account: AccountView| NearToken::from_yoctonear(account.amount);
+    let old_balance = chain
+        .view_account(network_name, &account_id)
+        .await
+        .map(balance)
+        .map_err(|error| AccountError::Rpc("account does not seem to exist".into(), error))?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Sending funding request...");
+    }
+
+    let journal_entry = JournalEntry::new("fund", &account_id, network_name);
+    journal::record(&journal_entry);
+
+    let result = match &sponsor {
+        None => match chain.request_tokens(network_name, &account_id).await {
+            // The faucet may respond before its transaction has been executed,
+            // so wait for it the same way as for our own transactions:
+            Ok(outcome) => chain
+                .tx_status(
+                    network_name,
+                    outcome.transaction.hash,
+                    &outcome.transaction.signer_id,
+                    wait_until,
+                )
+                .await
+                .map_err(|error| AccountError::transaction("failed to confirm funding", error)),
+            Err(error) => Err(AccountError::Rpc(
+                "failed to request tokens from the faucet".into(),
+                error,
+            )),
+        },
+        Some((sponsor, amount)) => {
+            let signer = keychain::signer(chain, network_name, sponsor, None, flags)
+                .await
+                .map_err(|error| {
+                    AccountError::keychain("unable to find keys for the sponsor account", error)
+                })?;
+            let transaction = Tokens::account(sponsor.clone())
+                .send_to(account_id.clone())
+                .near(*amount)
+                .with_signer(signer);
+            chain
+                .send(network_name, transaction, wait_until)
+                .await
+                .map_err(|error| AccountError::transaction("failed to fund account", error))
+        }
+    };
+
+    let outcome = match result {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            journal::record(&journal_entry.failed(&error));
+            return Err(error);
+        }
+    };
+    let journal_entry = journal_entry.with_tx_hash(Some(outcome.hash));
+
+    if let Some(error) = outcome.failure() {
+        journal::record(&journal_entry.failed(&error));
+        return Err(AccountError::TransactionFailed(
+            "failed to fund account".into(),
+            error,
+        ));
+    }
+
+    journal::record(&journal_entry.succeeded());
+
+    if flags.verbose >= 1 {
+        match &sponsor {
+            Some((sponsor, amount)) => {
+                cprintln!("<s,g>✓</> Sent {amount} from <s>{sponsor}</> to <s>{account_id}</>")
+            }
+            None => cprintln!("<s,g>✓</> Received tokens from the faucet"),
+        }
+    }
+
+    // A transaction that has only been included hasn't changed the balance yet:
+    if wait_until == WaitUntil::Included {
+        return Ok(());
+    }
+
+    let new_balance = chain
+        .view_account(network_name, &account_id)
+        .await
+        .map(balance)
+        .map_err(|error| AccountError::Rpc("failed to fetch the new balance".into(), error))?;
+    cprintln!("<s>{account_id}</> has {new_balance}, up from {old_balance}");
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{load_metadata, lock_registry_of, save_metadata};
+use crate::{
+    chain::Chain,
+    journal::{self, JournalEntry},
+    keychain,
+    network_name::NetworkName,
+    registry::{self, KeyMetadata},
+    tx::WaitUntil,
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::{
+    near_primitives::account::{AccessKeyPermission, FunctionCallPermission},
+    Account, AccountId, NearToken,
+};
+use near_crypto::PublicKey;
+
+#[tokio::main]
+#[allow(clippy::too_many_arguments)]
+pub async fn grant(
+    account_id: AccountId,
+    contract: AccountId,
+    methods: Vec<String>,
+    allowance: Option<NearToken>,
+    wait_until: WaitUntil,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    let registry_path =
+        registry::account_path(network_name, &account_id).ok_or(AccountError::NoHomeDir)?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Checking for credentials in keychain...");
+    }
+
+    let signer = keychain::signer(chain, network_name, &account_id, None, flags)
+        .await
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Found credentials in keychain");
+    }
+
+    let key_pair_properties = near_cli_rs::common::generate_keypair().map_err(|error| {
+        AccountError::Software(format!("failed to generate credentials: {error}"))
+    })?;
+    let public_key: PublicKey = key_pair_properties
+        .public_key_str
+        .parse()
+        .map_err(|error| {
+            AccountError::Software(format!("failed to generate credentials: {error}"))
+        })?;
+
+    // The keychain entry carries the key's scope so that it can be told
+    // apart from full-access credentials:
+    let mut key_pair_properties_json = serde_json::to_value(&key_pair_properties)?;
+    key_pair_properties_json["scope"] = serde_json::json!({
+        "contract": contract,
+        "methods": methods,
+        "allowance": allowance,
+    });
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Saving credentials to keychain...");
+    }
+
+    if let Err(error) = chain.save_credentials(
+        network_name,
+        &account_id,
+        &public_key,
+        &key_pair_properties_json,
+    ) {
+        return Err(AccountError::Software(format!(
+            "failed to save credentials to keychain: {error}"
+        )));
+    }
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Saved credentials to keychain");
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Sending add key request...");
+    }
+
+    let permission = AccessKeyPermission::FunctionCall(FunctionCallPermission {
+        allowance: allowance.map(|allowance| allowance.as_yoctonear()),
+        receiver_id: contract.to_string(),
+        method_names: methods.clone(),
+    });
+    let transaction = Account(account_id.clone())
+        .add_key(permission, public_key.clone())
+        .with_signer(signer);
+
+    let journal_entry = JournalEntry::new("grant", &account_id, network_name);
+    journal::record(&journal_entry);
+
+    let outcome = match chain.send(network_name, transaction, wait_until).await {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            journal::record(&journal_entry.failed(&error));
+            chain.forget_credentials(network_name, &account_id, &public_key);
+            return Err(AccountError::transaction("failed to add key", error));
+        }
+    };
+    let journal_entry = journal_entry.with_tx_hash(Some(outcome.hash));
+
+    if let Some(error) = outcome.failure() {
+        journal::record(&journal_entry.failed(&error));
+        chain.forget_credentials(network_name, &account_id, &public_key);
+        return Err(AccountError::TransactionFailed(
+            "failed to add key".into(),
+            error,
+        ));
+    }
+
+    journal::record(&journal_entry.succeeded());
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Added key {public_key}");
+    }
+
+    let _lock = lock_registry_of(&account_id)?;
+    // Only accounts in the registry remember their keys:
+    if let Some(mut metadata) = load_metadata(&registry_path)? {
+        metadata.keys.push(KeyMetadata {
+            public_key: public_key.clone(),
+            contract,
+            methods,
+            allowance,
+            granted: chrono::Utc::now(),
+        });
+        save_metadata(&registry_path, &metadata)?;
+    }
+
+    println!("{public_key}");
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    config::OutputFormat,
+    journal::{self, JournalStatus},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::AccountId;
+
+pub fn history(
+    account_id: Option<AccountId>,
+    command: Option<String>,
+    network: Option<String>,
+    status: Option<JournalStatus>,
+    limit: Option<usize>,
+    output: OutputFormat,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    if output == OutputFormat::Csv {
+        return Err(AccountError::Usage(
+            "CSV output is only supported by `list`".into(),
+        ));
+    }
+
+    if flags.verbose >= 2 {
+        if let Some(path) = journal::path() {
+            cprintln!(
+                "<s,c>»</> Reading transaction journal at {}",
+                path.display()
+            );
+        }
+    }
+
+    let entries = journal::read()
+        .map_err(|error| AccountError::Read("failed to read transaction journal".into(), error))?;
+
+    // Filter on the current status of each transaction, not on the entries
+    // that led to it:
+    let entries = journal::latest(entries)
+        .into_iter()
+        .filter(|entry| account_id.as_ref().is_none_or(|id| entry.account == *id))
+        .filter(|entry| {
+            command
+                .as_ref()
+                .is_none_or(|command| entry.command == *command)
+        })
+        .filter(|entry| {
+            network
+                .as_ref()
+                .is_none_or(|network| entry.network == *network)
+        })
+        .filter(|entry| status.is_none_or(|status| entry.status == status))
+        .collect::<Vec<_>>();
+
+    let skip = limit.map_or(0, |limit| entries.len().saturating_sub(limit));
+    let entries = entries.into_iter().skip(skip).collect::<Vec<_>>();
+
+    if output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        if flags.verbose >= 1 {
+            cprintln!("No transactions found");
+        }
+        return Ok(());
+    }
+
+    for entry in entries {
+        let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M:%S");
+        let status = match entry.status {
+            JournalStatus::Pending => color_print::cformat!("<y>{:<9}</>", entry.status),
+            JournalStatus::Succeeded => color_print::cformat!("<g>{:<9}</>", entry.status),
+            JournalStatus::Failed => color_print::cformat!("<r>{:<9}</>", entry.status),
+        };
+        let tx_hash = entry
+            .tx_hash
+            .map(|hash| hash.to_string())
+            .unwrap_or_default();
+        cprintln!(
+            "{timestamp}  {:<8}  {status}  <s>{}</> ({})  {tx_hash}",
+            entry.command,
+            entry.account,
+            entry.network
+        );
+        if let Some(error) = entry.error {
+            cprintln!("    <r>error:</> {error}");
+        }
+    }
+
+    Ok(())
+}
@@ -1,37 +1,44 @@
 // This is free and unencumbered software released into the public domain.
 
-use near_api::{AccountId, Signer, SignerTrait as _};
+use near_api::{AccountId, SignerTrait as _};
 
+use super::{lock_registry, save_metadata};
 use crate::{
+    chain::Chain,
+    keychain::KeychainError,
     network_name::NetworkName,
-    StandardOptions,
-    SysexitsError::{self, *},
+    registry::{self, AccountMetadata, Scope},
+    AccountError, StandardOptions,
 };
-use color_print::{ceprintln, cprintln};
+use chrono::Utc;
+use color_print::cprintln;
+use std::path::PathBuf;
 
 #[tokio::main]
-pub async fn import(account_id: AccountId, flags: &StandardOptions) -> Result<(), SysexitsError> {
-    let network_name = NetworkName::try_from(&account_id).map_err(|_| {
-        ceprintln!("<s,r>error:</> Unable to determine network name from the account");
-        EX_DATAERR
-    })?;
-    let network_config = network_name.config();
+pub async fn import(
+    account_id: AccountId,
+    scope: Scope,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
 
     if flags.verbose >= 2 {
         cprintln!("<s,c>»</> Checking for credentials in keychain...");
     }
 
-    let keychain = Signer::from_keystore_with_search_for_keys(account_id.clone(), &network_config)
+    let keychain = chain
+        .find_credentials(network_name, &account_id)
         .await
-        .map_err(|error| {
-            ceprintln!("<s,r>error:</> unable to find keys for the account: {error}");
-            EX_CONFIG
-        })?;
-
-    if let Err(error) = keychain.get_public_key() {
-        ceprintln!("<s,r>error:</> couldn't access credentials in keychain: {error}");
-        return Err(EX_SOFTWARE);
-    };
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+
+    keychain.get_public_key().map_err(|error| {
+        AccountError::keychain(
+            "couldn't access credentials in keychain",
+            KeychainError::NotFound(error),
+        )
+    })?;
 
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Found credentials in keychain");
@@ -41,14 +48,10 @@ pub async fn import(account_id: AccountId, flags: &StandardOptions) -> Result<()
         cprintln!("<s,c>»</> Verifying account exists on network...");
     }
 
-    if let Err(error) = near_api::Account(account_id.clone())
-        .view()
-        .fetch_from(&network_config)
+    chain
+        .view_account(network_name, &account_id)
         .await
-    {
-        ceprintln!("<s,r>error:</> account doesn't exist on the network: {error}");
-        return Err(EX_SOFTWARE);
-    }
+        .map_err(|error| AccountError::Rpc("account doesn't exist on the network".into(), error))?;
 
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Verified account exists on network");
@@ -58,42 +61,46 @@ pub async fn import(account_id: AccountId, flags: &StandardOptions) -> Result<()
         cprintln!("<s,c>»</> Saving account info locally...");
     }
 
-    let Some(dir) = dirs::home_dir() else {
-        ceprintln!("<s,r>error:</> Unable to determine home directory");
-        return Err(EX_CONFIG);
-    };
-
-    let dir = dir
-        .join(".asimov")
-        .join("accounts")
-        .join("near")
-        .join(network_name.as_str());
-
-    if let Err(error) = std::fs::create_dir_all(&dir) {
-        ceprintln!("<s,r>error:</> failed to create directory for saving accounts: {error}");
-        return Err(EX_CANTCREAT);
-    }
-
-    let account_file = dir.join(account_id.as_str());
+    let (account_file, existed) = add_to_registry(scope, network_name, &account_id)?;
 
-    if account_file.exists() {
-        if flags.verbose >= 1 {
-            cprintln!(
-                "<s,y>!</> Account already exists locally at {}",
-                account_file.display()
-            );
-        }
+    if existed {
+        cprintln!(
+            "<s,y>!</> Account already exists locally at {}, kept its metadata",
+            account_file.display()
+        );
         return Ok(());
     }
 
-    if let Err(error) = std::fs::File::create(&account_file) {
-        ceprintln!("<s,r>error:</> failed to save account: {error}");
-        return Err(EX_CANTCREAT);
-    }
-
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Imported account to {}", account_file.display());
     }
 
     Ok(())
 }
+
+/// Adds an account to the registry of a scope. An entry that is already there, e.g.
+/// one written concurrently by another process, keeps its metadata.
+/// Returns the registry file and whether the entry was already there.
+pub(crate) fn add_to_registry(
+    scope: Scope,
+    network_name: NetworkName,
+    account_id: &AccountId,
+) -> Result<(PathBuf, bool), AccountError> {
+    let path = registry::scoped_account_path(scope, network_name, account_id)
+        .ok_or(AccountError::NoHomeDir)?;
+    let _lock = lock_registry(scope)?;
+    registry::create(scope)
+        .map_err(|error| AccountError::write_registry("failed to create the registry", error))?;
+    // An existing entry is only read, to report one that can't be:
+    match AccountMetadata::load(&path) {
+        Ok(_) => return Ok((path, true)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => return Err(AccountError::Read("failed to read account".into(), error)),
+    }
+    let metadata = AccountMetadata {
+        created: Some(Utc::now()),
+        ..Default::default()
+    };
+    save_metadata(&path, &metadata)?;
+    Ok((path, false))
+}
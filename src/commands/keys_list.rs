@@ -0,0 +1,86 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    chain::Chain,
+    config::OutputFormat,
+    network_name::NetworkName,
+    registry::{self, AccountMetadata},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::{near_primitives::views::AccessKeyPermissionView, AccountId, NearToken};
+
+#[tokio::main]
+pub async fn keys_list(
+    account_id: AccountId,
+    output: OutputFormat,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    if output == OutputFormat::Csv {
+        return Err(AccountError::Usage(
+            "CSV output is only supported by `list`".into(),
+        ));
+    }
+
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    let metadata = registry::account_path(network_name, &account_id)
+        .and_then(|path| AccountMetadata::load(&path).ok())
+        .unwrap_or_default();
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Fetching keys of <s>{account_id}</>...");
+    }
+
+    let keys = chain
+        .list_keys(network_name, &account_id)
+        .await
+        .map_err(|error| AccountError::Rpc("failed to fetch keys".into(), error))?;
+
+    if output == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&keys)?);
+        return Ok(());
+    }
+
+    for key in keys {
+        match key.access_key.permission {
+            AccessKeyPermissionView::FullAccess => {
+                cprintln!("<s>{}</> <r>full access</>", key.public_key);
+            }
+            AccessKeyPermissionView::FunctionCall {
+                allowance,
+                receiver_id,
+                method_names,
+            } => {
+                let methods = if method_names.is_empty() {
+                    "any method".to_string()
+                } else {
+                    method_names.join(", ")
+                };
+                let allowance = match allowance {
+                    Some(allowance) => {
+                        format!("{} remaining", NearToken::from_yoctonear(allowance))
+                    }
+                    None => "unlimited".to_string(),
+                };
+                cprintln!(
+                    "<s>{}</> <g>function call</> to {receiver_id} ({methods}), allowance {allowance}",
+                    key.public_key
+                );
+                if let Some(granted) = metadata.key(&key.public_key) {
+                    let initial = granted
+                        .allowance
+                        .map(|allowance| allowance.to_string())
+                        .unwrap_or_else(|| "unlimited".into());
+                    cprintln!(
+                        "  granted {} with allowance {initial}",
+                        granted.granted.format("%Y-%m-%d %H:%M:%S")
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
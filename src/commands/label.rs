@@ -0,0 +1,58 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{lock_registry_of, registered_metadata, save_metadata};
+use crate::{AccountError, StandardOptions};
+use color_print::cprintln;
+use near_api::AccountId;
+
+pub fn label_add(
+    account_id: AccountId,
+    labels: Vec<String>,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    if let Some(label) = labels.iter().find(|label| !is_valid(label)) {
+        return Err(AccountError::Usage(format!(
+            "invalid label `{label}`: labels may not be empty or contain spaces, commas, or semicolons"
+        )));
+    }
+
+    let _lock = lock_registry_of(&account_id)?;
+    let (path, mut metadata) = registered_metadata(&account_id)?;
+    for label in labels {
+        if !metadata.labels.contains(&label) {
+            metadata.labels.push(label);
+        }
+    }
+    save_metadata(&path, &metadata)?;
+
+    if flags.verbose >= 1 {
+        cprintln!(
+            "<s,g>✓</> <s>{account_id}</> is labelled {}",
+            metadata.labels.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+pub fn label_remove(
+    account_id: AccountId,
+    labels: Vec<String>,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let _lock = lock_registry_of(&account_id)?;
+    let (path, mut metadata) = registered_metadata(&account_id)?;
+    metadata.labels.retain(|label| !labels.contains(label));
+    save_metadata(&path, &metadata)?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Removed label(s) from <s>{account_id}</>");
+    }
+
+    Ok(())
+}
+
+/// Labels are listed separated by commas in tables and semicolons in CSV.
+fn is_valid(label: &str) -> bool {
+    !label.is_empty() && !label.contains(|c: char| c.is_whitespace() || c == ',' || c == ';')
+}
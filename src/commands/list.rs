@@ -2,95 +2,414 @@
 
 use std::{
     collections::{BTreeMap, BTreeSet},
-    fs,
+    path::Path,
 };
 
+use super::{pool_balance, PoolBalance};
 use crate::{
-    StandardOptions,
-    SysexitsError::{self, *},
+    chain::{Chain, UnknownAccount},
+    config::{self, OutputFormat},
+    network_name::NetworkName,
+    registry::{self, AccountMetadata, Scope},
+    AccountError, StandardOptions,
 };
+use chrono::{DateTime, Utc};
+use clientele::crates::clap::ValueEnum;
 use color_print::{ceprintln, cprintln};
-use near_api::AccountId;
+use futures::{future, stream, StreamExt as _};
+use near_api::{AccountId, NearToken};
+use serde::Serialize;
 
-pub fn list(flags: &StandardOptions) -> Result<(), SysexitsError> {
-    let Some(home_dir) = dirs::home_dir() else {
-        ceprintln!("<s,r>error:</> Unable to determine home directory");
-        return Err(EX_CONFIG);
-    };
+/// How many accounts to fetch from the network at once.
+const CONCURRENT_FETCHES: usize = 8;
+
+/// The order of the accounts in a listing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ListOrder {
+    /// By network, then name.
+    #[default]
+    Name,
+    /// Oldest first, by when the account was added to the registry.
+    Created,
+    /// Largest balance first.
+    Balance,
+}
 
-    let base_path = home_dir.join(".asimov").join("accounts").join("near");
+/// Whether the keychain can sign for an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyStatus {
+    /// The keychain holds credentials for a full-access key of the account.
+    Available,
+    /// The keychain holds no credentials for the account's full-access keys.
+    Missing,
+    /// The account does not exist on the network.
+    AccountNotFound,
+}
 
-    if !base_path.exists() {
-        if flags.verbose >= 1 {
-            cprintln!("No accounts found");
+impl KeyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Available => "available",
+            Self::Missing => "missing",
+            Self::AccountNotFound => "account-not-found",
         }
-        return Ok(());
     }
+}
 
-    if flags.verbose >= 2 {
-        cprintln!(
-            "<s,c>»</> Searching for accounts in {}",
-            base_path.display()
-        );
+impl std::fmt::Display for KeyStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Available => "available",
+            Self::Missing => "missing",
+            Self::AccountNotFound => "account not found",
+        })
     }
+}
 
-    let mut networks: BTreeMap<String, BTreeSet<AccountId>> = BTreeMap::default();
-
-    let dir = fs::read_dir(&base_path).map_err(|error| {
-        ceprintln!(
-            "<s,r>error:</> failed to read accounts directory: {}",
-            error
-        );
-        EX_IOERR
-    })?;
+/// An account in a long listing.
+#[derive(Clone, Debug, Serialize)]
+struct Row {
+    name: AccountId,
+    network: NetworkName,
+    /// The registry the account is in.
+    scope: Scope,
+    /// Whether this is the configured sponsor of its network.
+    active: bool,
+    created: Option<DateTime<Utc>>,
+    aliases: Vec<String>,
+    labels: Vec<String>,
+    balance: Option<NearToken>,
+    /// The balance in the staking pools the account staked with.
+    staked: Option<NearToken>,
+    /// The balance in each of those pools.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stakes: Vec<PoolBalance>,
+    keys: Option<KeyStatus>,
+    #[serde(skip)]
+    pools: Vec<AccountId>,
+}
 
-    for network in dir.flatten() {
-        let network_path = network.path();
-        if !network_path.is_dir() {
-            continue;
-        }
+#[tokio::main]
+#[allow(clippy::too_many_arguments)]
+pub async fn list(
+    patterns: Vec<String>,
+    network: Option<NetworkName>,
+    labels: Vec<String>,
+    long: bool,
+    order: ListOrder,
+    output: OutputFormat,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let networks = match network {
+        Some(network_name) => vec![network_name],
+        None => NetworkName::value_variants().to_vec(),
+    };
 
-        let Some(network_name) = network_path.file_name().and_then(|name| name.to_str()) else {
-            continue;
-        };
+    let mut rows = Vec::new();
+    for network_name in networks {
+        let active = config::get().sponsor_on(network_name);
+        let mut seen = BTreeSet::new();
+        for scope in [Scope::Project, Scope::User] {
+            if scope == Scope::Project && registry::project_path().is_none() {
+                continue;
+            }
+            if flags.verbose >= 2 {
+                if let Some(path) = registry::network_path(scope, network_name) {
+                    cprintln!("<s,c>»</> Searching for accounts in {}", path.display());
+                }
+            }
 
-        let network_dir = fs::read_dir(&network_path).map_err(|error| {
-            ceprintln!("<s,r>error:</> failed to read network subdirectory: {error}");
-            EX_IOERR
-        })?;
+            let accounts = registry::scoped_accounts(scope, network_name).map_err(|error| {
+                AccountError::read_registry("failed to read accounts directory", error)
+            })?;
+            rows.extend(
+                accounts
+                    .into_iter()
+                    // An account in the project registry hides the user's entry:
+                    .filter(|account_id| seen.insert(account_id.clone()))
+                    .map(|account_id| {
+                        let path = registry::scoped_account_path(scope, network_name, &account_id);
+                        let metadata = path
+                            .as_deref()
+                            .and_then(|path| AccountMetadata::load(path).ok())
+                            .unwrap_or_default();
+                        Row {
+                            active: active == Some(&account_id),
+                            created: metadata
+                                .created
+                                .or_else(|| path.as_deref().and_then(created)),
+                            aliases: metadata.aliases,
+                            labels: metadata.labels,
+                            pools: metadata.pools,
+                            name: account_id,
+                            network: network_name,
+                            scope,
+                            balance: None,
+                            staked: None,
+                            stakes: Vec::new(),
+                            keys: None,
+                        }
+                    })
+                    // Patterns match the name of an account or any of its aliases:
+                    .filter(|row| {
+                        patterns.is_empty()
+                            || patterns.iter().any(|pattern| {
+                                std::iter::once(row.name.as_str())
+                                    .chain(row.aliases.iter().map(String::as_str))
+                                    .any(|name| glob_matches(pattern, name))
+                            })
+                    })
+                    .filter(|row| labels.iter().all(|label| row.labels.contains(label))),
+            );
+        }
+    }
 
-        let accounts = network_dir
-            .flatten()
-            .filter(|file| file.file_type().is_ok_and(|ft| ft.is_file()))
-            .filter_map(|account| account.file_name().to_str()?.parse::<AccountId>().ok())
-            .collect::<BTreeSet<AccountId>>();
+    // Balances and key statuses are only fetched when they are shown or sorted by:
+    if long || order == ListOrder::Balance {
+        if flags.verbose >= 2 {
+            cprintln!(
+                "<s,c>»</> Fetching {} account(s) from the network...",
+                rows.len()
+            );
+        }
+        rows = stream::iter(rows)
+            .map(|row| fetch(row, chain, flags))
+            .buffered(CONCURRENT_FETCHES)
+            .collect()
+            .await;
+    }
 
-        if accounts.is_empty() {
-            continue;
+    match order {
+        ListOrder::Name => {
+            rows.sort_by(|a, b| (a.network.as_str(), &a.name).cmp(&(b.network.as_str(), &b.name)))
         }
+        ListOrder::Created => rows.sort_by_key(|row| row.created),
+        ListOrder::Balance => rows.sort_by_key(|row| std::cmp::Reverse(row.balance)),
+    }
 
-        networks
-            .entry(network_name.to_owned())
-            .or_default()
-            .extend(accounts);
+    match (output, long) {
+        (OutputFormat::Json, true) => println!("{}", serde_json::to_string_pretty(&rows)?),
+        (OutputFormat::Json, false) => {
+            let mut networks: BTreeMap<&str, BTreeSet<AccountId>> = BTreeMap::default();
+            for row in rows {
+                networks
+                    .entry(row.network.as_str())
+                    .or_default()
+                    .insert(row.name);
+            }
+            println!("{}", serde_json::to_string_pretty(&networks)?);
+        }
+        (OutputFormat::Csv, _) => print_csv(&rows, long),
+        _ if rows.is_empty() => {
+            if flags.verbose >= 1 {
+                cprintln!("No accounts found");
+            }
+        }
+        (OutputFormat::Text, true) => print_table(&rows),
+        (OutputFormat::Text, false) => {
+            let mut network = None;
+            for row in rows {
+                if network != Some(row.network) {
+                    cprintln!("<s,b>{}</> accounts:", row.network);
+                    network = Some(row.network);
+                }
+                match row.scope {
+                    Scope::Project => cprintln!("  {} <dim>(project)</>", row.name),
+                    Scope::User => cprintln!("  {}", row.name),
+                }
+            }
+        }
     }
 
-    if networks.is_empty() {
-        if flags.verbose >= 1 {
-            cprintln!("No accounts found");
+    Ok(())
+}
+
+/// Fills in the balances and key status of an account from the network,
+/// leaving them empty if the network can't be reached.
+async fn fetch(mut row: Row, chain: &dyn Chain, flags: &StandardOptions) -> Row {
+    let account = match chain.view_account(row.network, &row.name).await {
+        Ok(account) => account,
+        Err(error) if error.is::<UnknownAccount>() => {
+            row.keys = Some(KeyStatus::AccountNotFound);
+            return row;
+        }
+        Err(error) => {
+            if flags.verbose >= 1 {
+                ceprintln!(
+                    "<s,y>!</> Failed to fetch <s>{}</> from the network: {error}",
+                    row.name
+                );
+            }
+            return row;
+        }
+    };
+    row.balance = Some(NearToken::from_yoctonear(account.amount));
+    row.keys = Some(match chain.find_credentials(row.network, &row.name).await {
+        Ok(_) => KeyStatus::Available,
+        Err(_) => KeyStatus::Missing,
+    });
+    if !row.pools.is_empty() {
+        let balances = future::try_join_all(
+            row.pools
+                .iter()
+                .map(|pool_id| pool_balance(chain, row.network, &row.name, pool_id)),
+        )
+        .await;
+        if let Ok(balances) = balances {
+            row.staked = Some(
+                balances
+                    .iter()
+                    .fold(NearToken::from_yoctonear(0), |sum, balance| {
+                        sum.saturating_add(balance.total())
+                    }),
+            );
+            row.stakes = balances;
         }
-        return Ok(());
     }
+    row
+}
+
+/// Returns when an account was added to the registry, going by its file,
+/// for entries that don't record it.
+fn created(path: &Path) -> Option<DateTime<Utc>> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let time = metadata.created().or_else(|_| metadata.modified()).ok()?;
+    Some(time.into())
+}
 
-    for (network_name, accounts) in networks {
-        if accounts.is_empty() {
-            continue;
+fn print_table(rows: &[Row]) {
+    let cells = rows
+        .iter()
+        .map(|row| {
+            [
+                format!("{} {}", if row.active { "*" } else { " " }, row.name),
+                row.network.to_string(),
+                row.scope.to_string(),
+                row.balance
+                    .map(|balance| balance.to_string())
+                    .unwrap_or_default(),
+                row.staked
+                    .map(|staked| staked.to_string())
+                    .unwrap_or_default(),
+                row.keys.map(|keys| keys.to_string()).unwrap_or_default(),
+                row.created
+                    .map(|created| created.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default(),
+                row.aliases.join(", "),
+                row.labels.join(", "),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let header = [
+        "  NAME", "NETWORK", "SCOPE", "BALANCE", "STAKED", "KEYS", "CREATED", "ALIASES", "LABELS",
+    ];
+    let widths = header.map(str::len);
+    let widths = cells.iter().fold(widths, |mut widths, cells| {
+        for (width, cell) in widths.iter_mut().zip(cells) {
+            *width = (*width).max(cell.chars().count());
         }
-        cprintln!("<s,b>{network_name}</> accounts:");
-        for account in accounts {
-            cprintln!("  {account}")
+        widths
+    });
+
+    let line = |cells: [&str; 9]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    cprintln!("<s>{}</>", line(header));
+    for (row, cells) in rows.iter().zip(&cells) {
+        println!("{}", line(cells.each_ref().map(String::as_str)));
+        // The staked balance is broken down by pool under the account:
+        for stake in &row.stakes {
+            cprintln!(
+                "<dim>    {}: {} staked, {} unstaked, {} withdrawable</>",
+                stake.pool,
+                stake.staked,
+                stake.unstaked,
+                stake.withdrawable
+            );
         }
     }
+}
 
-    Ok(())
+fn print_csv(rows: &[Row], long: bool) {
+    if !long {
+        println!("name,network,scope");
+        for row in rows {
+            println!("{},{},{}", row.name, row.network, row.scope);
+        }
+        return;
+    }
+
+    println!("name,network,scope,active,balance,staked,keys,created,aliases,labels");
+    for row in rows {
+        let fields = [
+            row.name.to_string(),
+            row.network.to_string(),
+            row.scope.to_string(),
+            row.active.to_string(),
+            row.balance
+                .map(|balance| balance.as_yoctonear().to_string())
+                .unwrap_or_default(),
+            row.staked
+                .map(|staked| staked.as_yoctonear().to_string())
+                .unwrap_or_default(),
+            row.keys
+                .map(|keys| keys.as_str().to_string())
+                .unwrap_or_default(),
+            row.created
+                .map(|created| created.to_rfc3339())
+                .unwrap_or_default(),
+            row.aliases.join(";"),
+            row.labels.join(";"),
+        ];
+        let fields = fields.map(|field| csv_field(&field));
+        println!("{}", fields.join(","));
+    }
+}
+
+/// Quotes a CSV field if it needs to be.
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Matches a name against a glob pattern, where `*` matches any run of
+/// characters and `?` any one character.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.as_bytes(), name.as_bytes());
+    let (mut p, mut n) = (0, 0);
+    // Where to resume after the last `*`, if the match after it fails:
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    n = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
 }
@@ -0,0 +1,49 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{man, AccountError, StandardOptions};
+use clientele::crates::clap::Command;
+use color_print::cprintln;
+use std::path::Path;
+
+pub fn man(
+    command: Command,
+    path: Vec<String>,
+    dir: Option<&Path>,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    if let Some(dir) = dir {
+        if !path.is_empty() {
+            return Err(AccountError::Usage(
+                "either name a command or write all pages with --dir".into(),
+            ));
+        }
+        if flags.verbose >= 2 {
+            cprintln!("<s,c>»</> Writing manual pages to {}...", dir.display());
+        }
+        std::fs::create_dir_all(dir).map_err(|error| {
+            AccountError::Write(format!("failed to create {}", dir.display()), error)
+        })?;
+        let files = man::generate_to(command, dir).map_err(|error| {
+            AccountError::Write(
+                format!("failed to write manual pages to {}", dir.display()),
+                error,
+            )
+        })?;
+        if flags.verbose >= 1 {
+            cprintln!(
+                "<s,g>✓</> Wrote {} manual pages to {}",
+                files.len(),
+                dir.display()
+            );
+        }
+        return Ok(());
+    }
+
+    let path = path.join(" ");
+    let page = man::pages(command)
+        .into_iter()
+        .find(|page| page.path == path)
+        .ok_or_else(|| AccountError::Usage(format!("no manual page for `{path}`")))?;
+    page.render(&mut std::io::stdout())
+        .map_err(|error| AccountError::Write("failed to write the manual page".into(), error))
+}
@@ -0,0 +1,440 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{
+    forget_account, load_metadata, lock_registries, pool_balance,
+    register::{create_account, generate_key_pair, holds_credentials},
+    save_metadata,
+};
+use crate::{
+    chain::{Chain, UnknownAccount},
+    config::Config,
+    journal::{self, JournalEntry},
+    keychain,
+    migration::{self, Migration, MigrationStep},
+    network_name::NetworkName,
+    registry::{self, AccountMetadata},
+    tx::WaitUntil,
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::{
+    near_primitives::{
+        account::{AccessKey, AccessKeyPermission},
+        action::{Action, AddKeyAction},
+        views::AccessKeyPermissionView,
+    },
+    Account, AccountId, NearToken, SignerTrait as _, Tokens, Transaction,
+};
+use std::path::Path;
+
+/// The amount the old account funds the new one with when registering it.
+/// The rest of its balance follows in the transfer.
+const REGISTRATION_AMOUNT: NearToken = NearToken::from_millinear(10);
+
+/// The amount the old account keeps after the transfer, besides what its
+/// storage costs, to pay for deleting itself.
+const FEE_RESERVE: NearToken = NearToken::from_millinear(10);
+
+/// The cost of storing a byte of state, in yoctoNEAR.
+const STORAGE_PRICE_PER_BYTE: u128 = 10_000_000_000_000_000_000;
+
+#[tokio::main]
+pub async fn migrate(
+    old: AccountId,
+    new: AccountId,
+    copy_keys: bool,
+    wait_until: WaitUntil,
+    config_path: &Path,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name =
+        NetworkName::try_from(&old).map_err(|_| AccountError::UnknownNetwork(old.clone()))?;
+    if NetworkName::try_from(&new).ok() != Some(network_name) {
+        return Err(AccountError::Usage(format!(
+            "{new} must be on the same network as {old}"
+        )));
+    }
+    if old == new {
+        return Err(AccountError::Usage(
+            "an account can't be migrated to itself".into(),
+        ));
+    }
+
+    let log_path = migration::path(network_name, &old).ok_or(AccountError::NoHomeDir)?;
+    let save = |migration: &Migration| {
+        migration
+            .save(&log_path)
+            .map_err(|error| AccountError::Write("failed to save the migration log".into(), error))
+    };
+
+    let mut migration = match Migration::load(&log_path)
+        .map_err(|error| AccountError::Read("failed to read the migration log".into(), error))?
+    {
+        Some(migration) if migration.new != new => {
+            return Err(AccountError::Usage(format!(
+                "{old} is already being migrated to {}; run `migrate {old} {}` to continue",
+                migration.new, migration.new
+            )));
+        }
+        Some(mut migration) => {
+            if flags.verbose >= 1 {
+                cprintln!(
+                    "<s,y>!</> Resuming migration of <s>{old}</> to <s>{new}</> after {} step(s)",
+                    migration.completed.len()
+                );
+            }
+            migration.copy_keys |= copy_keys;
+            migration
+        }
+        None => Migration::new(old.clone(), new.clone(), copy_keys),
+    };
+    save(&migration)?;
+
+    if migration.remaining().contains(&MigrationStep::Delete) {
+        check_pools(network_name, &old, &new, chain, flags).await?;
+    }
+
+    for step in migration.remaining() {
+        if flags.verbose >= 2 {
+            cprintln!("<s,c>»</> Migration step: {step}...");
+        }
+
+        match step {
+            MigrationStep::Register => {
+                register(network_name, &old, &new, wait_until, chain, flags).await?
+            }
+            MigrationStep::Transfer => {
+                transfer(
+                    network_name,
+                    &mut migration,
+                    wait_until,
+                    &save,
+                    chain,
+                    flags,
+                )
+                .await?
+            }
+            MigrationStep::CopyKeys => {
+                copy_keys_to(network_name, &old, &new, wait_until, chain, flags).await?
+            }
+            MigrationStep::Delete => {
+                delete(network_name, &old, &new, wait_until, chain, flags).await?
+            }
+            MigrationStep::UpdateRegistry => update_registry(
+                network_name,
+                &old,
+                &new,
+                migration.copy_keys,
+                config_path,
+                flags,
+            )?,
+        }
+
+        migration.completed.push(step);
+        save(&migration)?;
+    }
+
+    std::fs::remove_file(&log_path).ok();
+
+    cprintln!("<s,g>✓</> Migrated <s>{old}</> to <s>{new}</>");
+
+    Ok(())
+}
+
+/// Registers the new account, unless an interrupted migration already did.
+async fn register(
+    network_name: NetworkName,
+    old: &AccountId,
+    new: &AccountId,
+    wait_until: WaitUntil,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    if chain.view_account(network_name, new).await.is_ok() {
+        if !holds_credentials(network_name, new, chain).await {
+            return Err(AccountError::AccountExists(new.clone()));
+        }
+        return Ok(());
+    }
+
+    // The new account is kept in the same registry as the old one:
+    let scope = registry::account_scope(network_name, old).unwrap_or_default();
+    let key_pair = generate_key_pair()?;
+    create_account(
+        network_name,
+        new,
+        Some((old.clone(), REGISTRATION_AMOUNT)),
+        &key_pair,
+        wait_until,
+        scope,
+        chain,
+        flags,
+    )
+    .await
+}
+
+/// Transfers the balance of the old account to the new one, except what the
+/// old one needs until it is deleted.
+async fn transfer(
+    network_name: NetworkName,
+    migration: &mut Migration,
+    wait_until: WaitUntil,
+    save: &dyn Fn(&Migration) -> Result<(), AccountError>,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let (old, new) = (migration.old.clone(), migration.new.clone());
+
+    // An interrupted migration may have sent the transfer already:
+    if let Some(hash) = migration.transfer_hash {
+        if let Ok(outcome) = chain.tx_status(network_name, hash, &old, wait_until).await {
+            if outcome.failure().is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    let account = chain
+        .view_account(network_name, &old)
+        .await
+        .map_err(|error| AccountError::Rpc(format!("failed to fetch {old}"), error))?;
+    let reserve = (account.storage_usage as u128)
+        .saturating_mul(STORAGE_PRICE_PER_BYTE)
+        .saturating_add(FEE_RESERVE.as_yoctonear());
+    let amount = NearToken::from_yoctonear(account.amount.saturating_sub(reserve));
+    if amount.is_zero() {
+        return Ok(());
+    }
+
+    let signer = keychain::signer(chain, network_name, &old, None, flags)
+        .await
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+    let transaction = Tokens::account(old.clone())
+        .send_to(new.clone())
+        .near(amount)
+        .with_signer(signer);
+    let signed = chain
+        .sign(network_name, transaction)
+        .await
+        .map_err(|error| AccountError::transaction("failed to transfer the balance", error))?;
+
+    migration.transfer_hash = Some(signed.get_hash());
+    save(migration)?;
+
+    let journal_entry =
+        JournalEntry::new("migrate", &old, network_name).with_tx_hash(migration.transfer_hash);
+    journal::record(&journal_entry);
+    let outcome = chain.broadcast(network_name, signed, wait_until).await;
+    journal::check(outcome, journal_entry, "failed to transfer the balance")?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Transferred {amount} from <s>{old}</> to <s>{new}</>");
+    }
+
+    Ok(())
+}
+
+/// Adds the function-call keys of the old account that the new one lacks,
+/// along with their credentials in the keychain.
+async fn copy_keys_to(
+    network_name: NetworkName,
+    old: &AccountId,
+    new: &AccountId,
+    wait_until: WaitUntil,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let new_keys = chain
+        .list_keys(network_name, new)
+        .await
+        .map_err(|error| AccountError::Rpc(format!("failed to fetch keys of {new}"), error))?;
+    let missing = chain
+        .list_keys(network_name, old)
+        .await
+        .map_err(|error| AccountError::Rpc(format!("failed to fetch keys of {old}"), error))?
+        .into_iter()
+        .filter(|key| {
+            matches!(
+                key.access_key.permission,
+                AccessKeyPermissionView::FunctionCall { .. }
+            )
+        })
+        .filter(|key| {
+            !new_keys
+                .iter()
+                .any(|new_key| new_key.public_key == key.public_key)
+        })
+        .collect::<Vec<_>>();
+
+    for key in &missing {
+        let Ok(credentials) = chain.credentials_for_key(old, &key.public_key) else {
+            continue;
+        };
+        if let Ok(secret_key) = credentials.get_secret_key(old, &key.public_key).await {
+            let key_pair = serde_json::json!({
+                "public_key": key.public_key,
+                "private_key": secret_key,
+            });
+            chain
+                .save_credentials(network_name, new, &key.public_key, &key_pair)
+                .map_err(|error| {
+                    AccountError::Software(format!(
+                        "failed to save credentials to keychain: {error}"
+                    ))
+                })?;
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let signer = keychain::signer(chain, network_name, new, None, flags)
+        .await
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+    let actions = missing
+        .iter()
+        .map(|key| {
+            Action::AddKey(Box::new(AddKeyAction {
+                public_key: key.public_key.clone(),
+                access_key: AccessKey {
+                    nonce: 0,
+                    permission: AccessKeyPermission::from(key.access_key.permission.clone()),
+                },
+            }))
+        })
+        .collect();
+    let transaction = Transaction::construct(new.clone(), new.clone())
+        .add_actions(actions)
+        .with_signer(signer);
+
+    let journal_entry = JournalEntry::new("migrate", new, network_name);
+    journal::record(&journal_entry);
+    let outcome = chain.send(network_name, transaction, wait_until).await;
+    journal::check(outcome, journal_entry, "failed to copy keys")?;
+
+    if flags.verbose >= 1 {
+        cprintln!(
+            "<s,g>✓</> Copied {} function-call key(s) to <s>{new}</>",
+            missing.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks that the old account has nothing left in the staking pools it
+/// staked with, which would be lost when it is deleted.
+async fn check_pools(
+    network_name: NetworkName,
+    old: &AccountId,
+    new: &AccountId,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let path = registry::account_path(network_name, old).ok_or(AccountError::NoHomeDir)?;
+    let pools = load_metadata(&path)?
+        .map(|metadata| metadata.pools)
+        .unwrap_or_default();
+
+    for pool_id in &pools {
+        if flags.verbose >= 2 {
+            cprintln!("<s,c>»</> Checking the balance of <s>{old}</> in <s>{pool_id}</>...");
+        }
+        let balance = pool_balance(chain, network_name, old, pool_id)
+            .await
+            .map_err(|error| {
+                AccountError::Rpc(format!("failed to fetch the balance in {pool_id}"), error)
+            })?;
+        if !balance.total().is_zero() {
+            return Err(AccountError::NotPermitted(format!(
+                "{old} still has {} in the staking pool {pool_id}, which would be lost when it is deleted; unstake and withdraw it, then run `migrate {old} {new}` to continue",
+                balance.total()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes the old account with the new one as beneficiary, unless an
+/// interrupted migration already did.
+async fn delete(
+    network_name: NetworkName,
+    old: &AccountId,
+    new: &AccountId,
+    wait_until: WaitUntil,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    match chain.view_account(network_name, old).await {
+        Ok(_) => {}
+        Err(error) if error.is::<UnknownAccount>() => return Ok(()),
+        Err(error) => return Err(AccountError::Rpc(format!("failed to fetch {old}"), error)),
+    }
+
+    let signer = keychain::signer(chain, network_name, old, None, flags)
+        .await
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+    let transaction = Account(old.clone())
+        .delete_account_with_beneficiary(new.clone())
+        .with_signer(signer);
+
+    let journal_entry = JournalEntry::new("migrate", old, network_name);
+    journal::record(&journal_entry);
+    let outcome = chain.send(network_name, transaction, wait_until).await;
+    journal::check(outcome, journal_entry, "failed to delete account")?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Deleted <s>{old}</>");
+    }
+
+    Ok(())
+}
+
+/// Moves the registry entry of the old account to the new one, and points
+/// the configured sponsor at the new account if it was the old one.
+fn update_registry(
+    network_name: NetworkName,
+    old: &AccountId,
+    new: &AccountId,
+    copy_keys: bool,
+    config_path: &Path,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let old_path = registry::account_path(network_name, old).ok_or(AccountError::NoHomeDir)?;
+    let new_path = registry::account_path(network_name, new).ok_or(AccountError::NoHomeDir)?;
+
+    // The new account takes over the aliases and labels of the old one, and
+    // its keys if they were copied, but not its pools, where it has no stake:
+    {
+        let _lock = lock_registries()?;
+        if let Some(metadata) = load_metadata(&old_path)? {
+            let mut new_metadata = load_metadata(&new_path)?.unwrap_or_default();
+            new_metadata.merge(AccountMetadata {
+                keys: if copy_keys { metadata.keys } else { Vec::new() },
+                aliases: metadata.aliases,
+                labels: metadata.labels,
+                ..Default::default()
+            });
+            save_metadata(&new_path, &new_metadata)?;
+        }
+    }
+    forget_account(network_name, old)?;
+
+    let mut config = Config::read(config_path)?;
+    if config.sponsor.as_ref() == Some(old) {
+        config.sponsor = Some(new.clone());
+        config.write(config_path)?;
+        if flags.verbose >= 1 {
+            cprintln!("<s,g>✓</> Set the sponsor to <s>{new}</>");
+        }
+    }
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Saved account to {}", new_path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::csv_field;
+use crate::{
+    chain::{Chain, ChainError},
+    config::OutputFormat,
+    nep171::{self, NftContractMetadata, Token},
+    network_name::NetworkName,
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use futures::future;
+use near_api::AccountId;
+use serde::Serialize;
+
+/// How many tokens to fetch from a contract at once.
+const PAGE_SIZE: usize = 50;
+
+/// A non-fungible token held by an account.
+#[derive(Clone, Debug, Serialize)]
+pub struct NftHolding {
+    pub contract: AccountId,
+    pub token_id: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// The link to the media of the token, resolved against the base URI
+    /// of the contract.
+    pub media: Option<String>,
+}
+
+#[tokio::main]
+pub async fn nfts(
+    account_id: AccountId,
+    contracts: Vec<AccountId>,
+    output: OutputFormat,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+    if flags.verbose >= 2 {
+        cprintln!(
+            "<s,c>»</> Fetching the tokens of <s>{account_id}</> in {} contract(s)...",
+            contracts.len()
+        );
+    }
+
+    let holdings = future::try_join_all(
+        contracts
+            .iter()
+            .map(|contract_id| nft_holdings(chain, network_name, &account_id, contract_id)),
+    )
+    .await
+    .map_err(|error| AccountError::Rpc("failed to fetch the tokens".into(), error))?
+    .concat();
+
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&holdings)?),
+        OutputFormat::Csv => {
+            println!("contract,token_id,title,media");
+            for holding in &holdings {
+                let fields = [
+                    holding.contract.to_string(),
+                    holding.token_id.clone(),
+                    holding.title.clone().unwrap_or_default(),
+                    holding.media.clone().unwrap_or_default(),
+                ];
+                let fields = fields.map(|field| csv_field(&field));
+                println!("{}", fields.join(","));
+            }
+        }
+        _ if holdings.is_empty() => {
+            if flags.verbose >= 1 {
+                cprintln!("No tokens found");
+            }
+        }
+        OutputFormat::Text => {
+            let mut contract = None;
+            for holding in &holdings {
+                if contract != Some(&holding.contract) {
+                    cprintln!("<s,b>{}</> tokens:", holding.contract);
+                    contract = Some(&holding.contract);
+                }
+                match &holding.title {
+                    Some(title) => cprintln!("  {} <s>{}</>", holding.token_id, title),
+                    None => cprintln!("  {}", holding.token_id),
+                }
+                if let Some(media) = &holding.media {
+                    cprintln!("    <dim>{}</>", media);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches the tokens an account holds in a contract, page by page.
+pub async fn nft_holdings(
+    chain: &dyn Chain,
+    network_name: NetworkName,
+    account_id: &AccountId,
+    contract_id: &AccountId,
+) -> Result<Vec<NftHolding>, ChainError> {
+    let result = chain
+        .view_function(
+            network_name,
+            contract_id,
+            "nft_metadata",
+            &serde_json::json!({}),
+        )
+        .await?;
+    let contract: NftContractMetadata = serde_json::from_value(result)?;
+
+    let mut tokens = Vec::new();
+    loop {
+        let result = chain
+            .view_function(
+                network_name,
+                contract_id,
+                "nft_tokens_for_owner",
+                &serde_json::json!({
+                    "account_id": account_id,
+                    "from_index": tokens.len().to_string(),
+                    "limit": PAGE_SIZE,
+                }),
+            )
+            .await?;
+        let page: Vec<Token> = serde_json::from_value(result)?;
+        let done = page.len() < PAGE_SIZE;
+        tokens.extend(page);
+        if done {
+            break;
+        }
+    }
+
+    Ok(tokens
+        .into_iter()
+        .map(|token| {
+            let metadata = token.metadata.unwrap_or_default();
+            NftHolding {
+                contract: contract_id.clone(),
+                token_id: token.token_id,
+                title: metadata.title,
+                description: metadata.description,
+                media: metadata
+                    .media
+                    .map(|media| nep171::media_url(contract.base_uri.as_deref(), &media)),
+            }
+        })
+        .collect())
+}
@@ -1,186 +1,495 @@
 // This is free and unencumbered software released into the public domain.
 
+use super::add_to_registry;
 use crate::{
+    chain::Chain,
+    config::{self, ConfigError},
+    journal::{self, JournalEntry},
+    keychain,
     network_name::NetworkName,
-    StandardOptions,
-    SysexitsError::{self, *},
+    prompt::Prompt,
+    registry::{self, Scope},
+    tx::{self, WaitUntil},
+    AccountError, StandardOptions,
 };
 use color_print::{ceprintln, cprintln};
-use near_api::{AccountId, NearToken, Signer};
+use near_api::{AccountId, NearToken, SignerTrait as _};
+use near_cli_rs::common::KeyPairProperties;
 use near_crypto::PublicKey;
+use std::io::BufRead;
 
 #[tokio::main]
 pub async fn register(
     account_id: AccountId,
     sponsor: Option<AccountId>,
     sponsor_amount: Option<NearToken>,
+    wait_until: WaitUntil,
+    scope: Scope,
+    chain: &dyn Chain,
     flags: &StandardOptions,
-) -> Result<(), SysexitsError> {
-    let key_pair_properties = near_cli_rs::common::generate_keypair().map_err(|error| {
-        ceprintln!("<s,r>error:</> failed to generate credentials: {error}");
-        EX_SOFTWARE
-    })?;
-    let public_key: PublicKey = key_pair_properties
-        .public_key_str
-        .parse()
-        .map_err(|error| {
-            ceprintln!("<s,r>error:</> failed to generate credentials: {error}");
-            EX_SOFTWARE
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+    let sponsor = resolve_sponsor(network_name, sponsor, sponsor_amount)?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Checking whether the account already exists...");
+    }
+
+    if chain.view_account(network_name, &account_id).await.is_ok() {
+        // A previous run may have been interrupted after the account was
+        // created, in which case its credentials are already in the keychain:
+        if !holds_credentials(network_name, &account_id, chain).await {
+            return Err(AccountError::AccountExists(account_id));
+        }
+
+        if flags.verbose >= 1 {
+            cprintln!("<s,y>!</> Account already exists with credentials in keychain, resuming registration");
+        }
+
+        return save_account(network_name, &account_id, scope, flags);
+    }
+
+    let key_pair = generate_key_pair()?;
+    create_account(
+        network_name,
+        &account_id,
+        sponsor,
+        &key_pair,
+        wait_until,
+        scope,
+        chain,
+        flags,
+    )
+    .await
+}
+
+/// Returns whether the keychain has credentials for an existing account
+/// whose public key is one of the account's access keys, i.e. whether we
+/// created the account.
+pub(crate) async fn holds_credentials(
+    network_name: NetworkName,
+    account_id: &AccountId,
+    chain: &dyn Chain,
+) -> bool {
+    let Ok(keychain) = chain.find_credentials(network_name, account_id).await else {
+        return false;
+    };
+    let Ok(public_key) = keychain.get_public_key() else {
+        return false;
+    };
+    chain
+        .access_key(network_name, account_id, &public_key)
+        .await
+        .is_ok()
+}
+
+/// Registers an account, asking on the terminal for the name, network,
+/// and funding that the options don't give.
+#[tokio::main]
+#[allow(clippy::too_many_arguments)]
+pub async fn register_interactive(
+    account_id: Option<AccountId>,
+    sponsor: Option<AccountId>,
+    sponsor_amount: Option<NearToken>,
+    wait_until: WaitUntil,
+    scope: Scope,
+    input: &mut dyn BufRead,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let mut prompt = Prompt::new(input);
+    let cancelled = || AccountError::Usage("registration was cancelled".into());
+
+    let network_name = match &account_id {
+        Some(account_id) => NetworkName::try_from(account_id)
+            .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?,
+        None => {
+            let networks = [NetworkName::Testnet, NetworkName::Mainnet];
+            let default = config::get().network.unwrap_or(NetworkName::Testnet);
+            let choices = networks.map(|network_name| network_name.to_string());
+            let choice = prompt.choose(
+                "Which network should the account be on?",
+                &choices,
+                networks
+                    .iter()
+                    .position(|&network_name| network_name == default)
+                    .unwrap_or(0),
+            )?;
+            networks[choice]
+        }
+    };
+
+    let account_id = ask_name(&mut prompt, network_name, account_id, chain).await?;
+
+    let funded_by_faucet = match (network_name, &sponsor) {
+        (NetworkName::Testnet, None) => {
+            let choices = ["the testnet faucet".into(), "a sponsor account".into()];
+            let default = match config::get().sponsor_on(network_name).cloned() {
+                Some(_) => 1,
+                None => 0,
+            };
+            prompt.choose("How should the account be funded?", &choices, default)? == 0
+        }
+        _ => false,
+    };
+
+    let sponsor = if funded_by_faucet {
+        None
+    } else {
+        let (sponsor, balance) =
+            ask_sponsor(&mut prompt, network_name, &account_id, sponsor, chain).await?;
+        let default = sponsor_amount
+            .or(config::get().sponsor_amount)
+            .map(|amount| amount.to_string());
+        let amount = prompt.ask_with(
+            &format!("How much should {sponsor} fund it with? {sponsor} has {balance}"),
+            default.as_deref(),
+            |answer| match answer.parse::<NearToken>() {
+                Ok(amount) if amount.is_zero() => Err("the amount must be more than zero".into()),
+                Ok(amount) if amount > balance => Err(format!("{sponsor} only has {balance}")),
+                Ok(amount) => Ok(amount),
+                Err(error) => Err(format!(
+                    "`{answer}` isn't an amount such as `0.1 NEAR`: {error}"
+                )),
+            },
+        )?;
+        Some((sponsor, amount))
+    };
+
+    let funding = match &sponsor {
+        Some((sponsor, amount)) => format!("{amount} from {sponsor}"),
+        None => "tokens from the testnet faucet".into(),
+    };
+    if !prompt.confirm(
+        &format!("Register {account_id} on {network_name} with {funding}?"),
+        true,
+    )? {
+        return Err(cancelled());
+    }
+
+    let key_pair = generate_key_pair()?;
+    ceprintln!("\nThe seed phrase of the credentials for <s>{account_id}</> is:\n");
+    ceprintln!("    <s>{}</>\n", key_pair.master_seed_phrase);
+    ceprintln!("It recovers the credentials if they are lost from the keychain. Write it down and keep it somewhere safe.\n");
+    if !prompt.confirm("Have you backed up the seed phrase?", false)? {
+        return Err(cancelled());
+    }
+
+    create_account(
+        network_name,
+        &account_id,
+        sponsor,
+        &key_pair,
+        wait_until,
+        scope,
+        chain,
+        flags,
+    )
+    .await
+}
+
+/// Asks for the name of the account until it is available, starting with
+/// the given name. A name without a network suffix gets the network's.
+async fn ask_name(
+    prompt: &mut Prompt<'_>,
+    network_name: NetworkName,
+    mut account_id: Option<AccountId>,
+    chain: &dyn Chain,
+) -> Result<AccountId, AccountError> {
+    let suffix = match network_name {
+        NetworkName::Testnet => "testnet",
+        NetworkName::Mainnet => "near",
+    };
+    loop {
+        let account_id = match account_id.take() {
+            Some(account_id) => account_id,
+            None => prompt.ask_with("What should the account be named?", None, |answer| {
+                let name = match answer.contains('.') {
+                    true => answer.to_string(),
+                    false => format!("{answer}.{suffix}"),
+                };
+                let account_id = name
+                    .parse::<AccountId>()
+                    .map_err(|error| format!("`{name}` isn't a valid account name: {error}"))?;
+                match NetworkName::try_from(&account_id) {
+                    Ok(account_network) if account_network == network_name => Ok(account_id),
+                    _ => Err(format!("{account_id} isn't a {network_name} account")),
+                }
+            })?,
+        };
+
+        if chain.view_account(network_name, &account_id).await.is_ok() {
+            ceprintln!("<s,y>!</> {account_id} is already taken");
+            continue;
+        }
+        ceprintln!("<s,g>✓</> {account_id} is available");
+        return Ok(account_id);
+    }
+}
+
+/// Asks for the sponsor of a registration among the configured sponsor and
+/// the accounts in the registry, returning it with its balance.
+async fn ask_sponsor(
+    prompt: &mut Prompt<'_>,
+    network_name: NetworkName,
+    account_id: &AccountId,
+    sponsor: Option<AccountId>,
+    chain: &dyn Chain,
+) -> Result<(AccountId, NearToken), AccountError> {
+    let balance = async |sponsor: &AccountId| {
+        chain
+            .view_account(network_name, sponsor)
+            .await
+            .ok()
+            .map(|account| NearToken::from_yoctonear(account.amount))
+    };
+
+    let mut candidates = Vec::<(AccountId, NearToken)>::new();
+    for candidate in sponsor
+        .into_iter()
+        .chain(config::get().sponsor_on(network_name).cloned())
+        .chain(registry::accounts(network_name).unwrap_or_default())
+    {
+        if &candidate == account_id || candidates.iter().any(|(known, _)| *known == candidate) {
+            continue;
+        }
+        // Only accounts that exist can sponsor:
+        if let Some(balance) = balance(&candidate).await {
+            candidates.push((candidate, balance));
+        }
+    }
+
+    if !candidates.is_empty() {
+        let mut choices = candidates
+            .iter()
+            .map(|(candidate, balance)| format!("{candidate} ({balance})"))
+            .collect::<Vec<_>>();
+        choices.push("another account".into());
+        let choice = prompt.choose("Which account should sponsor it?", &choices, 0)?;
+        if let Some(candidate) = candidates.get(choice) {
+            return Ok(candidate.clone());
+        }
+    }
+
+    loop {
+        let sponsor = prompt.ask_with("What is the name of the sponsor?", None, |answer| {
+            answer
+                .parse::<AccountId>()
+                .map_err(|error| format!("`{answer}` isn't a valid account name: {error}"))
         })?;
-    let key_pair_properties_buf = serde_json::to_string(&key_pair_properties)?;
-    let config = near_cli_rs::config::Config::default();
-    let network_name = NetworkName::try_from(&account_id).map_err(|_| {
-            ceprintln!(
-                "<s,r>error:</> unable to determine network name from the account <s>{account_id}</>. The account must end with either <s>.near</> for mainnet or <s>.testnet</> for testnet accounts.",
-            );
-            EX_USAGE
+        match balance(&sponsor).await {
+            Some(balance) => return Ok((sponsor, balance)),
+            None => ceprintln!("<s,y>!</> {sponsor} doesn't exist on {network_name}"),
+        }
+    }
+}
 
-    })?;
-    let api_network_config = network_name.config();
-    let Some(cli_network_config) = config.network_connection.get(network_name.as_str()) else {
-        return Err(EX_SOFTWARE);
+/// Resolves the sponsor of a registration from the options and the
+/// configuration, or returns `None` if the testnet faucet sponsors it.
+fn resolve_sponsor(
+    network_name: NetworkName,
+    sponsor: Option<AccountId>,
+    sponsor_amount: Option<NearToken>,
+) -> Result<Option<(AccountId, NearToken)>, AccountError> {
+    // A configured sponsor only applies to registrations on its own network,
+    // and only together with a configured amount:
+    let (sponsor, sponsor_amount) = match (sponsor, sponsor_amount) {
+        (None, None) => match config::get().sponsor_on(network_name).cloned() {
+            Some(sponsor) => match config::get().sponsor_amount {
+                Some(amount) => (Some(sponsor), Some(amount)),
+                None => {
+                    return Err(ConfigError::MissingValue(
+                        "sponsor-amount".into(),
+                        format!("required to sponsor registrations with {sponsor}"),
+                    )
+                    .into())
+                }
+            },
+            None => (None, None),
+        },
+        (sponsor, sponsor_amount) => (sponsor, sponsor_amount),
     };
 
+    match (network_name, sponsor, sponsor_amount) {
+        (NetworkName::Testnet, None, None) => Ok(None),
+        (_, Some(sponsor), Some(amount)) => Ok(Some((sponsor, amount))),
+        (_, Some(_), None) | (_, None, Some(_)) => Err(AccountError::Usage(
+            "options --sponsor and --sponsor-amount are required together".into(),
+        )),
+        (NetworkName::Mainnet, _, _) => Err(AccountError::Usage(
+            "mainnet account registration requires a sponsor and an amount to be specified (--sponsor and --sponsor-amount, or --interactive)".into(),
+        )),
+    }
+}
+
+pub(crate) fn generate_key_pair() -> Result<KeyPairProperties, AccountError> {
+    near_cli_rs::common::generate_keypair()
+        .map_err(|error| AccountError::Software(format!("failed to generate credentials: {error}")))
+}
+
+/// Creates an account with the given credentials, funded by the sponsor or
+/// the testnet faucet, and saves it to the registry of the scope.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn create_account(
+    network_name: NetworkName,
+    account_id: &AccountId,
+    sponsor: Option<(AccountId, NearToken)>,
+    key_pair: &KeyPairProperties,
+    wait_until: WaitUntil,
+    scope: Scope,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let public_key: PublicKey = key_pair.public_key_str.parse().map_err(|error| {
+        AccountError::Software(format!("failed to generate credentials: {error}"))
+    })?;
+    let key_pair_properties_json = serde_json::to_value(key_pair)?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Saving credentials to keychain...");
+    }
+
+    // The credentials are saved before the account is created so that an
+    // interrupted registration can be resumed by running it again:
+    if let Err(error) = chain.save_credentials(
+        network_name,
+        account_id,
+        &public_key,
+        &key_pair_properties_json,
+    ) {
+        return Err(AccountError::Software(format!(
+            "failed to save credentials to keychain: {error}"
+        )));
+    }
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Saved credentials to keychain");
+    }
+
     if flags.verbose >= 2 {
         cprintln!("<s,c>»</> Sending registration request...");
     }
 
-    use near_api::near_primitives::views::{FinalExecutionOutcomeView, FinalExecutionStatus};
-    let outcome: FinalExecutionOutcomeView = match (&network_name, sponsor, sponsor_amount) {
-        (NetworkName::Testnet, None, None) => {
-            let result = near_api::Account::create_account(account_id.clone())
-                .sponsor_by_faucet_service()
-                .public_key(public_key)
-                .map_err(|_| EX_SOFTWARE)?
-                .send_to_config_faucet(&api_network_config)
+    let journal_entry = JournalEntry::new("register", account_id, network_name);
+    journal::record(&journal_entry);
+
+    let result: Result<tx::TxOutcome, AccountError> = match sponsor {
+        None => match chain
+            .request_faucet(network_name, account_id, &public_key)
+            .await
+        {
+            // The faucet may respond before its transaction has been executed,
+            // so wait for it the same way as for our own transactions:
+            Ok(outcome) => chain
+                .tx_status(
+                    network_name,
+                    outcome.transaction.hash,
+                    &outcome.transaction.signer_id,
+                    wait_until,
+                )
                 .await
                 .map_err(|error| {
-                    ceprintln!("<s,r>error:</> failed to create account: {error}");
-                    EX_TEMPFAIL
+                    AccountError::transaction("failed to confirm account creation", error)
+                }),
+            Err(error) => Err(AccountError::Rpc("failed to create account".into(), error)),
+        },
+        Some((sponsor, amount)) => {
+            let signer = keychain::signer(chain, network_name, &sponsor, None, flags)
+                .await
+                .map_err(|error| {
+                    AccountError::keychain("unable to find keys for the sponsor account", error)
                 })?;
 
-            result.json().await.map_err(|error| {
-                ceprintln!("<s,r>error:</> failed to parse response: {error}");
-                EX_SOFTWARE
-            })?
-        }
-        (_, Some(sponsor), Some(amount)) => {
-            let signer =
-                Signer::from_keystore_with_search_for_keys(sponsor.clone(), &api_network_config)
-                    .await
-                    .map_err(|error| {
-                        ceprintln!(
-                            "<s,r>error:</> unable to find keys for the sponsor account: {error}"
-                        );
-                        EX_SOFTWARE
-                    })
-                    .and_then(|signer| {
-                        Signer::new(signer).map_err(|error| {
-                            ceprintln!(
-                        "<s,r>error:</> unable to find keys for the sponsor account: {error}"
-                    );
-                            EX_SOFTWARE
-                        })
-                    })?;
-
-            near_api::Account::create_account(account_id.clone())
+            let transaction = near_api::Account::create_account(account_id.clone())
                 .fund_myself(sponsor, amount)
-                .public_key(public_key)
+                .public_key(public_key.clone())
                 .map_err(|error| {
-                    ceprintln!(
-                        "<s,r>error:</> unexpected error while creating transaction: {error}"
-                    );
-                    EX_SOFTWARE
+                    AccountError::Software(format!(
+                        "unexpected error while creating transaction: {error}"
+                    ))
                 })?
-                .with_signer(signer)
-                .send_to(&api_network_config)
+                .with_signer(signer);
+
+            chain
+                .send(network_name, transaction, wait_until)
                 .await
-                .map_err(|error| {
-                    ceprintln!("<s,r>error:</> failed to create account: {error}");
-                    EX_TEMPFAIL
-                })?
+                .map_err(|error| AccountError::transaction("failed to create account", error))
         }
-        (_, Some(_), None) | (_, None, Some(_)) => {
-            ceprintln!("<s,r>error:</> options <s>--sponsor</> and <s>--sponsor-amount</> are required together");
-            return Err(EX_USAGE);
+    };
+
+    let outcome = match result {
+        Ok(outcome) => Some(outcome),
+        // The request may have reached the network even though we didn't get
+        // a usable response, so check for the account before giving up:
+        Err(_)
+            if chain
+                .access_key(network_name, account_id, &public_key)
+                .await
+                .is_ok() =>
+        {
+            if flags.verbose >= 1 {
+                cprintln!("<s,y>!</> Registration request failed but the account was created");
+            }
+            None
         }
-        (NetworkName::Mainnet, _, _) => {
-            ceprintln!(
-                "<s,r>error:</> mainnet account registration requires a sponsor and an amount to be specified (<s>--sponsor</> and <s>--sponsor-amount</>)"
-            );
-            return Err(EX_USAGE);
+        Err(error) => {
+            journal::record(&journal_entry.failed(&error));
+            chain.forget_credentials(network_name, account_id, &public_key);
+            return Err(error);
         }
     };
+    let journal_entry = journal_entry.with_tx_hash(outcome.as_ref().map(|outcome| outcome.hash));
 
-    // Check for explicit failure. The returned status could also be `NotStarted` so we confirm
-    // that it was created below.
-    if let FinalExecutionStatus::Failure(error) = outcome.status {
-        ceprintln!("<s,r>error:</> failed to create account: {error}");
-        return Err(EX_UNAVAILABLE);
+    if let Some(error) = outcome.as_ref().and_then(tx::TxOutcome::failure) {
+        journal::record(&journal_entry.failed(&error));
+        chain.forget_credentials(network_name, account_id, &public_key);
+        return Err(AccountError::TransactionFailed(
+            "failed to create account".into(),
+            error,
+        ));
     }
 
+    journal::record(&journal_entry.succeeded());
+
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Sent registration request");
     }
 
+    // A transaction that has only been included hasn't created the account yet:
+    if wait_until == WaitUntil::Included {
+        return save_account(network_name, account_id, scope, flags);
+    }
+
     if flags.verbose >= 2 {
         cprintln!("<s,c>»</> Confirming account exists...");
     }
 
-    if let Err(error) = near_api::Account(account_id.clone())
-        .view()
-        .fetch_from(&api_network_config)
-        .await
-    {
-        ceprintln!("<s,r>error:</> account does not seem to exist: {error}");
-        return Err(EX_SOFTWARE);
+    if let Err(error) = chain.view_account(network_name, account_id).await {
+        return Err(AccountError::Rpc(
+            "account does not seem to exist".into(),
+            error,
+        ));
     }
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Confirmed account exists");
     }
 
-    if flags.verbose >= 2 {
-        cprintln!("<s,c>»</> Saving credentials to keychain...");
-    }
-
-    if let Err(error) = near_cli_rs::common::save_access_key_to_keychain(
-        cli_network_config.clone(),
-        &key_pair_properties_buf,
-        &key_pair_properties.public_key_str,
-        account_id.as_str(),
-    ) {
-        ceprintln!("<s,r>error:</> failed to save credentials to keychain: {error}");
-        return Err(EX_SOFTWARE);
-    }
-
-    if flags.verbose >= 1 {
-        cprintln!("<s,g>✓</> Saved credentials to keychain");
-    }
+    save_account(network_name, account_id, scope, flags)
+}
 
+fn save_account(
+    network_name: NetworkName,
+    account_id: &AccountId,
+    scope: Scope,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
     if flags.verbose >= 2 {
         cprintln!("<s,c>»</> Saving account info locally...");
     }
 
-    let Some(dir) = dirs::home_dir() else {
-        ceprintln!("Unable to determine home dir");
-        return Err(EX_CONFIG);
-    };
-    let dir = dir
-        .join(".asimov")
-        .join("accounts")
-        .join("near")
-        .join(network_name.as_str());
-    if let Err(error) = std::fs::create_dir_all(&dir) {
-        ceprintln!("<s,r>error:</> failed to create directory for saving accounts: {error}");
-        return Err(EX_CANTCREAT);
-    }
-
-    let account_file = dir.join(account_id.as_str());
-
-    if let Err(error) = std::fs::File::create(&account_file) {
-        ceprintln!("<s,r>error:</> failed to save account: {error}");
-        return Err(EX_CANTCREAT);
-    }
+    let (account_file, _) = add_to_registry(scope, network_name, account_id)?;
 
     if flags.verbose >= 1 {
         cprintln!("<s,g>✓</> Saved account to {}", account_file.display());
@@ -0,0 +1,162 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{lock_registry, save_metadata};
+use crate::{
+    registry::{self, AccountMetadata, Scope},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use std::path::{Path, PathBuf};
+
+/// A change to a registry file on the way to the current format.
+enum Change {
+    /// Rewrite an account file, e.g. an empty one, as JSON metadata.
+    Rewrite(PathBuf, AccountMetadata),
+    /// Move the file of a deleted account into `.deleted`.
+    Move(PathBuf, PathBuf, AccountMetadata),
+    /// Remove a temporary file left behind by an interrupted write.
+    Remove(PathBuf),
+}
+
+pub fn registry_migrate(flags: &StandardOptions) -> Result<(), AccountError> {
+    // The project registry, if there is one, is migrated along with the user's:
+    if registry::project_path().is_some() {
+        migrate(Scope::Project, flags)?;
+    }
+    migrate(Scope::User, flags)
+}
+
+/// Upgrades the registry of a scope, keeping a backup in its directory.
+fn migrate(scope: Scope, flags: &StandardOptions) -> Result<(), AccountError> {
+    let accounts_dir = scope.path().ok_or(AccountError::NoHomeDir)?;
+    let _lock = lock_registry(scope)?;
+
+    let version = registry::scoped_version(scope)
+        .map_err(|error| AccountError::Read("failed to read the registry version".into(), error))?
+        .unwrap_or(registry::VERSION);
+    if version == registry::VERSION {
+        cprintln!(
+            "<s,g>✓</> The {scope} registry is already at version {}",
+            registry::VERSION
+        );
+        return Ok(());
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!(
+            "<s,c>»</> Checking the files of the {scope} registry in {}...",
+            accounts_dir.display()
+        );
+    }
+
+    // Every file is checked before anything is changed:
+    let registry_dir = accounts_dir.join("near");
+    let changes = plan(&registry_dir)?;
+
+    let backup_dir = accounts_dir.join("backups").join(format!(
+        "near-v{version}-{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S")
+    ));
+    copy_dir(&registry_dir, &backup_dir)
+        .map_err(|error| AccountError::Write("failed to back up the registry".into(), error))?;
+
+    if flags.verbose >= 1 {
+        cprintln!(
+            "<s,g>✓</> Backed up the {scope} registry to {}",
+            backup_dir.display()
+        );
+    }
+
+    for change in changes {
+        match change {
+            Change::Rewrite(path, metadata) => save_metadata(&path, &metadata)?,
+            Change::Move(from, to, metadata) => {
+                save_metadata(&to, &metadata)?;
+                std::fs::remove_file(&from).map_err(|error| {
+                    AccountError::Write("failed to remove account file".into(), error)
+                })?;
+            }
+            Change::Remove(path) => std::fs::remove_file(&path).map_err(|error| {
+                AccountError::Write("failed to remove temporary file".into(), error)
+            })?,
+        }
+    }
+
+    registry::set_version(scope, registry::VERSION).map_err(|error| {
+        AccountError::Write("failed to write the registry version".into(), error)
+    })?;
+
+    cprintln!(
+        "<s,g>✓</> Migrated the {scope} registry from version {version} to {}, with a backup in {}",
+        registry::VERSION,
+        backup_dir.display()
+    );
+
+    Ok(())
+}
+
+/// Reads the registry of every network, returning the changes that bring
+/// it to the current format.
+fn plan(registry_dir: &Path) -> Result<Vec<Change>, AccountError> {
+    let read_dir = |dir: &Path| {
+        std::fs::read_dir(dir)
+            .map_err(|error| AccountError::Read("failed to read accounts directory".into(), error))
+    };
+
+    let mut changes = Vec::new();
+    let networks = match read_dir(registry_dir) {
+        Ok(networks) => networks,
+        Err(AccountError::Read(_, error)) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(changes)
+        }
+        Err(error) => return Err(error),
+    };
+    for network in networks.flatten() {
+        if !network
+            .file_type()
+            .is_ok_and(|file_type| file_type.is_dir())
+        {
+            continue;
+        }
+        for entry in read_dir(&network.path())?.flatten() {
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                continue;
+            }
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') && name.ends_with(".tmp") {
+                changes.push(Change::Remove(path));
+                continue;
+            }
+
+            let metadata = AccountMetadata::load(&path).map_err(|error| {
+                AccountError::InvalidData(format!(
+                    "{} is not a valid account file: {error}",
+                    path.display()
+                ))
+            })?;
+            match name.strip_prefix('.') {
+                Some(deleted) => {
+                    let to = network.path().join(".deleted").join(deleted);
+                    changes.push(Change::Move(path, to, metadata));
+                }
+                None => changes.push(Change::Rewrite(path, metadata)),
+            }
+        }
+    }
+    Ok(changes)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let to = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to)?;
+        } else {
+            std::fs::copy(entry.path(), to)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{load_metadata, lock_registry_of, save_metadata};
+use crate::{
+    chain::Chain,
+    journal::{self, JournalEntry},
+    keychain,
+    network_name::NetworkName,
+    registry,
+    tx::WaitUntil,
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::{near_primitives::views::AccessKeyPermissionView, Account, AccountId};
+use near_crypto::PublicKey;
+
+#[tokio::main]
+pub async fn revoke(
+    account_id: AccountId,
+    public_key: PublicKey,
+    force: bool,
+    wait_until: WaitUntil,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    let registry_path =
+        registry::account_path(network_name, &account_id).ok_or(AccountError::NoHomeDir)?;
+
+    // Deleting a full-access key, such as the one that signs, may lock the
+    // owner out of the account:
+    if !force {
+        if flags.verbose >= 2 {
+            cprintln!("<s,c>»</> Checking the key...");
+        }
+        let access_key = chain
+            .access_key(network_name, &account_id, &public_key)
+            .await
+            .map_err(|error| {
+                AccountError::Rpc(format!("failed to fetch key {public_key}"), error)
+            })?;
+        if access_key.permission == AccessKeyPermissionView::FullAccess {
+            return Err(AccountError::NotPermitted(format!(
+                "{public_key} is a full-access key; use --force to delete it anyway"
+            )));
+        }
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Checking for credentials in keychain...");
+    }
+
+    let signer = keychain::signer(chain, network_name, &account_id, None, flags)
+        .await
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Found credentials in keychain");
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Sending delete key request...");
+    }
+
+    let transaction = Account(account_id.clone())
+        .delete_key(public_key.clone())
+        .with_signer(signer);
+
+    let journal_entry = JournalEntry::new("revoke", &account_id, network_name);
+    journal::record(&journal_entry);
+
+    let outcome = chain.send(network_name, transaction, wait_until).await;
+    journal::check(outcome, journal_entry, "failed to delete key")?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Deleted key {public_key}");
+    }
+
+    chain.forget_credentials(network_name, &account_id, &public_key);
+
+    let _lock = lock_registry_of(&account_id)?;
+    if let Some(mut metadata) = load_metadata(&registry_path)? {
+        if metadata.key(&public_key).is_some() {
+            metadata.keys.retain(|key| key.public_key != public_key);
+            save_metadata(&registry_path, &metadata)?;
+        }
+    }
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Removed credentials from keychain");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    chain::Chain,
+    keychain::{self, KeychainError},
+    nep413,
+    network_name::NetworkName,
+    AccountError, StandardOptions,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use color_print::cprintln;
+use near_api::{signer::NEP413Payload, AccountId, SignerTrait as _};
+use near_crypto::PublicKey;
+
+#[tokio::main]
+#[allow(clippy::too_many_arguments)]
+pub async fn sign_message(
+    account_id: AccountId,
+    message: String,
+    recipient: String,
+    nonce: Option<[u8; 32]>,
+    callback_url: Option<String>,
+    public_key: Option<PublicKey>,
+    base64: bool,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Checking for credentials in keychain...");
+    }
+
+    let credentials =
+        keychain::credentials(chain, network_name, &account_id, public_key.as_ref(), flags)
+            .await
+            .map_err(|error| {
+                AccountError::keychain("unable to find keys for the account", error)
+            })?;
+    let public_key = credentials.get_public_key().map_err(|error| {
+        AccountError::keychain(
+            "unable to find keys for the account",
+            KeychainError::NotFound(error),
+        )
+    })?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Found credentials in keychain");
+    }
+
+    let payload = NEP413Payload {
+        message,
+        nonce: nonce.unwrap_or_else(nep413::random_nonce),
+        recipient,
+        callback_url,
+    };
+    let signed = nep413::sign(&credentials, account_id, public_key, payload)
+        .await
+        .map_err(|error| AccountError::Software(format!("failed to sign message: {error}")))?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Signed message with {}", signed.public_key);
+    }
+
+    let json = serde_json::to_string_pretty(&signed)?;
+    if base64 {
+        println!("{}", BASE64.encode(json));
+    } else {
+        println!("{json}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{chain::Chain, keychain, tx, AccountError, StandardOptions};
+use color_print::cprintln;
+use near_api::{
+    near_primitives::{hash::CryptoHash, transaction::Transaction, types::Nonce},
+    types::transactions::PrepopulateTransaction,
+    SignerTrait as _,
+};
+use near_crypto::PublicKey;
+use std::path::Path;
+
+#[tokio::main]
+pub async fn sign_tx(
+    file: &Path,
+    nonce: Nonce,
+    block_hash: CryptoHash,
+    public_key: Option<PublicKey>,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let input = read_input(file)?;
+    let transaction: Transaction = tx::decode(&input).map_err(|error| {
+        AccountError::InvalidData(format!("failed to read unsigned transaction: {error}"))
+    })?;
+
+    let public_key = public_key.unwrap_or_else(|| transaction.public_key().clone());
+    let signer_id = transaction.signer_id().clone();
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Signing transaction for <s>{signer_id}</> with {public_key}...");
+    }
+
+    let unsigned = PrepopulateTransaction {
+        signer_id: signer_id.clone(),
+        receiver_id: transaction.receiver_id().clone(),
+        actions: transaction.take_actions(),
+    };
+    let credentials = keychain::credentials_for_key(chain, &signer_id, &public_key, flags)
+        .await
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+    let signed = credentials
+        .sign(unsigned, public_key, nonce, block_hash.into())
+        .await
+        .map_err(|error| {
+            AccountError::Software(format!(
+                "unable to sign with the credentials in keychain: {error}"
+            ))
+        })?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Signed transaction {}", signed.get_hash());
+    }
+
+    println!("{}", tx::encode(&signed));
+
+    Ok(())
+}
+
+/// Reads a base64-encoded transaction from a file, or from standard input if the path is `-`.
+pub(crate) fn read_input(file: &Path) -> Result<String, AccountError> {
+    let result = if file == Path::new("-") {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(file)
+    };
+    result.map_err(|error| AccountError::Input(format!("failed to read {}", file.display()), error))
+}
@@ -0,0 +1,353 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::{load_metadata, lock_registry_of, save_metadata};
+use crate::{
+    chain::{Chain, ChainError},
+    config::OutputFormat,
+    journal::{self, JournalEntry},
+    keychain,
+    network_name::NetworkName,
+    registry::{self, AccountMetadata},
+    tx::{self, SignOptions, WaitUntil},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use futures::future;
+use near_api::{AccountId, Contract, NearToken};
+use serde::{Deserialize, Serialize};
+
+/// The balance of an account in a staking pool.
+#[derive(Clone, Debug, Serialize)]
+pub struct PoolBalance {
+    pub pool: AccountId,
+    pub staked: NearToken,
+    pub unstaked: NearToken,
+    /// The part of the unstaked balance that can be withdrawn now.
+    pub withdrawable: NearToken,
+}
+
+impl PoolBalance {
+    /// Returns the staked and unstaked balance together.
+    pub fn total(&self) -> NearToken {
+        self.staked.saturating_add(self.unstaked)
+    }
+}
+
+#[tokio::main]
+pub async fn stake(
+    account_id: AccountId,
+    pool_id: AccountId,
+    amount: NearToken,
+    wait_until: WaitUntil,
+    sign: &SignOptions,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let call = PoolCall {
+        command: "stake",
+        method: "deposit_and_stake",
+        amount: None,
+        deposit: amount,
+    };
+    if !call
+        .send(&account_id, &pool_id, wait_until, sign, chain, flags)
+        .await?
+    {
+        return Ok(());
+    }
+    remember_pool(&account_id, &pool_id)?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Staked {amount} from <s>{account_id}</> with <s>{pool_id}</>");
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+pub async fn unstake(
+    account_id: AccountId,
+    pool_id: AccountId,
+    amount: Option<NearToken>,
+    wait_until: WaitUntil,
+    sign: &SignOptions,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let call = PoolCall {
+        command: "unstake",
+        method: if amount.is_some() {
+            "unstake"
+        } else {
+            "unstake_all"
+        },
+        amount,
+        deposit: NearToken::from_yoctonear(0),
+    };
+    if !call
+        .send(&account_id, &pool_id, wait_until, sign, chain, flags)
+        .await?
+    {
+        return Ok(());
+    }
+
+    if flags.verbose >= 1 {
+        let amount = amount.map_or("all tokens".into(), |amount| amount.to_string());
+        cprintln!("<s,g>✓</> Unstaked {amount} of <s>{account_id}</> in <s>{pool_id}</>");
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+pub async fn withdraw(
+    account_id: AccountId,
+    pool_id: AccountId,
+    amount: Option<NearToken>,
+    wait_until: WaitUntil,
+    sign: &SignOptions,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let call = PoolCall {
+        command: "withdraw",
+        method: if amount.is_some() {
+            "withdraw"
+        } else {
+            "withdraw_all"
+        },
+        amount,
+        deposit: NearToken::from_yoctonear(0),
+    };
+    if !call
+        .send(&account_id, &pool_id, wait_until, sign, chain, flags)
+        .await?
+    {
+        return Ok(());
+    }
+
+    if flags.verbose >= 1 {
+        let amount = amount.map_or("all unstaked tokens".into(), |amount| amount.to_string());
+        cprintln!("<s,g>✓</> Withdrew {amount} from <s>{pool_id}</> to <s>{account_id}</>");
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+pub async fn stake_status(
+    account_id: AccountId,
+    pools: Vec<AccountId>,
+    output: OutputFormat,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+    // Without pools given, show the ones the account staked with:
+    let pools = if pools.is_empty() {
+        registry::account_path(network_name, &account_id)
+            .and_then(|path| AccountMetadata::load(&path).ok())
+            .unwrap_or_default()
+            .pools
+    } else {
+        pools
+    };
+    if pools.is_empty() {
+        return Err(AccountError::Usage(format!(
+            "{account_id} hasn't staked with this program; give the pools with --pool"
+        )));
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!(
+            "<s,c>»</> Fetching the balances of <s>{account_id}</> in {} pool(s)...",
+            pools.len()
+        );
+    }
+
+    let balances = future::try_join_all(
+        pools
+            .iter()
+            .map(|pool_id| pool_balance(chain, network_name, &account_id, pool_id)),
+    )
+    .await
+    .map_err(|error| AccountError::Rpc("failed to fetch the staking balances".into(), error))?;
+
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&balances)?),
+        OutputFormat::Csv => {
+            println!("pool,staked,unstaked,withdrawable");
+            for balance in &balances {
+                println!(
+                    "{},{},{},{}",
+                    balance.pool,
+                    balance.staked.as_yoctonear(),
+                    balance.unstaked.as_yoctonear(),
+                    balance.withdrawable.as_yoctonear()
+                );
+            }
+        }
+        OutputFormat::Text => {
+            let width = balances
+                .iter()
+                .map(|balance| balance.pool.len())
+                .chain(["POOL".len()])
+                .max()
+                .unwrap_or_default();
+            cprintln!(
+                "<s>{:<width$}  {:<20}  {:<20}  WITHDRAWABLE</>",
+                "POOL",
+                "STAKED",
+                "UNSTAKED"
+            );
+            for balance in &balances {
+                println!(
+                    "{:<width$}  {:<20}  {:<20}  {}",
+                    balance.pool,
+                    balance.staked.to_string(),
+                    balance.unstaked.to_string(),
+                    balance.withdrawable
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches the balance of an account in a staking pool.
+pub(crate) async fn pool_balance(
+    chain: &dyn Chain,
+    network_name: NetworkName,
+    account_id: &AccountId,
+    pool_id: &AccountId,
+) -> Result<PoolBalance, ChainError> {
+    /// The result of the `get_account` method of a staking pool, with
+    /// balances as strings of yoctoNEAR.
+    #[derive(Deserialize)]
+    struct PoolAccount {
+        staked_balance: NearToken,
+        unstaked_balance: NearToken,
+        can_withdraw: bool,
+    }
+
+    let result = chain
+        .view_function(
+            network_name,
+            pool_id,
+            "get_account",
+            &serde_json::json!({ "account_id": account_id }),
+        )
+        .await?;
+    let account: PoolAccount = serde_json::from_value(result)?;
+    Ok(PoolBalance {
+        pool: pool_id.clone(),
+        staked: account.staked_balance,
+        unstaked: account.unstaked_balance,
+        withdrawable: if account.can_withdraw {
+            account.unstaked_balance
+        } else {
+            NearToken::from_yoctonear(0)
+        },
+    })
+}
+
+/// A call of a staking pool method on behalf of an account.
+struct PoolCall {
+    /// The command recorded in the journal.
+    command: &'static str,
+    method: &'static str,
+    /// The `amount` argument of the method, if any.
+    amount: Option<NearToken>,
+    deposit: NearToken,
+}
+
+impl PoolCall {
+    /// Signs the call and sends it, unless only signing. Returns whether it
+    /// was sent.
+    async fn send(
+        &self,
+        account_id: &AccountId,
+        pool_id: &AccountId,
+        wait_until: WaitUntil,
+        sign: &SignOptions,
+        chain: &dyn Chain,
+        flags: &StandardOptions,
+    ) -> Result<bool, AccountError> {
+        let network_name = NetworkName::try_from(account_id)
+            .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+        let context = format!("failed to {} with {pool_id}", self.command);
+
+        if flags.verbose >= 2 {
+            cprintln!("<s,c>»</> Checking for credentials in keychain...");
+        }
+
+        let signer = keychain::signer(
+            chain,
+            network_name,
+            account_id,
+            sign.public_key.as_ref(),
+            flags,
+        )
+        .await
+        .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+
+        if flags.verbose >= 1 {
+            cprintln!("<s,g>✓</> Found credentials in keychain");
+        }
+
+        // Pools take amounts as strings of yoctoNEAR, which is how they serialize:
+        let args = match self.amount {
+            Some(amount) => serde_json::json!({ "amount": amount }),
+            None => serde_json::json!({}),
+        };
+        let transaction = Contract(pool_id.clone())
+            .call_function(self.method, args)
+            .map_err(|error| AccountError::Software(format!("{context}: {error}")))?
+            .transaction()
+            .deposit(self.deposit)
+            .with_signer(account_id.clone(), signer);
+        let signed = tx::sign_with(sign, chain, network_name, transaction)
+            .await
+            .map_err(|error| AccountError::transaction(&context, error))?;
+
+        if sign.sign_only {
+            println!("{}", tx::encode(&signed));
+            return Ok(false);
+        }
+
+        if flags.verbose >= 2 {
+            cprintln!(
+                "<s,c>»</> Calling <s>{}</> of <s>{pool_id}</>...",
+                self.method
+            );
+        }
+
+        let journal_entry = JournalEntry::new(self.command, account_id, network_name)
+            .with_tx_hash(Some(signed.get_hash()));
+        journal::record(&journal_entry);
+
+        let outcome = chain.broadcast(network_name, signed, wait_until).await;
+        journal::check(outcome, journal_entry, &context)?;
+        Ok(true)
+    }
+}
+
+/// Records a pool in the registry entry of an account, if it has one, for
+/// `stake status` and `list --long`.
+fn remember_pool(account_id: &AccountId, pool_id: &AccountId) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+    let _lock = lock_registry_of(account_id)?;
+    let path = registry::account_path(network_name, account_id).ok_or(AccountError::NoHomeDir)?;
+    let Some(mut metadata) = load_metadata(&path)? else {
+        return Ok(());
+    };
+    if !metadata.pools.contains(pool_id) {
+        metadata.pools.push(pool_id.clone());
+        save_metadata(&path, &metadata)?;
+    }
+    Ok(())
+}
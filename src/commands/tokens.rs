@@ -0,0 +1,165 @@
+// This is free and unencumbered software released into the public domain.
+
+use super::csv_field;
+use crate::{
+    chain::{Chain, ChainError},
+    config::{self, OutputFormat},
+    nep141::{self, FungibleTokenMetadata},
+    network_name::NetworkName,
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use futures::future;
+use near_api::AccountId;
+
+/// The balance of an account in a fungible token.
+#[derive(Clone, Debug)]
+pub struct TokenBalance {
+    pub token: AccountId,
+    pub metadata: FungibleTokenMetadata,
+    /// The balance in the smallest units of the token.
+    pub balance: u128,
+}
+
+impl TokenBalance {
+    /// Returns the balance as a decimal number of tokens.
+    pub fn amount(&self) -> String {
+        nep141::format_amount(self.balance, self.metadata.decimals)
+    }
+}
+
+#[tokio::main]
+pub async fn tokens(
+    account_id: AccountId,
+    tokens: Vec<AccountId>,
+    output: OutputFormat,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+    // Without tokens given, list the ones configured for the network:
+    let tokens = if tokens.is_empty() {
+        config::get().tokens_on(network_name).to_vec()
+    } else {
+        tokens
+    };
+    if tokens.is_empty() {
+        return Err(AccountError::Usage(format!(
+            "no tokens are configured for {network_name}; set tokens.{network_name} or give them with --token"
+        )));
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!(
+            "<s,c>»</> Fetching the balances of <s>{account_id}</> in {} token(s)...",
+            tokens.len()
+        );
+    }
+
+    let balances = future::try_join_all(
+        tokens
+            .iter()
+            .map(|token_id| token_balance(chain, network_name, &account_id, token_id)),
+    )
+    .await
+    .map_err(|error| AccountError::Rpc("failed to fetch the token balances".into(), error))?;
+
+    match output {
+        OutputFormat::Json => {
+            let balances = balances
+                .iter()
+                .map(|balance| {
+                    serde_json::json!({
+                        "token": balance.token,
+                        "symbol": balance.metadata.symbol,
+                        "decimals": balance.metadata.decimals,
+                        "balance": balance.balance.to_string(),
+                        "amount": balance.amount(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            println!("{}", serde_json::to_string_pretty(&balances)?);
+        }
+        OutputFormat::Csv => {
+            println!("token,symbol,decimals,balance");
+            for balance in &balances {
+                // The symbol comes from the contract and may need quoting:
+                let fields = [
+                    balance.token.to_string(),
+                    balance.metadata.symbol.clone(),
+                    balance.metadata.decimals.to_string(),
+                    balance.balance.to_string(),
+                ];
+                let fields = fields.map(|field| csv_field(&field));
+                println!("{}", fields.join(","));
+            }
+        }
+        OutputFormat::Text => {
+            let width = balances
+                .iter()
+                .map(|balance| balance.token.len())
+                .chain(["TOKEN".len()])
+                .max()
+                .unwrap_or_default();
+            cprintln!("<s>{:<width$}  BALANCE</>", "TOKEN");
+            for balance in &balances {
+                println!(
+                    "{:<width$}  {} {}",
+                    balance.token,
+                    balance.amount(),
+                    balance.metadata.symbol
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetches the metadata of a fungible token.
+pub(crate) async fn token_metadata(
+    chain: &dyn Chain,
+    network_name: NetworkName,
+    token_id: &AccountId,
+) -> Result<FungibleTokenMetadata, ChainError> {
+    let result = chain
+        .view_function(
+            network_name,
+            token_id,
+            "ft_metadata",
+            &serde_json::json!({}),
+        )
+        .await?;
+    Ok(serde_json::from_value(result)?)
+}
+
+/// Fetches the balance of an account in a fungible token, along with the
+/// metadata of the token.
+pub async fn token_balance(
+    chain: &dyn Chain,
+    network_name: NetworkName,
+    account_id: &AccountId,
+    token_id: &AccountId,
+) -> Result<TokenBalance, ChainError> {
+    let (metadata, result) = future::try_join(
+        token_metadata(chain, network_name, token_id),
+        chain.view_function(
+            network_name,
+            token_id,
+            "ft_balance_of",
+            &serde_json::json!({ "account_id": account_id }),
+        ),
+    )
+    .await?;
+    // Balances are strings, as JSON numbers can't hold them:
+    let balance = serde_json::from_value::<String>(result)?
+        .parse()
+        .map_err(|error| format!("invalid balance from {token_id}: {error}"))?;
+    Ok(TokenBalance {
+        token: token_id.clone(),
+        metadata,
+        balance,
+    })
+}
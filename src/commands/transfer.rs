@@ -0,0 +1,74 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    chain::Chain,
+    journal::{self, JournalEntry},
+    keychain,
+    network_name::NetworkName,
+    tx::{self, SignOptions, WaitUntil},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::{AccountId, NearToken, Tokens};
+
+#[tokio::main]
+pub async fn transfer(
+    account_id: AccountId,
+    receiver: AccountId,
+    amount: NearToken,
+    wait_until: WaitUntil,
+    sign: &SignOptions,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&account_id)
+        .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Checking for credentials in keychain...");
+    }
+
+    let signer = keychain::signer(
+        chain,
+        network_name,
+        &account_id,
+        sign.public_key.as_ref(),
+        flags,
+    )
+    .await
+    .map_err(|error| AccountError::keychain("unable to find keys for the account", error))?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Found credentials in keychain");
+    }
+
+    let transaction = Tokens::account(account_id.clone())
+        .send_to(receiver.clone())
+        .near(amount)
+        .with_signer(signer);
+    let signed = tx::sign_with(sign, chain, network_name, transaction)
+        .await
+        .map_err(|error| AccountError::transaction("failed to transfer tokens", error))?;
+
+    if sign.sign_only {
+        println!("{}", tx::encode(&signed));
+        return Ok(());
+    }
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Sending transfer request...");
+    }
+
+    let journal_entry = JournalEntry::new("transfer", &account_id, network_name)
+        .with_tx_hash(Some(signed.get_hash()));
+    journal::record(&journal_entry);
+
+    let outcome = chain.broadcast(network_name, signed, wait_until).await;
+    journal::check(outcome, journal_entry, "failed to transfer tokens")?;
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Transferred {amount} from <s>{account_id}</> to <s>{receiver}</>");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    chain::Chain,
+    network_name::NetworkName,
+    tx::{self, WaitUntil},
+    AccountError, StandardOptions,
+};
+use color_print::cprintln;
+use near_api::{near_primitives::hash::CryptoHash, AccountId};
+
+#[tokio::main]
+pub async fn tx_status(
+    hash: CryptoHash,
+    sender: AccountId,
+    wait_until: WaitUntil,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name =
+        NetworkName::try_from(&sender).map_err(|_| AccountError::UnknownNetwork(sender.clone()))?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Waiting for transaction status...");
+    }
+
+    let outcome = chain
+        .tx_status(network_name, hash, &sender, wait_until)
+        .await
+        .map_err(|error| AccountError::transaction("failed to fetch transaction status", error))?;
+
+    tx::print_outcome(&outcome);
+
+    if let Some(error) = outcome.failure() {
+        return Err(AccountError::TransactionFailed(
+            "transaction failed".into(),
+            error,
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    chain::Chain, commands::read_input, nep413::SignedMessage, network_name::NetworkName,
+    AccountError, StandardOptions,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use color_print::cprintln;
+use near_api::near_primitives::views::AccessKeyPermissionView;
+use std::path::Path;
+
+#[tokio::main]
+pub async fn verify_message(
+    file: &Path,
+    on_chain: bool,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let input = read_input(file)?;
+    let signed = parse(&input).map_err(|error| {
+        AccountError::InvalidData(format!("failed to read signed message: {error}"))
+    })?;
+    let account_id = &signed.account_id;
+
+    match signed.verify() {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(AccountError::InvalidData(
+                "signature is not valid for the message".into(),
+            ));
+        }
+        Err(error) => {
+            return Err(AccountError::InvalidData(format!(
+                "failed to read signed message: {error}"
+            )));
+        }
+    }
+
+    if flags.verbose >= 1 {
+        cprintln!("<s,g>✓</> Signature is valid for {}", signed.public_key);
+    }
+
+    if on_chain {
+        let network_name = NetworkName::try_from(account_id)
+            .map_err(|_| AccountError::UnknownNetwork(account_id.clone()))?;
+
+        if flags.verbose >= 2 {
+            cprintln!("<s,c>»</> Checking the key of account <s>{account_id}</> on the network...");
+        }
+
+        let access_key = chain
+            .access_key(network_name, account_id, &signed.public_key)
+            .await
+            .map_err(|error| {
+                AccountError::NotPermitted(format!(
+                    "public key is not a key of account {account_id}: {error}"
+                ))
+            })?;
+        if !matches!(access_key.permission, AccessKeyPermissionView::FullAccess) {
+            return Err(AccountError::NotPermitted(format!(
+                "public key is not a full-access key of account {account_id}"
+            )));
+        }
+
+        if flags.verbose >= 1 {
+            cprintln!("<s,g>✓</> Public key is a full-access key of <s>{account_id}</>");
+        }
+    }
+
+    println!(
+        "Message was signed by {account_id} for {}",
+        signed.recipient
+    );
+
+    Ok(())
+}
+
+/// Parses a signed message as JSON, or as base64-encoded JSON.
+fn parse(input: &str) -> Result<SignedMessage, String> {
+    let input = input.trim();
+    if input.starts_with('{') {
+        return serde_json::from_str(input).map_err(|error| format!("invalid JSON: {error}"));
+    }
+    let json = BASE64
+        .decode(input)
+        .map_err(|error| format!("invalid base64: {error}"))?;
+    serde_json::from_slice(&json).map_err(|error| format!("invalid JSON: {error}"))
+}
@@ -0,0 +1,121 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    chain::Chain, config::OutputFormat, network_name::NetworkName, AccountError, StandardOptions,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use clientele::crates::clap::Args;
+use color_print::{ceprintln, cprintln};
+use near_api::AccountId;
+use serde::Serialize;
+
+/// The arguments of a contract method, given as JSON or, for Borsh and
+/// other binary encodings, as base64.
+#[derive(Clone, Debug, Default, Args)]
+pub struct MethodArgs {
+    /// The arguments of the method as JSON. For example `{"account_id": "alice.near"}`.
+    #[clap(long = "args", value_name = "JSON", conflicts_with = "args_base64")]
+    pub json: Option<String>,
+
+    /// The arguments of the method as base64, e.g. when they are Borsh-encoded.
+    #[clap(long, value_name = "BASE64")]
+    pub args_base64: Option<String>,
+}
+
+impl MethodArgs {
+    /// Returns the arguments as the bytes to send, `{}` if none were given.
+    pub fn encode(&self) -> Result<Vec<u8>, AccountError> {
+        match (&self.json, &self.args_base64) {
+            (_, Some(input)) => BASE64
+                .decode(input.trim())
+                .map_err(|error| AccountError::Usage(format!("invalid --args-base64: {error}"))),
+            (Some(input), None) => {
+                let args: serde_json::Value = serde_json::from_str(input)
+                    .map_err(|error| AccountError::Usage(format!("invalid --args: {error}")))?;
+                Ok(args.to_string().into_bytes())
+            }
+            (None, None) => Ok(b"{}".to_vec()),
+        }
+    }
+}
+
+/// The value returned by a contract method, decoded for display.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "encoding", content = "value", rename_all = "lowercase")]
+pub enum MethodResult {
+    /// The method returned nothing.
+    None,
+    Json(serde_json::Value),
+    /// Anything else, such as Borsh, which can't be decoded without its
+    /// schema, as base64.
+    Base64(String),
+}
+
+impl MethodResult {
+    pub fn decode(bytes: &[u8]) -> Self {
+        if bytes.is_empty() {
+            return Self::None;
+        }
+        match serde_json::from_slice(bytes) {
+            Ok(value) => Self::Json(value),
+            Err(_) => Self::Base64(BASE64.encode(bytes)),
+        }
+    }
+
+    /// Prints the value, pretty-printing JSON.
+    pub(crate) fn print(&self) -> Result<(), AccountError> {
+        match self {
+            Self::None => {}
+            Self::Json(value) => println!("{}", serde_json::to_string_pretty(value)?),
+            Self::Base64(value) => println!("{value}"),
+        }
+        Ok(())
+    }
+}
+
+#[tokio::main]
+pub async fn view(
+    contract_id: AccountId,
+    method_name: String,
+    args: &MethodArgs,
+    output: OutputFormat,
+    chain: &dyn Chain,
+    flags: &StandardOptions,
+) -> Result<(), AccountError> {
+    let network_name = NetworkName::try_from(&contract_id)
+        .map_err(|_| AccountError::UnknownNetwork(contract_id.clone()))?;
+    let args = args.encode()?;
+
+    if flags.verbose >= 2 {
+        cprintln!("<s,c>»</> Calling <s>{method_name}</> on <s>{contract_id}</>...");
+    }
+
+    let view = chain
+        .call_view(network_name, &contract_id, &method_name, args)
+        .await
+        .map_err(|error| AccountError::Rpc(format!("failed to call {method_name}"), error))?;
+    let result = MethodResult::decode(&view.result);
+
+    match output {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "result": result,
+                "logs": view.logs,
+            }))?
+        ),
+        OutputFormat::Text | OutputFormat::Csv => {
+            for log in &view.logs {
+                ceprintln!("<s>Log:</> {log}");
+            }
+            if flags.verbose >= 1 {
+                if let MethodResult::Base64(_) = result {
+                    ceprintln!("<s,y>!</> The result isn't JSON; showing it as base64");
+                }
+            }
+            result.print()?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+// This is free and unencumbered software released into the public domain.
+
+//! Shell completions, including account names from the local registry.
+//!
+//! The shell scripts printed by `completions` call back into the binary to
+//! complete each argument, so that account names reflect the registry at
+//! the time of completion.
+
+use crate::{
+    config::{self, Config},
+    network_name::NetworkName,
+    registry,
+};
+use clap_complete::{
+    engine::CompletionCandidate,
+    env::{Bash, Elvish, EnvCompleter, Fish, Powershell, Zsh},
+};
+use clientele::crates::clap::ValueEnum;
+use near_api::AccountId;
+use std::collections::BTreeSet;
+
+/// The environment variable that asks the binary to complete its arguments.
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// A shell that completions can be generated for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Elvish,
+    Powershell,
+}
+
+impl Shell {
+    pub fn completer(&self) -> &'static dyn EnvCompleter {
+        match self {
+            Self::Bash => &Bash,
+            Self::Zsh => &Zsh,
+            Self::Fish => &Fish,
+            Self::Elvish => &Elvish,
+            Self::Powershell => &Powershell,
+        }
+    }
+}
+
+/// Completes the names of the accounts in the local registry, and their aliases.
+pub fn account_names() -> Vec<CompletionCandidate> {
+    let mut candidates = candidates(registry_accounts());
+    candidates.extend(aliases());
+    candidates
+}
+
+/// Completes the aliases of accounts in the local registry.
+pub fn aliases() -> Vec<CompletionCandidate> {
+    registry::aliases()
+        .into_iter()
+        .map(|(alias, account_id)| {
+            CompletionCandidate::new(alias).help(Some(account_id.to_string().into()))
+        })
+        .collect()
+}
+
+/// Completes the names of accounts that can sponsor a registration: the
+/// configured sponsor and the accounts in the local registry.
+pub fn sponsor_names() -> Vec<CompletionCandidate> {
+    let mut accounts = registry_accounts();
+    // Completion runs before the configuration is loaded:
+    if let Some(sponsor) = config::path(None)
+        .and_then(|path| Config::load(&path).ok())
+        .and_then(|config| config.sponsor)
+    {
+        accounts.insert(sponsor);
+    }
+    candidates(accounts)
+}
+
+fn registry_accounts() -> BTreeSet<AccountId> {
+    [NetworkName::Testnet, NetworkName::Mainnet]
+        .into_iter()
+        .flat_map(|network_name| registry::accounts(network_name).unwrap_or_default())
+        .collect()
+}
+
+fn candidates(accounts: BTreeSet<AccountId>) -> Vec<CompletionCandidate> {
+    accounts
+        .into_iter()
+        .map(|account_id| CompletionCandidate::new(account_id.as_str()))
+        .collect()
+}
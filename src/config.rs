@@ -0,0 +1,371 @@
+// This is free and unencumbered software released into the public domain.
+
+//! The user configuration in `~/.asimov/accounts/config.toml`.
+//!
+//! Every setting can also be given in an environment variable named after
+//! its key, e.g. `ASIMOV_ACCOUNT_SPONSOR_AMOUNT` for `sponsor-amount`, which
+//! takes precedence over the file. Command-line flags take precedence over both.
+
+use crate::{agent::Confirm, network_name::NetworkName};
+use clientele::crates::clap::ValueEnum;
+use near_api::{AccountId, NearToken, RPCEndpoint};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+/// The environment variable holding the path of the configuration file.
+pub const PATH_VAR: &str = "ASIMOV_ACCOUNT_CONFIG";
+
+/// The configuration keys, with a description of each.
+pub const KEYS: &[(&str, &str)] = &[
+    (
+        "network",
+        "The network of accounts whose name doesn't end in .near or .testnet",
+    ),
+    ("rpc.testnet", "The RPC URL to use for testnet"),
+    ("rpc.mainnet", "The RPC URL to use for mainnet"),
+    (
+        "faucet",
+        "The URL of the faucet to use on testnet, e.g. a local stand-in",
+    ),
+    (
+        "sponsor",
+        "The account that sponsors registrations on its network",
+    ),
+    (
+        "sponsor-amount",
+        "The amount of NEAR tokens to sponsor registrations with",
+    ),
+    (
+        "keystore",
+        "Where credentials are read from: auto, keychain, or agent",
+    ),
+    (
+        "tokens.testnet",
+        "The fungible token contracts listed by tokens on testnet, separated by commas",
+    ),
+    (
+        "tokens.mainnet",
+        "The fungible token contracts listed by tokens on mainnet, separated by commas",
+    ),
+    (
+        "output",
+        "The output format of listings: text, json, or csv",
+    ),
+    (
+        "confirm",
+        "Which requests the agent confirms: never, transactions, or always",
+    ),
+];
+
+/// Where the credentials for signing are read from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Keystore {
+    /// The agent if `ASIMOV_ACCOUNT_AGENT_SOCK` is set and holds the key, otherwise the keychain.
+    #[default]
+    Auto,
+    /// Always the keychain.
+    Keychain,
+    /// Always the agent, at `ASIMOV_ACCOUNT_AGENT_SOCK` or the default socket.
+    Agent,
+}
+
+/// The output format of listings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkName>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rpc: BTreeMap<NetworkName, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sponsor: Option<AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none", with = "token")]
+    pub sponsor_amount: Option<NearToken>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keystore: Option<Keystore>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tokens: BTreeMap<NetworkName, Vec<AccountId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<OutputFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirm: Option<Confirm>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    UnknownKey(String),
+    InvalidValue(String, String),
+    MissingValue(String, String),
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Write(PathBuf, std::io::Error),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownKey(key) => write!(f, "unknown configuration key `{key}`"),
+            Self::InvalidValue(key, error) => write!(f, "invalid value for `{key}`: {error}"),
+            Self::MissingValue(key, reason) => write!(f, "missing value for `{key}`: {reason}"),
+            Self::Read(path, error) => write!(f, "failed to read {}: {error}", path.display()),
+            Self::Parse(path, error) => write!(f, "failed to parse {}: {error}", path.display()),
+            Self::Write(path, error) => write!(f, "failed to write {}: {error}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UnknownKey(_) | Self::InvalidValue(..) | Self::MissingValue(..) => None,
+            Self::Read(_, error) | Self::Write(_, error) => Some(error),
+            Self::Parse(_, error) => Some(error),
+        }
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Sets the configuration in effect for this process.
+pub fn init(config: Config) {
+    CONFIG.set(config).ok();
+}
+
+/// Returns the configuration in effect for this process, or the defaults if
+/// none was set.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Returns the path of the configuration file: the given one, the one in
+/// `ASIMOV_ACCOUNT_CONFIG`, or the default.
+pub fn path(path: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = path {
+        return Some(path.into());
+    }
+    if let Some(path) = std::env::var_os(PATH_VAR).filter(|path| !path.is_empty()) {
+        return Some(path.into());
+    }
+    Some(
+        dirs::home_dir()?
+            .join(".asimov")
+            .join("accounts")
+            .join("config.toml"),
+    )
+}
+
+/// Returns the name of the environment variable for a key.
+pub fn env_var(key: &str) -> String {
+    format!(
+        "ASIMOV_ACCOUNT_{}",
+        key.to_uppercase().replace(['.', '-'], "_")
+    )
+}
+
+impl Config {
+    /// Reads the configuration file, which need not exist.
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                toml::from_str(&contents).map_err(|error| ConfigError::Parse(path.into(), error))
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(ConfigError::Read(path.into(), error)),
+        }
+    }
+
+    /// Reads the configuration file and applies the environment variables over it.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let mut config = Self::read(path)?;
+        for (key, _) in KEYS {
+            if let Ok(value) = std::env::var(env_var(key)) {
+                if !value.is_empty() {
+                    config.set(key, &value)?;
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Writes the configuration file.
+    pub fn write(&self, path: &Path) -> Result<(), ConfigError> {
+        let contents = toml::to_string_pretty(self).expect("the configuration should serialize");
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|error| ConfigError::Write(path.into(), error))?;
+        }
+        std::fs::write(path, contents).map_err(|error| ConfigError::Write(path.into(), error))
+    }
+
+    /// Returns the value of a key, if set.
+    pub fn get(&self, key: &str) -> Result<Option<String>, ConfigError> {
+        Ok(match key {
+            "network" => self.network.map(|network| network.to_string()),
+            "rpc.testnet" => self.rpc.get(&NetworkName::Testnet).cloned(),
+            "rpc.mainnet" => self.rpc.get(&NetworkName::Mainnet).cloned(),
+            "faucet" => self.faucet.clone(),
+            "sponsor" => self.sponsor.as_ref().map(AccountId::to_string),
+            "sponsor-amount" => self.sponsor_amount.map(|amount| amount.to_string()),
+            "keystore" => self.keystore.map(|keystore| name(&keystore)),
+            "tokens.testnet" | "tokens.mainnet" => {
+                self.tokens.get(&tokens_network(key)).map(|tokens| {
+                    tokens
+                        .iter()
+                        .map(AccountId::to_string)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                })
+            }
+            "output" => self.output.map(|output| name(&output)),
+            "confirm" => self.confirm.map(|confirm| name(&confirm)),
+            _ => return Err(ConfigError::UnknownKey(key.into())),
+        })
+    }
+
+    /// Sets a key to a value, which is validated.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = |error: String| ConfigError::InvalidValue(key.into(), error);
+        match key {
+            "network" => self.network = Some(parse_enum(value).map_err(invalid)?),
+            "rpc.testnet" | "rpc.mainnet" => {
+                let network = if key == "rpc.testnet" {
+                    NetworkName::Testnet
+                } else {
+                    NetworkName::Mainnet
+                };
+                RPCEndpoint::new(value.parse().map_err(|error| invalid(format!("{error}")))?);
+                self.rpc.insert(network, value.into());
+            }
+            "faucet" => {
+                value
+                    .parse::<Url>()
+                    .map_err(|error| invalid(format!("{error}")))?;
+                self.faucet = Some(value.into());
+            }
+            "sponsor" => {
+                self.sponsor = Some(value.parse().map_err(|error| invalid(format!("{error}")))?)
+            }
+            "sponsor-amount" => {
+                self.sponsor_amount =
+                    Some(value.parse().map_err(|error| invalid(format!("{error}")))?)
+            }
+            "keystore" => self.keystore = Some(parse_enum(value).map_err(invalid)?),
+            "tokens.testnet" | "tokens.mainnet" => {
+                let tokens = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|token| !token.is_empty())
+                    .map(|token| token.parse().map_err(|error| invalid(format!("{error}"))))
+                    .collect::<Result<_, _>>()?;
+                self.tokens.insert(tokens_network(key), tokens);
+            }
+            "output" => self.output = Some(parse_enum(value).map_err(invalid)?),
+            "confirm" => self.confirm = Some(parse_enum(value).map_err(invalid)?),
+            _ => return Err(ConfigError::UnknownKey(key.into())),
+        }
+        Ok(())
+    }
+
+    /// Removes a key.
+    pub fn unset(&mut self, key: &str) -> Result<(), ConfigError> {
+        match key {
+            "network" => self.network = None,
+            "rpc.testnet" => _ = self.rpc.remove(&NetworkName::Testnet),
+            "rpc.mainnet" => _ = self.rpc.remove(&NetworkName::Mainnet),
+            "faucet" => self.faucet = None,
+            "sponsor" => self.sponsor = None,
+            "sponsor-amount" => self.sponsor_amount = None,
+            "keystore" => self.keystore = None,
+            "tokens.testnet" | "tokens.mainnet" => _ = self.tokens.remove(&tokens_network(key)),
+            "output" => self.output = None,
+            "confirm" => self.confirm = None,
+            _ => return Err(ConfigError::UnknownKey(key.into())),
+        }
+        Ok(())
+    }
+
+    /// Returns the RPC endpoint configured for a network, if any.
+    pub fn rpc_endpoint(&self, network_name: NetworkName) -> Option<RPCEndpoint> {
+        let url = self.rpc.get(&network_name)?;
+        Some(RPCEndpoint::new(url.parse().ok()?))
+    }
+
+    /// Returns the configured faucet URL, if any.
+    pub fn faucet_url(&self) -> Option<Url> {
+        self.faucet.as_ref()?.parse().ok()
+    }
+
+    /// Returns the fungible token contracts configured for a network.
+    pub fn tokens_on(&self, network_name: NetworkName) -> &[AccountId] {
+        self.tokens.get(&network_name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the configured sponsor, if it is on the given network.
+    pub fn sponsor_on(&self, network_name: NetworkName) -> Option<&AccountId> {
+        self.sponsor.as_ref().filter(|sponsor| {
+            NetworkName::try_from(*sponsor)
+                .is_ok_and(|sponsor_network| sponsor_network == network_name)
+        })
+    }
+}
+
+/// Returns the network of a `tokens.*` key.
+fn tokens_network(key: &str) -> NetworkName {
+    if key == "tokens.testnet" {
+        NetworkName::Testnet
+    } else {
+        NetworkName::Mainnet
+    }
+}
+
+fn name(value: &impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .expect("values should not be skipped")
+        .get_name()
+        .into()
+}
+
+fn parse_enum<T: ValueEnum>(value: &str) -> Result<T, String> {
+    T::from_str(value, true)
+}
+
+/// Serializes token amounts as strings like `0.1 NEAR`.
+mod token {
+    use near_api::NearToken;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        amount: &Option<NearToken>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match amount {
+            Some(amount) => serializer.serialize_str(&amount.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<NearToken>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|amount| amount.parse().map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
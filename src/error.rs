@@ -0,0 +1,167 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    config::ConfigError, keychain::KeychainError, registry::UnsupportedVersion, tx::TxError,
+    SysexitsError,
+};
+use near_api::AccountId;
+use std::error::Error;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+/// An error from a command.
+///
+/// Commands don't print their errors; the caller decides how to render them
+/// and can use [`AccountError::to_sysexits`] for an exit code.
+#[derive(Debug)]
+pub enum AccountError {
+    /// The network couldn't be determined from the name of an account.
+    UnknownNetwork(AccountId),
+    /// The command was used incorrectly.
+    Usage(String),
+    /// The home directory couldn't be determined.
+    NoHomeDir,
+    /// The configuration couldn't be read or changed.
+    Config(ConfigError),
+    /// The credentials of an account couldn't be found or used.
+    Keychain(String, Box<KeychainError>),
+    /// A request to the network failed.
+    Rpc(String, BoxError),
+    /// A transaction couldn't be signed, sent, or confirmed.
+    Transaction(String, Box<TxError>),
+    /// A transaction was executed but failed.
+    TransactionFailed(String, String),
+    /// The account already exists on the network.
+    AccountExists(AccountId),
+    /// The input data is malformed or doesn't check out.
+    InvalidData(String),
+    /// The account doesn't permit what was requested.
+    NotPermitted(String),
+    /// An input file couldn't be read.
+    Input(String, std::io::Error),
+    /// A local file or directory couldn't be read.
+    Read(String, std::io::Error),
+    /// A local file or directory couldn't be created or written.
+    Write(String, std::io::Error),
+    /// The registry is in a newer format than this program understands.
+    UnsupportedRegistry(u32),
+    /// An unexpected internal error.
+    Software(String),
+}
+
+impl AccountError {
+    pub fn rpc(context: impl Into<String>, error: impl Error + Send + Sync + 'static) -> Self {
+        Self::Rpc(context.into(), Box::new(error))
+    }
+
+    pub fn keychain(context: impl Into<String>, error: KeychainError) -> Self {
+        Self::Keychain(context.into(), Box::new(error))
+    }
+
+    pub fn transaction(context: impl Into<String>, error: TxError) -> Self {
+        Self::Transaction(context.into(), Box::new(error))
+    }
+
+    /// Returns the error of reading the registry, which may be in a newer
+    /// format than this program understands.
+    pub fn read_registry(context: impl Into<String>, error: std::io::Error) -> Self {
+        match unsupported_version(&error) {
+            Some(version) => Self::UnsupportedRegistry(version),
+            None => Self::Read(context.into(), error),
+        }
+    }
+
+    /// Returns the error of changing the registry, which may be in a newer
+    /// format than this program understands.
+    pub fn write_registry(context: impl Into<String>, error: std::io::Error) -> Self {
+        match unsupported_version(&error) {
+            Some(version) => Self::UnsupportedRegistry(version),
+            None => Self::Write(context.into(), error),
+        }
+    }
+
+    /// Returns the exit code for this error.
+    pub fn to_sysexits(&self) -> SysexitsError {
+        use SysexitsError::*;
+        match self {
+            Self::UnknownNetwork(_) | Self::InvalidData(_) | Self::UnsupportedRegistry(_) => {
+                EX_DATAERR
+            }
+            Self::Usage(_) => EX_USAGE,
+            Self::NoHomeDir => EX_CONFIG,
+            Self::Config(error) => match error {
+                ConfigError::UnknownKey(_) | ConfigError::InvalidValue(..) => EX_USAGE,
+                ConfigError::Read(..) => EX_IOERR,
+                ConfigError::Parse(..) | ConfigError::MissingValue(..) => EX_CONFIG,
+                ConfigError::Write(..) => EX_CANTCREAT,
+            },
+            Self::Keychain(..) | Self::Software(_) => EX_SOFTWARE,
+            Self::Rpc(..) | Self::TransactionFailed(..) => EX_UNAVAILABLE,
+            Self::Transaction(..) => EX_TEMPFAIL,
+            Self::AccountExists(_) | Self::Write(..) => EX_CANTCREAT,
+            Self::NotPermitted(_) => EX_NOPERM,
+            Self::Input(..) => EX_NOINPUT,
+            Self::Read(..) => EX_IOERR,
+        }
+    }
+}
+
+impl std::fmt::Display for AccountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownNetwork(account_id) => write!(
+                f,
+                "unable to determine network name from the account {account_id}. The account must end with either .near for mainnet or .testnet for testnet accounts, or the `network` setting must be configured"
+            ),
+            Self::Usage(message) | Self::InvalidData(message) | Self::NotPermitted(message) => {
+                write!(f, "{message}")
+            }
+            Self::Software(message) => write!(f, "{message}"),
+            Self::NoHomeDir => write!(f, "unable to determine home directory"),
+            Self::Config(error) => write!(f, "{error}"),
+            Self::Keychain(context, error) => write!(f, "{context}: {error}"),
+            Self::Rpc(context, error) => write!(f, "{context}: {error}"),
+            Self::Transaction(context, error) => write!(f, "{context}: {error}"),
+            Self::TransactionFailed(context, error) => write!(f, "{context}: {error}"),
+            Self::AccountExists(account_id) => {
+                write!(f, "account {account_id} already exists on the network")
+            }
+            Self::UnsupportedRegistry(version) => write!(f, "{}", UnsupportedVersion(*version)),
+            Self::Input(context, error) | Self::Read(context, error) | Self::Write(context, error) => {
+                write!(f, "{context}: {error}")
+            }
+        }
+    }
+}
+
+impl Error for AccountError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Config(error) => Some(error),
+            Self::Keychain(_, error) => Some(error.as_ref()),
+            Self::Rpc(_, error) => Some(error.as_ref()),
+            Self::Transaction(_, error) => Some(error.as_ref()),
+            Self::Input(_, error) | Self::Read(_, error) | Self::Write(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ConfigError> for AccountError {
+    fn from(error: ConfigError) -> Self {
+        Self::Config(error)
+    }
+}
+
+impl From<serde_json::Error> for AccountError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Software(format!("failed to serialize: {error}"))
+    }
+}
+
+/// Returns the version of a registry in a newer format than this program
+/// understands, if that is what an I/O error reports.
+fn unsupported_version(error: &std::io::Error) -> Option<u32> {
+    let version = error.get_ref()?.downcast_ref::<UnsupportedVersion>()?;
+    Some(version.0)
+}
@@ -0,0 +1,210 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    tx::{TxError, TxOutcome},
+    AccountError,
+};
+use chrono::{DateTime, Utc};
+use clientele::crates::clap::ValueEnum;
+use color_print::ceprintln;
+use near_api::{near_primitives::hash::CryptoHash, AccountId};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+    path::PathBuf,
+};
+
+/// The outcome of a journaled transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalStatus {
+    /// The transaction was about to be sent; no outcome was recorded.
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+impl std::fmt::Display for JournalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Pending => "pending",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+        })
+    }
+}
+
+/// A record of a transaction sent by a command.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    pub command: String,
+    pub account: AccountId,
+    pub network: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<CryptoHash>,
+    pub status: JournalStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// When the command started, which is the same in all entries of one
+    /// run of a command. Missing in entries from older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started: Option<DateTime<Utc>>,
+}
+
+impl JournalEntry {
+    pub fn new(command: &str, account: &AccountId, network: impl ToString) -> Self {
+        let now = Utc::now();
+        Self {
+            timestamp: now,
+            command: command.into(),
+            account: account.clone(),
+            network: network.to_string(),
+            tx_hash: None,
+            status: JournalStatus::Pending,
+            error: None,
+            started: Some(now),
+        }
+    }
+
+    pub fn with_tx_hash(mut self, tx_hash: Option<CryptoHash>) -> Self {
+        self.tx_hash = tx_hash.or(self.tx_hash);
+        self
+    }
+
+    /// Returns a copy of this entry recording that the transaction succeeded.
+    pub fn succeeded(&self) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            status: JournalStatus::Succeeded,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of this entry recording that the transaction failed.
+    pub fn failed(&self, error: impl ToString) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            status: JournalStatus::Failed,
+            error: Some(error.to_string()),
+            ..self.clone()
+        }
+    }
+}
+
+/// Returns the path of the journal in the accounts home directory.
+pub fn path() -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".asimov")
+            .join("accounts")
+            .join("journal.jsonl"),
+    )
+}
+
+/// Appends an entry to the journal.
+pub fn append(entry: &JournalEntry) -> std::io::Result<()> {
+    let Some(path) = path() else {
+        return Err(std::io::ErrorKind::NotFound.into());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Appends an entry to the journal, warning instead of failing the command
+/// if it can't be written.
+pub fn record(entry: &JournalEntry) {
+    if let Err(error) = append(entry) {
+        ceprintln!("<s,y>warning:</> failed to write to transaction journal: {error}");
+    }
+}
+
+/// Records the outcome of a journaled transaction, returning it if the
+/// transaction succeeded, or else an error with the context.
+pub fn check(
+    outcome: Result<TxOutcome, TxError>,
+    entry: JournalEntry,
+    context: &str,
+) -> Result<TxOutcome, AccountError> {
+    let outcome = match outcome {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            record(&entry.failed(&error));
+            return Err(AccountError::transaction(context, error));
+        }
+    };
+    let entry = entry.with_tx_hash(Some(outcome.hash));
+    if let Some(error) = outcome.failure() {
+        record(&entry.failed(&error));
+        return Err(AccountError::TransactionFailed(context.into(), error));
+    }
+    record(&entry.succeeded());
+    Ok(outcome)
+}
+
+/// Reads all entries from the journal, oldest first, skipping any that are malformed.
+pub fn read() -> std::io::Result<Vec<JournalEntry>> {
+    let Some(path) = path() else {
+        return Err(std::io::ErrorKind::NotFound.into());
+    };
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    let mut entries = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        if let Ok(entry) = serde_json::from_str(&line?) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Folds the entries recorded by each run of a command into the latest one,
+/// which has the current status of its transaction, in the order the runs
+/// started.
+pub fn latest(entries: Vec<JournalEntry>) -> Vec<JournalEntry> {
+    #[derive(PartialEq, Eq, Hash)]
+    enum Run {
+        Started(DateTime<Utc>),
+        Transaction(CryptoHash),
+    }
+
+    let mut latest: Vec<JournalEntry> = Vec::new();
+    let mut runs = HashMap::new();
+    for entry in entries {
+        // Older entries without a start time are matched by their transaction:
+        let run = match (entry.started, entry.tx_hash) {
+            (Some(started), _) => Run::Started(started),
+            (None, Some(tx_hash)) => Run::Transaction(tx_hash),
+            (None, None) => {
+                latest.push(entry);
+                continue;
+            }
+        };
+        let key = (
+            entry.command.clone(),
+            entry.account.clone(),
+            entry.network.clone(),
+            run,
+        );
+        match runs.get(&key) {
+            Some(&index) => latest[index] = entry,
+            None => {
+                runs.insert(key, latest.len());
+                latest.push(entry);
+            }
+        }
+    }
+    latest
+}
@@ -0,0 +1,212 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    agent::{self, AgentSigner},
+    chain::Chain,
+    config::{self, Keystore},
+    network_name::NetworkName,
+    rpc::RpcError,
+    StandardOptions,
+};
+use color_print::ceprintln;
+use near_api::{
+    errors::{KeyStoreError, SignerError},
+    near_primitives::{transaction::SignedTransaction, types::Nonce},
+    signer::{keystore::KeystoreSigner, secret_key::SecretKeySigner, NEP413Payload},
+    types::{transactions::PrepopulateTransaction, CryptoHash},
+    AccountId, Signer, SignerTrait,
+};
+use near_crypto::{PublicKey, SecretKey, Signature};
+use std::{io::ErrorKind, sync::Arc};
+
+#[derive(Debug)]
+pub enum KeychainError {
+    Search(RpcError<KeyStoreError>),
+    NotFound(SignerError),
+    Agent(std::io::Error),
+}
+
+impl std::fmt::Display for KeychainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Search(error) => write!(f, "{error}"),
+            Self::NotFound(error) => write!(f, "{error}"),
+            Self::Agent(error) => write!(f, "failed to contact agent: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for KeychainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Search(error) => Some(error),
+            Self::NotFound(error) => Some(error),
+            Self::Agent(error) => Some(error),
+        }
+    }
+}
+
+/// An account's credentials: either in the keychain, held by a running
+/// agent (see [`agent`]), or in memory.
+#[derive(Clone, Debug)]
+pub enum Credentials {
+    Keystore(KeystoreSigner),
+    Agent(AgentSigner),
+    SecretKey(SecretKeySigner),
+}
+
+#[async_trait::async_trait]
+impl SignerTrait for Credentials {
+    async fn sign(
+        &self,
+        tr: PrepopulateTransaction,
+        public_key: PublicKey,
+        nonce: Nonce,
+        block_hash: CryptoHash,
+    ) -> Result<SignedTransaction, SignerError> {
+        match self {
+            Self::Keystore(signer) => signer.sign(tr, public_key, nonce, block_hash).await,
+            Self::Agent(signer) => signer.sign(tr, public_key, nonce, block_hash).await,
+            Self::SecretKey(signer) => signer.sign(tr, public_key, nonce, block_hash).await,
+        }
+    }
+
+    async fn sign_message_nep413(
+        &self,
+        signer_id: AccountId,
+        public_key: PublicKey,
+        payload: NEP413Payload,
+    ) -> Result<Signature, SignerError> {
+        match self {
+            Self::Keystore(signer) => {
+                signer
+                    .sign_message_nep413(signer_id, public_key, payload)
+                    .await
+            }
+            Self::Agent(signer) => {
+                signer
+                    .sign_message_nep413(signer_id, public_key, payload)
+                    .await
+            }
+            Self::SecretKey(signer) => {
+                signer
+                    .sign_message_nep413(signer_id, public_key, payload)
+                    .await
+            }
+        }
+    }
+
+    async fn get_secret_key(
+        &self,
+        signer_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<SecretKey, SignerError> {
+        match self {
+            Self::Keystore(signer) => signer.get_secret_key(signer_id, public_key).await,
+            Self::Agent(signer) => signer.get_secret_key(signer_id, public_key).await,
+            Self::SecretKey(signer) => signer.get_secret_key(signer_id, public_key).await,
+        }
+    }
+
+    fn get_public_key(&self) -> Result<PublicKey, SignerError> {
+        match self {
+            Self::Keystore(signer) => signer.get_public_key(),
+            Self::Agent(signer) => signer.get_public_key(),
+            Self::SecretKey(signer) => signer.get_public_key(),
+        }
+    }
+}
+
+/// Returns a signer for the account's credentials in the keychain.
+///
+/// With a public key, the matching credentials are used without contacting
+/// the network. Otherwise, the account's full-access keys are fetched from
+/// the network and searched for in the keychain.
+///
+/// If `ASIMOV_ACCOUNT_AGENT_SOCK` is set and the agent holds a key for the
+/// account, the agent signs instead and the keychain isn't read. An agent
+/// that is no longer listening is skipped. The `keystore` setting can
+/// restrict this to either the agent or the keychain.
+pub async fn signer(
+    chain: &dyn Chain,
+    network_name: NetworkName,
+    account_id: &AccountId,
+    public_key: Option<&PublicKey>,
+    flags: &StandardOptions,
+) -> Result<Arc<Signer>, KeychainError> {
+    Signer::new(credentials(chain, network_name, account_id, public_key, flags).await?)
+        .map_err(KeychainError::NotFound)
+}
+
+/// Returns the account's credentials, for signing other than transactions.
+/// See [`signer`].
+pub async fn credentials(
+    chain: &dyn Chain,
+    network_name: NetworkName,
+    account_id: &AccountId,
+    public_key: Option<&PublicKey>,
+    flags: &StandardOptions,
+) -> Result<Credentials, KeychainError> {
+    if let Some(signer) = agent_signer(account_id, public_key, flags).await? {
+        return Ok(Credentials::Agent(signer));
+    }
+    match public_key {
+        Some(public_key) => chain.credentials_for_key(account_id, public_key),
+        None => chain.find_credentials(network_name, account_id).await,
+    }
+}
+
+/// Returns the credentials with the given public key, without contacting the network.
+pub async fn credentials_for_key(
+    chain: &dyn Chain,
+    account_id: &AccountId,
+    public_key: &PublicKey,
+    flags: &StandardOptions,
+) -> Result<Credentials, KeychainError> {
+    match agent_signer(account_id, Some(public_key), flags).await? {
+        Some(signer) => Ok(Credentials::Agent(signer)),
+        None => chain.credentials_for_key(account_id, public_key),
+    }
+}
+
+async fn agent_signer(
+    account_id: &AccountId,
+    public_key: Option<&PublicKey>,
+    flags: &StandardOptions,
+) -> Result<Option<AgentSigner>, KeychainError> {
+    let keystore = config::get().keystore.unwrap_or_default();
+    let socket = match keystore {
+        Keystore::Keychain => None,
+        Keystore::Auto => agent::socket_from_env(),
+        Keystore::Agent => agent::socket_from_env().or_else(agent::default_socket),
+    };
+    let Some(socket) = socket else {
+        return Ok(None);
+    };
+    let signer = match AgentSigner::connect(&socket, account_id, public_key).await {
+        Ok(signer) => signer,
+        // A stale socket of an agent that has exited:
+        Err(error)
+            if keystore == Keystore::Auto
+                && matches!(
+                    error.kind(),
+                    ErrorKind::NotFound | ErrorKind::ConnectionRefused
+                ) =>
+        {
+            if flags.verbose >= 1 {
+                ceprintln!(
+                    "<s,y>!</> No agent is listening on {}; using the keychain",
+                    socket.display()
+                );
+            }
+            return Ok(None);
+        }
+        Err(error) => return Err(KeychainError::Agent(error)),
+    };
+    if signer.is_none() && keystore == Keystore::Agent {
+        return Err(KeychainError::Agent(std::io::Error::other(format!(
+            "the agent holds no key for {account_id}"
+        ))));
+    }
+    Ok(signer)
+}
@@ -1,9 +1,29 @@
 // This is free and unencumbered software released into the public domain.
 
+pub mod agent;
+pub mod chain;
 pub mod commands;
+pub mod completions;
+pub mod config;
+pub mod error;
 pub mod features;
 pub mod options {}
 
+pub mod journal;
+pub mod keychain;
+pub mod man;
+pub mod migration;
+#[cfg(feature = "mock-rpc")]
+pub mod mock_rpc;
+pub mod nep141;
+pub mod nep171;
+pub mod nep413;
 pub mod network_name;
+pub mod prompt;
+pub mod registry;
+pub mod rpc;
+pub mod tx;
+
+pub use error::AccountError;
 
 use clientele::{StandardOptions, SysexitsError};
@@ -2,14 +2,34 @@
 
 #![deny(unsafe_code)]
 
-use asimov_account_cli::commands;
+use asimov_account_cli::{
+    agent::AgentOptions,
+    chain::NearChain,
+    commands::{self, ListOrder, MethodArgs},
+    completions::{self, Shell, COMPLETE_VAR},
+    config::{self, Config, OutputFormat},
+    journal::JournalStatus,
+    nep413,
+    network_name::NetworkName,
+    registry::{self, Scope},
+    rpc::RpcOptions,
+    tx::{SignOptions, WaitUntil},
+    AccountError,
+};
 
+use clap_complete::{engine::ArgValueCandidates, env::CompleteEnv};
 use clientele::{
-    crates::clap::{Parser, Subcommand},
+    crates::clap::{CommandFactory as _, Parser, Subcommand},
     StandardOptions,
     SysexitsError::{self, *},
 };
-use near_api::{AccountId, NearToken};
+use color_print::ceprintln;
+use near_api::{
+    near_primitives::{hash::CryptoHash, types::Nonce},
+    AccountId, NearGas, NearToken,
+};
+use near_crypto::PublicKey;
+use std::{io::IsTerminal as _, path::PathBuf};
 
 /// ASIMOV Account Command-Line Interface (CLI)
 #[derive(Debug, Parser)]
@@ -19,17 +39,199 @@ struct Options {
     #[clap(flatten)]
     flags: StandardOptions,
 
+    #[clap(flatten)]
+    rpc: RpcOptions,
+
+    /// The configuration file to use. Defaults to `~/.asimov/accounts/config.toml`.
+    #[clap(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
+    /// The output format of listings.
+    #[clap(long, value_name = "FORMAT", value_enum, global = true)]
+    output: Option<OutputFormat>,
+
     #[clap(subcommand)]
     command: Option<Command>,
 }
 
 #[derive(Debug, Subcommand)]
 enum Command {
+    /// Hold account keys unlocked and sign for other processes over a Unix socket.
+    ///
+    /// Prints the `ASIMOV_ACCOUNT_AGENT_SOCK` setting that makes other commands
+    /// sign through the agent.
+    Agent {
+        /// The names of the accounts whose keys to hold.
+        #[clap(value_name = "NAME", required = true, value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        names: Vec<AccountId>,
+
+        #[clap(flatten)]
+        options: AgentOptions,
+    },
+
+    /// Manage short names that may be given instead of account names.
+    Alias {
+        #[clap(subcommand)]
+        command: AliasCommand,
+    },
+
+    /// Send a signed transaction created with `--sign-only` or `sign-tx`.
+    Broadcast {
+        /// The file containing the base64-encoded signed transaction, or `-` for standard input.
+        #[clap(value_name = "FILE")]
+        file: PathBuf,
+
+        /// How far the transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+    },
+
+    /// Call a method of a contract in a transaction signed by an account, and show its execution outcome.
+    Call {
+        /// The name of the account to call from.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The contract to call, e.g. `wrap.near`.
+        #[clap(value_name = "CONTRACT")]
+        contract: AccountId,
+
+        /// The name of the method to call.
+        #[clap(value_name = "METHOD")]
+        method: String,
+
+        #[clap(flatten)]
+        args: MethodArgs,
+
+        /// The amount of NEAR tokens to attach to the call. For example `1 yoctoNEAR` or `0.1 NEAR`.
+        #[clap(long, value_name = "NEAR", default_value = "0 NEAR")]
+        deposit: NearToken,
+
+        /// The gas to attach to the call. For example `30 Tgas`.
+        #[clap(long, value_name = "GAS", default_value = "100 Tgas")]
+        gas: NearGas,
+
+        /// How far the call transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+
+        #[clap(flatten)]
+        sign: SignOptions,
+    },
+
+    /// Print a script that completes commands and account names in a shell.
+    ///
+    /// For example, add `source <(asimov-account completions bash)` to `~/.bashrc`.
+    Completions {
+        /// The shell to complete in.
+        #[clap(value_name = "SHELL", value_enum)]
+        shell: Shell,
+    },
+
+    /// Show or change the configuration.
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommand,
+    },
+
     /// Check whether an account exists on the network.
     Find {
         /// The name of the account to find.
-        #[clap(value_name = "NAME")]
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+    },
+
+    /// Transfer NEP-141 fungible tokens to another account, registering it with the token first if needed.
+    FtTransfer {
+        /// The name of the account to transfer from.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The token contract, e.g. `usdt.tether-token.near`.
+        #[clap(long, value_name = "CONTRACT")]
+        token: AccountId,
+
+        /// The name of the account to transfer to.
+        #[clap(value_name = "RECEIVER", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        receiver: AccountId,
+
+        /// The amount of tokens to transfer, in whole tokens. For example `1.5`.
+        #[clap(value_name = "AMOUNT")]
+        amount: String,
+
+        /// How far the transfer transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+
+        #[clap(flatten)]
+        sign: SignOptions,
+    },
+
+    /// Send tokens to an existing account from the testnet faucet or a sponsor, and show its new balance.
+    ///
+    /// The faucet is testnet's, unless `--faucet-url` or the `faucet` configuration key gives another.
+    Fund {
+        /// The name of the account to fund.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The name of the account to send the tokens from instead of the faucet.
+        #[clap(long, value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::sponsor_names))]
+        sponsor: Option<AccountId>,
+
+        /// The amount of NEAR tokens for the sponsor to send. For example `10 NEAR`, `0.1 NEAR`, or `10 yoctoNEAR`.
+        #[clap(long, value_name = "NEAR")]
+        sponsor_amount: Option<NearToken>,
+
+        /// How far the funding transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+    },
+
+    /// Add a function-call access key that may only call the given contract.
+    Grant {
+        /// The name of the account to add the key to.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
         name: AccountId,
+
+        /// The contract the key may call.
+        #[clap(long, value_name = "NAME", value_parser = registry::resolve)]
+        contract: AccountId,
+
+        /// The methods the key may call, separated by commas. Defaults to any method.
+        #[clap(long, value_name = "METHODS", value_delimiter = ',')]
+        methods: Vec<String>,
+
+        /// The amount of NEAR tokens the key may spend on fees. For example `0.25 NEAR`. Defaults to unlimited.
+        #[clap(long, value_name = "NEAR")]
+        allowance: Option<NearToken>,
+
+        /// How far the transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+    },
+
+    /// Show the journal of transactions sent by this program.
+    History {
+        /// Only show transactions for this account.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: Option<AccountId>,
+
+        /// Only show transactions sent by this command. For example `register` or `delete`.
+        #[clap(long, value_name = "COMMAND")]
+        command: Option<String>,
+
+        /// Only show transactions on this network. For example `testnet` or `mainnet`.
+        #[clap(long, value_name = "NETWORK")]
+        network: Option<String>,
+
+        /// Only show transactions with this status, e.g. `pending` for ones interrupted before their outcome was recorded.
+        #[clap(long, value_name = "STATUS", value_enum)]
+        status: Option<JournalStatus>,
+
+        /// Only show the most recent transactions.
+        #[clap(short = 'n', long, value_name = "COUNT")]
+        limit: Option<usize>,
     },
 
     /// Import an existing ASIMOV account.
@@ -37,41 +239,490 @@ enum Command {
         /// The name of the account to import.
         #[clap(value_name = "NAME")]
         name: AccountId,
+
+        /// The registry to save the account in: the project's, found in or above the current directory, or the user's.
+        #[clap(long, value_name = "SCOPE", value_enum, default_value_t)]
+        scope: Scope,
+    },
+
+    /// Manage the access keys of an account.
+    Keys {
+        #[clap(subcommand)]
+        command: KeysCommand,
+    },
+
+    /// Manage the labels of accounts, for finding them with `list --label`.
+    Label {
+        #[clap(subcommand)]
+        command: LabelCommand,
     },
 
     /// List all known ASIMOV accounts.
+    ///
+    /// Accounts come from the user registry in `~/.asimov/accounts`, and from the project registry in the nearest
+    /// `.asimov/accounts` in or above the current directory, which takes precedence.
+    ///
+    /// The long listing also fetches the balance of each account and whether the keychain can sign for it,
+    /// with its balance in each staking pool it staked with, and marks the configured sponsor of each
+    /// network with `*`.
     #[clap(alias = "ls")]
-    List {},
+    List {
+        /// Only list accounts whose names match one of these patterns, where `*` matches any characters and `?` any one character. For example `worker-*.testnet`.
+        #[clap(value_name = "PATTERN")]
+        patterns: Vec<String>,
+
+        /// Only list accounts on this network.
+        #[clap(long, value_name = "NETWORK", value_enum)]
+        network: Option<NetworkName>,
+
+        /// Only list accounts with this label. May be given more than once to require several labels.
+        #[clap(long = "label", value_name = "LABEL")]
+        labels: Vec<String>,
+
+        /// Show the balance, key status, creation time, and labels of each account.
+        #[clap(short = 'l', long)]
+        long: bool,
+
+        /// The order to list accounts in.
+        #[clap(long, value_name = "ORDER", value_enum, default_value_t)]
+        sort: ListOrder,
+    },
+
+    /// Print the manual page of this program or one of its commands.
+    ///
+    /// For example, `asimov-account man transfer | man -l -` shows the page of `transfer`.
+    Man {
+        /// The command to print the page of, e.g. `transfer` or `keys list`. Defaults to this program.
+        #[clap(value_name = "COMMAND", conflicts_with = "dir")]
+        command: Vec<String>,
+
+        /// Write the pages of this program and all of its commands into this directory instead.
+        #[clap(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+
+    /// Move an account to a new name: register the new account, transfer the balance, and delete the old one.
+    ///
+    /// Each step is logged, so an interrupted migration continues where it left off when run again.
+    /// An account that still has a balance in a staking pool it staked with is not deleted.
+    Migrate {
+        /// The name of the account to migrate.
+        #[clap(value_name = "OLD", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        old: AccountId,
+
+        /// The name of the account to register and migrate to.
+        #[clap(value_name = "NEW")]
+        new: AccountId,
+
+        /// Also add the function-call keys of the old account to the new one.
+        #[clap(long)]
+        copy_keys: bool,
+
+        /// How far each transaction must progress before continuing.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+    },
+
+    /// List the NEP-171 non-fungible tokens an account holds, e.g. badges or credentials.
+    Nfts {
+        /// The name of the account.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The token contracts to list the tokens of.
+        #[clap(long = "contract", value_name = "CONTRACT", required = true)]
+        contracts: Vec<AccountId>,
+    },
 
     /// Register a new ASIMOV account.
+    ///
+    /// Asks for the name and funding of the account on the terminal when
+    /// `--interactive` is given, or when standard input is a terminal and
+    /// NAME is missing or is a mainnet name without `--sponsor`.
     Register {
         /// The name of the account to register.
         #[clap(value_name = "NAME")]
-        name: AccountId,
+        name: Option<AccountId>,
+
+        /// Ask for the name, network, sponsor, and amount on the terminal, defaulting to the options given.
+        #[clap(long)]
+        interactive: bool,
 
         /// The name of the account that sponsors the registration.
-        #[clap(long, value_name = "NAME", requires = "sponsor_amount")]
+        #[clap(long, value_name = "NAME", requires = "sponsor_amount", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::sponsor_names))]
         sponsor: Option<AccountId>,
 
         /// The amount of NEAR tokens to sponsor the account with. For example `10 NEAR`, `0.1 NEAR`, or `10 yoctoNEAR`.
         #[clap(long, value_name = "NEAR", requires = "sponsor")]
         sponsor_amount: Option<NearToken>,
+
+        /// How far the registration transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+
+        /// The registry to save the account in: the project's, found in or above the current directory, or the user's.
+        #[clap(long, value_name = "SCOPE", value_enum, default_value_t)]
+        scope: Scope,
     },
 
     /// Delete a registered ASIMOV account.
     #[clap(alias = "rm")]
     Delete {
         /// The name of the account to delete.
-        #[clap(value_name = "NAME")]
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
         name: AccountId,
 
         /// The beneficiary account where remaining balance will be sent.
-        #[clap(long, value_name = "NAME")]
+        #[clap(long, value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
         beneficiary: AccountId,
+
+        /// How far the delete transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+
+        #[clap(flatten)]
+        sign: SignOptions,
+    },
+
+    /// Manage the format of the local account registry.
+    Registry {
+        #[clap(subcommand)]
+        command: RegistryCommand,
+    },
+
+    /// Delete a function-call access key added with `grant`.
+    Revoke {
+        /// The name of the account to delete the key from.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The public key to delete.
+        #[clap(value_name = "KEY")]
+        public_key: PublicKey,
+
+        /// Also delete a full-access key, which may lock you out of the account.
+        #[clap(long)]
+        force: bool,
+
+        /// How far the transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+    },
+
+    /// Sign a message with an account's credentials, as specified by NEP-413.
+    SignMessage {
+        /// The name of the account to sign as.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The message to sign.
+        #[clap(long, value_name = "TEXT")]
+        message: String,
+
+        /// The recipient of the message, e.g. an account or a domain name.
+        #[clap(long, value_name = "RECIPIENT")]
+        recipient: String,
+
+        /// The 32-byte nonce as base64. Defaults to a random one.
+        #[clap(long, value_name = "NONCE", value_parser = nep413::parse_nonce)]
+        nonce: Option<[u8; 32]>,
+
+        /// The URL the signed message should be sent to.
+        #[clap(long, value_name = "URL")]
+        callback_url: Option<String>,
+
+        /// The public key of the credentials in the keychain to sign with.
+        #[clap(long, value_name = "KEY")]
+        public_key: Option<PublicKey>,
+
+        /// Print the signed message as base64-encoded JSON.
+        #[clap(long)]
+        base64: bool,
+    },
+
+    /// Sign an unsigned transaction without contacting the network.
+    SignTx {
+        /// The file containing the base64-encoded unsigned transaction, or `-` for standard input.
+        #[clap(value_name = "FILE")]
+        file: PathBuf,
+
+        /// The nonce to sign with, i.e. the current nonce of the access key plus one.
+        #[clap(long, value_name = "NONCE")]
+        nonce: Nonce,
+
+        /// The hash of a recent block to sign with.
+        #[clap(long, value_name = "HASH")]
+        block_hash: CryptoHash,
+
+        /// The public key of the credentials in the keychain to sign with. Defaults to the one in the transaction.
+        #[clap(long, value_name = "KEY")]
+        public_key: Option<PublicKey>,
+    },
+
+    /// Stake NEAR tokens with a staking pool, or show the staked balances.
+    #[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Stake {
+        #[clap(subcommand)]
+        command: Option<StakeCommand>,
+
+        /// The name of the account to stake from.
+        #[clap(value_name = "NAME", required = true, value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: Option<AccountId>,
+
+        /// The staking pool to stake with, e.g. `aurora.poolv1.near`.
+        #[clap(long, value_name = "POOL", required = true)]
+        pool: Option<AccountId>,
+
+        /// The amount of NEAR tokens to stake. For example `10 NEAR` or `0.1 NEAR`.
+        #[clap(value_name = "NEAR", required = true)]
+        amount: Option<NearToken>,
+
+        /// How far the staking transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+
+        #[clap(flatten)]
+        sign: SignOptions,
+    },
+
+    /// List the balances of an account in NEP-141 fungible tokens.
+    Tokens {
+        /// The name of the account.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The token contracts to list. Defaults to the ones configured with `tokens.testnet` or `tokens.mainnet`.
+        #[clap(long = "token", value_name = "CONTRACT")]
+        tokens: Vec<AccountId>,
+    },
+
+    /// Transfer NEAR tokens to another account.
+    Transfer {
+        /// The name of the account to transfer from.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The name of the account to transfer to.
+        #[clap(value_name = "RECEIVER", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        receiver: AccountId,
+
+        /// The amount of NEAR tokens to transfer. For example `10 NEAR`, `0.1 NEAR`, or `10 yoctoNEAR`.
+        #[clap(value_name = "NEAR")]
+        amount: NearToken,
+
+        /// How far the transfer transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+
+        #[clap(flatten)]
+        sign: SignOptions,
+    },
+
+    /// Show the status and execution outcome of a transaction.
+    TxStatus {
+        /// The hash of the transaction.
+        #[clap(value_name = "HASH")]
+        hash: CryptoHash,
+
+        /// The name of the account that signed the transaction.
+        #[clap(long, value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        sender: AccountId,
+
+        /// How far the transaction must progress before its status is shown.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t = WaitUntil::Final)]
+        wait_until: WaitUntil,
+    },
+
+    /// Unstake NEAR tokens in a staking pool, making them withdrawable after a few epochs.
+    Unstake {
+        /// The name of the account that staked.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The staking pool to unstake from.
+        #[clap(long, value_name = "POOL")]
+        pool: AccountId,
+
+        /// The amount of NEAR tokens to unstake. Defaults to all of the staked tokens.
+        #[clap(value_name = "NEAR")]
+        amount: Option<NearToken>,
+
+        /// How far the unstaking transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+
+        #[clap(flatten)]
+        sign: SignOptions,
+    },
+
+    /// Verify a message signed as specified by NEP-413.
+    VerifyMessage {
+        /// The file containing the signed message as JSON or base64, or `-` for standard input.
+        #[clap(value_name = "FILE", default_value = "-")]
+        file: PathBuf,
+
+        /// Also check that the public key is a full-access key of the account on the network.
+        #[clap(long)]
+        on_chain: bool,
+    },
+
+    /// Call a view method of a contract, which reads its state without a transaction.
+    View {
+        /// The contract to call, e.g. `wrap.near`.
+        #[clap(value_name = "CONTRACT")]
+        contract: AccountId,
+
+        /// The name of the view method to call.
+        #[clap(value_name = "METHOD")]
+        method: String,
+
+        #[clap(flatten)]
+        args: MethodArgs,
+    },
+
+    /// Withdraw unstaked NEAR tokens from a staking pool back to the account.
+    Withdraw {
+        /// The name of the account that staked.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The staking pool to withdraw from.
+        #[clap(long, value_name = "POOL")]
+        pool: AccountId,
+
+        /// The amount of NEAR tokens to withdraw. Defaults to all of the withdrawable tokens.
+        #[clap(value_name = "NEAR")]
+        amount: Option<NearToken>,
+
+        /// How far the withdrawal transaction must progress before returning.
+        #[clap(long, value_name = "STATUS", value_enum, default_value_t)]
+        wait_until: WaitUntil,
+
+        #[clap(flatten)]
+        sign: SignOptions,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum AliasCommand {
+    /// Make ALIAS stand for an account in the registry, moving it from any other account.
+    Set {
+        /// The alias, which may not contain dots, e.g. `worker3`.
+        #[clap(value_name = "ALIAS")]
+        alias: String,
+
+        /// The name of the account.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+    },
+
+    /// Remove an alias.
+    Unset {
+        /// The alias to remove.
+        #[clap(value_name = "ALIAS", add = ArgValueCandidates::new(completions::aliases))]
+        alias: String,
+    },
+
+    /// List the aliases and the accounts they stand for.
+    #[clap(alias = "ls")]
+    List {},
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Print the value of a configuration key.
+    Get {
+        /// The key, e.g. `sponsor` or `rpc.testnet`.
+        #[clap(value_name = "KEY")]
+        key: String,
+    },
+
+    /// Set a configuration key in the configuration file.
+    Set {
+        /// The key, e.g. `sponsor` or `rpc.testnet`.
+        #[clap(value_name = "KEY")]
+        key: String,
+
+        /// The value to set.
+        #[clap(value_name = "VALUE")]
+        value: String,
+    },
+
+    /// Remove a configuration key from the configuration file.
+    Unset {
+        /// The key, e.g. `sponsor` or `rpc.testnet`.
+        #[clap(value_name = "KEY")]
+        key: String,
+    },
+
+    /// List the configuration keys that are set.
+    #[clap(alias = "ls")]
+    List {},
+}
+
+#[derive(Debug, Subcommand)]
+enum LabelCommand {
+    /// Add labels to an account in the registry.
+    Add {
+        /// The name of the account.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The labels to add, e.g. `ci` or `prod`.
+        #[clap(value_name = "LABEL", required = true)]
+        labels: Vec<String>,
+    },
+
+    /// Remove labels from an account in the registry.
+    #[clap(alias = "rm")]
+    Remove {
+        /// The name of the account.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The labels to remove.
+        #[clap(value_name = "LABEL", required = true)]
+        labels: Vec<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum KeysCommand {
+    /// List the access keys of an account, with the allowance remaining on function-call keys.
+    #[clap(alias = "ls")]
+    List {
+        /// The name of the account.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum RegistryCommand {
+    /// Upgrade the user registry, and the project registry if there is one, to the current format, keeping a backup of each.
+    Migrate {},
+}
+
+#[derive(Debug, Subcommand)]
+enum StakeCommand {
+    /// Show the staked, unstaked and withdrawable balances of an account per staking pool.
+    Status {
+        /// The name of the account.
+        #[clap(value_name = "NAME", value_parser = registry::resolve, add = ArgValueCandidates::new(completions::account_names))]
+        name: AccountId,
+
+        /// The staking pools to show. Defaults to the ones the account staked with using `stake`.
+        #[clap(long = "pool", value_name = "POOL")]
+        pools: Vec<AccountId>,
     },
 }
 
 pub fn main() -> SysexitsError {
+    // Complete the command line, if requested by the completion script:
+    CompleteEnv::with_factory(Options::command)
+        .var(COMPLETE_VAR)
+        .complete();
+
     // Load environment variables from `.env`:
     clientele::dotenv().ok();
 
@@ -100,23 +751,345 @@ pub fn main() -> SysexitsError {
         std::env::set_var("RUST_BACKTRACE", "1");
     }
 
+    // Load the configuration:
+    let Some(config_path) = config::path(options.config.as_deref()) else {
+        return report(AccountError::NoHomeDir);
+    };
+    match Config::load(&config_path) {
+        Ok(config) => config::init(config),
+        Err(error) => return report(error.into()),
+    }
+    let output = options.output.or(config::get().output).unwrap_or_default();
+
+    let chain = NearChain::new(options.rpc.clone());
+
     // Execute the given command:
     let result = match options.command.unwrap() {
-        Command::Delete { name, beneficiary } => {
-            commands::delete(name, beneficiary, &options.flags)
+        Command::Agent {
+            names,
+            options: agent,
+        } => commands::agent(names, &agent, &chain, &options.flags),
+        Command::Alias { command } => match command {
+            AliasCommand::Set { alias, name } => commands::alias_set(&alias, name, &options.flags),
+            AliasCommand::Unset { alias } => commands::alias_unset(&alias, &options.flags),
+            AliasCommand::List {} => commands::alias_list(output, &options.flags),
+        },
+        Command::Broadcast { file, wait_until } => {
+            commands::broadcast(&file, wait_until, &chain, &options.flags)
+        }
+        Command::Call {
+            name,
+            contract,
+            method,
+            args,
+            deposit,
+            gas,
+            wait_until,
+            sign,
+        } => commands::call(
+            name,
+            contract,
+            method,
+            &args,
+            deposit,
+            gas,
+            wait_until,
+            &sign,
+            output,
+            &chain,
+            &options.flags,
+        ),
+        Command::Completions { shell } => commands::completions(shell, &options.flags),
+        Command::Config { command } => match command {
+            ConfigCommand::Get { key } => commands::config_get(&key, &options.flags),
+            ConfigCommand::Set { key, value } => {
+                commands::config_set(&config_path, &key, &value, &options.flags)
+            }
+            ConfigCommand::Unset { key } => {
+                commands::config_unset(&config_path, &key, &options.flags)
+            }
+            ConfigCommand::List {} => commands::config_list(&config_path, &options.flags),
+        },
+        Command::Delete {
+            name,
+            beneficiary,
+            wait_until,
+            sign,
+        } => commands::delete(name, beneficiary, wait_until, &sign, &chain, &options.flags),
+        Command::Find { name } => commands::find(name, &chain, &options.flags),
+        Command::FtTransfer {
+            name,
+            token,
+            receiver,
+            amount,
+            wait_until,
+            sign,
+        } => commands::ft_transfer(
+            name,
+            token,
+            receiver,
+            amount,
+            wait_until,
+            &sign,
+            &chain,
+            &options.flags,
+        ),
+        Command::Fund {
+            name,
+            sponsor,
+            sponsor_amount,
+            wait_until,
+        } => commands::fund(
+            name,
+            sponsor,
+            sponsor_amount,
+            wait_until,
+            &chain,
+            &options.flags,
+        ),
+        Command::Grant {
+            name,
+            contract,
+            methods,
+            allowance,
+            wait_until,
+        } => commands::grant(
+            name,
+            contract,
+            methods,
+            allowance,
+            wait_until,
+            &chain,
+            &options.flags,
+        ),
+        Command::History {
+            name,
+            command,
+            network,
+            status,
+            limit,
+        } => commands::history(
+            name,
+            command,
+            network,
+            status,
+            limit,
+            output,
+            &options.flags,
+        ),
+        Command::Import { name, scope } => commands::import(name, scope, &chain, &options.flags),
+        Command::Keys { command } => match command {
+            KeysCommand::List { name } => commands::keys_list(name, output, &chain, &options.flags),
+        },
+        Command::Label { command } => match command {
+            LabelCommand::Add { name, labels } => commands::label_add(name, labels, &options.flags),
+            LabelCommand::Remove { name, labels } => {
+                commands::label_remove(name, labels, &options.flags)
+            }
+        },
+        Command::List {
+            patterns,
+            network,
+            labels,
+            long,
+            sort,
+        } => commands::list(
+            patterns,
+            network,
+            labels,
+            long,
+            sort,
+            output,
+            &chain,
+            &options.flags,
+        ),
+        Command::Man { command, dir } => {
+            commands::man(Options::command(), command, dir.as_deref(), &options.flags)
+        }
+        Command::Migrate {
+            old,
+            new,
+            copy_keys,
+            wait_until,
+        } => commands::migrate(
+            old,
+            new,
+            copy_keys,
+            wait_until,
+            &config_path,
+            &chain,
+            &options.flags,
+        ),
+        Command::Nfts { name, contracts } => {
+            commands::nfts(name, contracts, output, &chain, &options.flags)
         }
-        Command::Find { name } => commands::find(name, &options.flags),
-        Command::Import { name } => commands::import(name, &options.flags),
-        Command::List {} => commands::list(&options.flags),
         Command::Register {
             name,
+            interactive,
             sponsor,
             sponsor_amount,
-        } => commands::register(name, sponsor, sponsor_amount, &options.flags),
+            wait_until,
+            scope,
+        } => {
+            // A mainnet account needs a sponsor, which the wizard helps to choose:
+            let asks = interactive
+                || std::io::stdin().is_terminal()
+                    && name.as_ref().is_none_or(|name| {
+                        NetworkName::try_from(name)
+                            .is_ok_and(|network| network == NetworkName::Mainnet)
+                            && sponsor.is_none()
+                    });
+            match name {
+                _ if asks => commands::register_interactive(
+                    name,
+                    sponsor,
+                    sponsor_amount,
+                    wait_until,
+                    scope,
+                    &mut std::io::stdin().lock(),
+                    &chain,
+                    &options.flags,
+                ),
+                Some(name) => commands::register(
+                    name,
+                    sponsor,
+                    sponsor_amount,
+                    wait_until,
+                    scope,
+                    &chain,
+                    &options.flags,
+                ),
+                None => Err(AccountError::Usage(
+                    "the name of the account to register is required unless using --interactive"
+                        .into(),
+                )),
+            }
+        }
+        Command::Registry { command } => match command {
+            RegistryCommand::Migrate {} => commands::registry_migrate(&options.flags),
+        },
+        Command::Revoke {
+            name,
+            public_key,
+            force,
+            wait_until,
+        } => commands::revoke(name, public_key, force, wait_until, &chain, &options.flags),
+        Command::SignMessage {
+            name,
+            message,
+            recipient,
+            nonce,
+            callback_url,
+            public_key,
+            base64,
+        } => commands::sign_message(
+            name,
+            message,
+            recipient,
+            nonce,
+            callback_url,
+            public_key,
+            base64,
+            &chain,
+            &options.flags,
+        ),
+        Command::SignTx {
+            file,
+            nonce,
+            block_hash,
+            public_key,
+        } => commands::sign_tx(&file, nonce, block_hash, public_key, &chain, &options.flags),
+        Command::Stake {
+            command: Some(StakeCommand::Status { name, pools }),
+            ..
+        } => commands::stake_status(name, pools, output, &chain, &options.flags),
+        Command::Stake {
+            command: None,
+            name,
+            pool,
+            amount,
+            wait_until,
+            sign,
+        } => commands::stake(
+            name.unwrap(),
+            pool.unwrap(),
+            amount.unwrap(),
+            wait_until,
+            &sign,
+            &chain,
+            &options.flags,
+        ),
+        Command::Tokens { name, tokens } => {
+            commands::tokens(name, tokens, output, &chain, &options.flags)
+        }
+        Command::Transfer {
+            name,
+            receiver,
+            amount,
+            wait_until,
+            sign,
+        } => commands::transfer(
+            name,
+            receiver,
+            amount,
+            wait_until,
+            &sign,
+            &chain,
+            &options.flags,
+        ),
+        Command::TxStatus {
+            hash,
+            sender,
+            wait_until,
+        } => commands::tx_status(hash, sender, wait_until, &chain, &options.flags),
+        Command::Unstake {
+            name,
+            pool,
+            amount,
+            wait_until,
+            sign,
+        } => commands::unstake(
+            name,
+            pool,
+            amount,
+            wait_until,
+            &sign,
+            &chain,
+            &options.flags,
+        ),
+        Command::VerifyMessage { file, on_chain } => {
+            commands::verify_message(&file, on_chain, &chain, &options.flags)
+        }
+        Command::View {
+            contract,
+            method,
+            args,
+        } => commands::view(contract, method, &args, output, &chain, &options.flags),
+        Command::Withdraw {
+            name,
+            pool,
+            amount,
+            wait_until,
+            sign,
+        } => commands::withdraw(
+            name,
+            pool,
+            amount,
+            wait_until,
+            &sign,
+            &chain,
+            &options.flags,
+        ),
     };
 
     match result {
         Ok(()) => EX_OK,
-        Err(err) => err,
+        Err(error) => report(error),
     }
 }
+
+/// Prints an error and returns its exit code.
+fn report(error: AccountError) -> SysexitsError {
+    ceprintln!("<s,r>error:</> {error}");
+    error.to_sysexits()
+}
@@ -0,0 +1,522 @@
+// This is free and unencumbered software released into the public domain.
+
+//! Manual pages generated from the command-line definitions, with examples
+//! and the exit statuses of each command.
+
+use clap_mangen::Man;
+use clientele::{
+    crates::clap::Command,
+    SysexitsError::{self, *},
+};
+use roff::{bold, roman, Roff};
+use std::{
+    fs::File,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+/// The exit status of a command line that couldn't be parsed.
+pub const EXIT_STATUS_INVALID_COMMAND_LINE: u8 = 2;
+
+/// The exit statuses that every command can end with, as it loads the configuration.
+const COMMON_EXIT_STATUSES: &[SysexitsError] = &[EX_OK, EX_IOERR, EX_CONFIG];
+
+/// The manual page of the binary or one of its subcommands.
+pub struct Page {
+    /// The path of the command below the binary, e.g. `keys list`, or
+    /// empty for the binary itself.
+    pub path: String,
+    command: Command,
+}
+
+impl Page {
+    /// Returns the name of the page's file, e.g. `asimov-account-keys-list.1`.
+    pub fn filename(&self) -> String {
+        self.manual().get_filename()
+    }
+
+    /// Writes the page as roff.
+    pub fn render(&self, out: &mut dyn io::Write) -> io::Result<()> {
+        let man = self.manual();
+        man.render_title(out)?;
+        man.render_name_section(out)?;
+        man.render_synopsis_section(out)?;
+        man.render_description_section(out)?;
+        if self.command.get_arguments().any(|arg| !arg.is_hide_set()) {
+            man.render_options_section(out)?;
+        }
+        if self.command.has_subcommands() {
+            man.render_subcommands_section(out)?;
+        }
+
+        let mut roff = Roff::new();
+        let examples = examples(&self.path);
+        if !examples.is_empty() {
+            roff.control("SH", ["EXAMPLES"]);
+            for (description, command) in examples {
+                roff.control("TP", [] as [&str; 0]);
+                roff.text([bold(*command)]);
+                roff.text([roman(*description)]);
+            }
+        }
+        roff.control("SH", ["EXIT STATUS"]);
+        for (code, description) in exit_statuses(&self.path) {
+            roff.control("TP", [] as [&str; 0]);
+            let label = match SysexitsError::try_from(code) {
+                Ok(status) => format!("{code} ({})", status.name()),
+                Err(_) => code.to_string(),
+            };
+            roff.text([bold(label)]);
+            roff.text([roman(description)]);
+        }
+        roff.to_writer(out)
+    }
+
+    fn manual(&self) -> Man {
+        Man::new(self.command.clone())
+            .source(format!("asimov-account {}", env!("CARGO_PKG_VERSION")))
+    }
+}
+
+/// Returns the pages of the binary and all of its subcommands, the binary's first.
+pub fn pages(command: Command) -> Vec<Page> {
+    let mut command = command.disable_help_subcommand(true);
+    command.build();
+    let mut pages = Vec::new();
+    collect(String::new(), command, &mut pages);
+    pages
+}
+
+fn collect(path: String, command: Command, pages: &mut Vec<Page>) {
+    let subcommands = command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+        .cloned()
+        .collect::<Vec<_>>();
+    pages.push(Page {
+        path: path.clone(),
+        command,
+    });
+    for subcommand in subcommands {
+        let path = match path.as_str() {
+            "" => subcommand.get_name().to_string(),
+            _ => format!("{path} {}", subcommand.get_name()),
+        };
+        collect(path, subcommand, pages);
+    }
+}
+
+/// Writes the pages of the binary and all of its subcommands into a
+/// directory, returning the paths of the files written.
+pub fn generate_to(command: Command, dir: &Path) -> io::Result<Vec<PathBuf>> {
+    pages(command)
+        .iter()
+        .map(|page| {
+            let path = dir.join(page.filename());
+            let mut file = io::BufWriter::new(File::create(&path)?);
+            page.render(&mut file)?;
+            file.flush()?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Returns the examples of a command, given its path below the binary, as
+/// pairs of what the example does and its command line.
+pub fn examples(path: &str) -> &'static [(&'static str, &'static str)] {
+    match path {
+        "" => &[
+            (
+                "Register an account sponsored by the testnet faucet.",
+                "asimov-account register alice.testnet",
+            ),
+            (
+                "List the accounts in the local registry.",
+                "asimov-account list",
+            ),
+        ],
+        "agent" => &[(
+            "Hold the keys of an account for an hour. Run the printed export command in the shells that should sign through the agent.",
+            "asimov-account agent alice.near --lifetime 1h",
+        )],
+        "alias" | "alias set" => &[(
+            "Let `worker3` stand for a long account name in any command.",
+            "asimov-account alias set worker3 asimov-prod-ingest-worker-3.testnet",
+        )],
+        "alias unset" => &[(
+            "Remove an alias.",
+            "asimov-account alias unset worker3",
+        )],
+        "alias list" => &[(
+            "List the aliases as JSON.",
+            "asimov-account alias list --output json",
+        )],
+        "broadcast" => &[(
+            "Send a transaction signed elsewhere.",
+            "asimov-account broadcast signed.txt",
+        )],
+        "call" => &[
+            (
+                "Wrap 1 NEAR, attaching it to the call.",
+                "asimov-account call alice.near wrap.near near_deposit --deposit '1 NEAR'",
+            ),
+            (
+                "Register another account with a token, with 30 Tgas.",
+                "asimov-account call alice.near wrap.near storage_deposit --args '{\"account_id\": \"bob.near\"}' --deposit '0.00125 NEAR' --gas '30 Tgas'",
+            ),
+        ],
+        "completions" => &[(
+            "Complete commands and account names in bash.",
+            "source <(asimov-account completions bash)",
+        )],
+        "config" => &[
+            (
+                "Show every configuration key, with a description of those that aren't set.",
+                "asimov-account config list -v",
+            ),
+            (
+                "Use a private RPC node for testnet.",
+                "asimov-account config set rpc.testnet https://rpc.example.com",
+            ),
+        ],
+        "config get" => &[(
+            "Print the default sponsor.",
+            "asimov-account config get sponsor",
+        )],
+        "config set" => &[(
+            "Use a private RPC node for testnet.",
+            "asimov-account config set rpc.testnet https://rpc.example.com",
+        )],
+        "config unset" => &[(
+            "Go back to sponsoring registrations with the testnet faucet.",
+            "asimov-account config unset sponsor",
+        )],
+        "config list" => &[(
+            "List the configuration keys that are set.",
+            "asimov-account config list",
+        )],
+        "delete" => &[(
+            "Delete an account and send its remaining balance to another.",
+            "asimov-account delete alice.testnet --beneficiary bob.testnet",
+        )],
+        "find" => &[(
+            "Check whether an account exists.",
+            "asimov-account find alice.near",
+        )],
+        "ft-transfer" => &[(
+            "Send 2.5 USDT, registering the receiver with the token if needed.",
+            "asimov-account ft-transfer alice.near --token usdt.tether-token.near bob.near 2.5",
+        )],
+        "fund" => &[
+            (
+                "Top up a testnet account from the testnet faucet.",
+                "asimov-account fund alice.testnet",
+            ),
+            (
+                "Top up a testnet account from a local stand-in for the faucet.",
+                "asimov-account fund alice.testnet --faucet-url http://localhost:3000/account",
+            ),
+            (
+                "Send 1 NEAR to an account from a sponsor.",
+                "asimov-account fund alice.near --sponsor bob.near --sponsor-amount '1 NEAR'",
+            ),
+        ],
+        "grant" => &[(
+            "Add a key that may only call two methods of a contract, spending at most 0.25 NEAR on fees.",
+            "asimov-account grant alice.near --contract app.near --methods get,set --allowance '0.25 NEAR'",
+        )],
+        "history" => &[(
+            "Show the last ten transfers sent from an account.",
+            "asimov-account history alice.near --command transfer -n 10",
+        )],
+        "import" => &[
+            (
+                "Import an account whose credentials are already in the keychain.",
+                "asimov-account import alice.near",
+            ),
+            (
+                "Import an account into the registry of the project in the current directory.",
+                "asimov-account import alice.testnet --scope project",
+            ),
+        ],
+        "keys" | "keys list" => &[(
+            "List the access keys of an account as JSON.",
+            "asimov-account keys list alice.near --output json",
+        )],
+        "label" | "label add" => &[(
+            "Label an account, using its alias.",
+            "asimov-account label add worker3 ci ingest",
+        )],
+        "label remove" => &[(
+            "Remove a label from an account.",
+            "asimov-account label remove worker3 ci",
+        )],
+        "list" => &[
+            (
+                "List the accounts labelled `ci` on testnet.",
+                "asimov-account list --network testnet --label ci",
+            ),
+            (
+                "List the accounts in the local registry as JSON.",
+                "asimov-account list --output json",
+            ),
+            (
+                "Show the balances of the testnet workers, largest first.",
+                "asimov-account list --network testnet --long --sort balance 'worker-*'",
+            ),
+            (
+                "Export the accounts with their balances as CSV.",
+                "asimov-account list --long --output csv > accounts.csv",
+            ),
+        ],
+        "man" => &[
+            (
+                "Show the page of a command.",
+                "asimov-account man transfer | man -l -",
+            ),
+            (
+                "Install the pages of all commands.",
+                "asimov-account man --dir /usr/local/share/man/man1",
+            ),
+        ],
+        "migrate" => &[
+            (
+                "Move an account and its balance to a new name.",
+                "asimov-account migrate alice.testnet alicia.testnet",
+            ),
+            (
+                "Also keep the keys that applications were granted.",
+                "asimov-account migrate alice.near alicia.near --copy-keys",
+            ),
+        ],
+        "nfts" => &[(
+            "List the badges issued to an account, as JSON.",
+            "asimov-account nfts alice.near --contract badges.asimov.near --output json",
+        )],
+        "register" => &[
+            (
+                "Register an account sponsored by the testnet faucet.",
+                "asimov-account register alice.testnet",
+            ),
+            (
+                "Register an account sponsored by another account.",
+                "asimov-account register alice.near --sponsor bob.near --sponsor-amount '0.1 NEAR'",
+            ),
+            (
+                "Ask for the name, network, sponsor, and amount of the account on the terminal.",
+                "asimov-account register --interactive",
+            ),
+        ],
+        "registry" | "registry migrate" => &[(
+            "Upgrade a registry written by an older version.",
+            "asimov-account registry migrate",
+        )],
+        "revoke" => &[(
+            "Delete a key added with grant.",
+            "asimov-account revoke alice.near ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp",
+        )],
+        "sign-message" => &[(
+            "Sign a message for an application.",
+            "asimov-account sign-message alice.near --message 'Log in' --recipient app.example > message.json",
+        )],
+        "sign-tx" => &[(
+            "Sign a transaction offline.",
+            "asimov-account sign-tx unsigned.txt --nonce 42 --block-hash 6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp > signed.txt",
+        )],
+        "stake" => &[
+            (
+                "Stake 10 NEAR with a staking pool.",
+                "asimov-account stake alice.near --pool aurora.poolv1.near '10 NEAR'",
+            ),
+            (
+                "Show the balances in the pools staked with.",
+                "asimov-account stake status alice.near",
+            ),
+        ],
+        "stake status" => &[(
+            "Show the balances in a pool staked with elsewhere.",
+            "asimov-account stake status alice.near --pool aurora.poolv1.near",
+        )],
+        "tokens" => &[
+            (
+                "List the balances in the tokens configured for mainnet.",
+                "asimov-account config set tokens.mainnet usdt.tether-token.near,wrap.near && asimov-account tokens alice.near",
+            ),
+            (
+                "List the balance in one token.",
+                "asimov-account tokens alice.near --token wrap.near",
+            ),
+        ],
+        "transfer" => &[
+            (
+                "Transfer 1.5 NEAR to another account.",
+                "asimov-account transfer alice.near bob.near '1.5 NEAR'",
+            ),
+            (
+                "Sign a transfer without sending it, to send later with broadcast.",
+                "asimov-account transfer alice.near bob.near '1.5 NEAR' --sign-only > signed.txt",
+            ),
+        ],
+        "tx-status" => &[(
+            "Show the outcome of a transaction.",
+            "asimov-account tx-status 6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp --sender alice.near",
+        )],
+        "unstake" => &[(
+            "Unstake everything in a pool, to withdraw it after about four epochs.",
+            "asimov-account unstake alice.near --pool aurora.poolv1.near",
+        )],
+        "verify-message" => &[(
+            "Verify a signed message, including that its key belongs to the account.",
+            "asimov-account verify-message message.json --on-chain",
+        )],
+        "view" => &[(
+            "Show the balance of an account in wrapped NEAR.",
+            "asimov-account view wrap.near ft_balance_of --args '{\"account_id\": \"alice.near\"}'",
+        )],
+        "withdraw" => &[(
+            "Withdraw 5 of the unstaked NEAR from a pool.",
+            "asimov-account withdraw alice.near --pool aurora.poolv1.near '5 NEAR'",
+        )],
+        _ => &[],
+    }
+}
+
+/// Returns the exit statuses of a command, given its path below the binary,
+/// in order of their codes.
+pub fn exit_statuses(path: &str) -> Vec<(u8, &'static str)> {
+    let specific = specific_exit_statuses(path).unwrap_or_default();
+    let mut statuses = COMMON_EXIT_STATUSES
+        .iter()
+        .chain(specific)
+        .map(|&status| (status.as_u8(), describe(status)))
+        .collect::<Vec<_>>();
+    statuses.push((
+        EXIT_STATUS_INVALID_COMMAND_LINE,
+        "The command line couldn't be parsed.",
+    ));
+    statuses.sort_by_key(|&(code, _)| code);
+    statuses
+}
+
+/// Returns the exit statuses that a command, given its path below the
+/// binary, can end with besides the common ones, or `None` for a command
+/// that isn't documented here.
+pub fn specific_exit_statuses(path: &str) -> Option<&'static [SysexitsError]> {
+    Some(match path {
+        "" => &[
+            EX_USAGE,
+            EX_DATAERR,
+            EX_NOINPUT,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_CANTCREAT,
+            EX_TEMPFAIL,
+            EX_NOPERM,
+        ],
+        "agent" => &[EX_USAGE, EX_DATAERR, EX_UNAVAILABLE, EX_CANTCREAT],
+        "alias" | "alias set" | "label" | "label add" | "label remove" => {
+            &[EX_USAGE, EX_DATAERR, EX_CANTCREAT]
+        }
+        "alias unset" => &[EX_USAGE, EX_CANTCREAT],
+        "alias list" | "config list" => &[],
+        "broadcast" => &[EX_DATAERR, EX_NOINPUT, EX_UNAVAILABLE, EX_TEMPFAIL],
+        "call" => &[
+            EX_USAGE,
+            EX_DATAERR,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_TEMPFAIL,
+        ],
+        "completions" | "man" => &[EX_USAGE, EX_CANTCREAT],
+        "config" | "config set" | "config unset" => &[EX_USAGE, EX_CANTCREAT],
+        "config get" => &[EX_USAGE],
+        "delete" | "grant" => &[
+            EX_DATAERR,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_CANTCREAT,
+            EX_TEMPFAIL,
+        ],
+        "find" => &[EX_DATAERR, EX_UNAVAILABLE, EX_SOFTWARE],
+        "ft-transfer" => &[
+            EX_USAGE,
+            EX_DATAERR,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_TEMPFAIL,
+        ],
+        "fund" => &[
+            EX_USAGE,
+            EX_DATAERR,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_TEMPFAIL,
+        ],
+        "history" => &[EX_USAGE],
+        "import" => &[EX_DATAERR, EX_UNAVAILABLE, EX_SOFTWARE, EX_CANTCREAT],
+        "keys" | "keys list" => &[EX_USAGE, EX_DATAERR, EX_UNAVAILABLE],
+        "list" => &[EX_DATAERR],
+        "migrate" => &[
+            EX_USAGE,
+            EX_DATAERR,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_NOPERM,
+            EX_CANTCREAT,
+            EX_TEMPFAIL,
+        ],
+        "nfts" => &[EX_DATAERR, EX_UNAVAILABLE],
+        "register" => &[
+            EX_USAGE,
+            EX_DATAERR,
+            EX_NOINPUT,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_CANTCREAT,
+            EX_TEMPFAIL,
+        ],
+        "registry" | "registry migrate" => &[EX_DATAERR, EX_CANTCREAT],
+        "revoke" => &[
+            EX_DATAERR,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_NOPERM,
+            EX_CANTCREAT,
+            EX_TEMPFAIL,
+        ],
+        "sign-message" => &[EX_DATAERR, EX_SOFTWARE],
+        "sign-tx" => &[EX_DATAERR, EX_NOINPUT, EX_SOFTWARE],
+        "stake" => &[
+            EX_DATAERR,
+            EX_UNAVAILABLE,
+            EX_SOFTWARE,
+            EX_CANTCREAT,
+            EX_TEMPFAIL,
+        ],
+        "stake status" => &[EX_USAGE, EX_DATAERR, EX_UNAVAILABLE],
+        "tokens" => &[EX_USAGE, EX_DATAERR, EX_UNAVAILABLE],
+        "transfer" => &[EX_DATAERR, EX_UNAVAILABLE, EX_SOFTWARE, EX_TEMPFAIL],
+        "tx-status" => &[EX_DATAERR, EX_UNAVAILABLE, EX_TEMPFAIL],
+        "unstake" | "withdraw" => &[EX_DATAERR, EX_UNAVAILABLE, EX_SOFTWARE, EX_TEMPFAIL],
+        "verify-message" => &[EX_DATAERR, EX_NOINPUT, EX_UNAVAILABLE, EX_NOPERM],
+        "view" => &[EX_USAGE, EX_DATAERR, EX_UNAVAILABLE],
+        _ => return None,
+    })
+}
+
+fn describe(status: SysexitsError) -> &'static str {
+    match status {
+        EX_OK => "The command succeeded.",
+        EX_USAGE => "The command was used incorrectly, e.g. with an unknown configuration key.",
+        EX_DATAERR => "The network couldn't be determined from the name of an account, or the input data is malformed or doesn't check out.",
+        EX_NOINPUT => "An input file couldn't be read.",
+        EX_UNAVAILABLE => "A request to the network failed, or a transaction was executed but failed.",
+        EX_SOFTWARE => "The credentials of an account couldn't be found or used in the keychain, or an unexpected internal error occurred.",
+        EX_CANTCREAT => "The account already exists on the network, or a local file or directory couldn't be created or written.",
+        EX_IOERR => "A local file or directory, e.g. the configuration file, couldn't be read.",
+        EX_TEMPFAIL => "A transaction couldn't be signed, sent, or confirmed. Retrying may succeed.",
+        EX_NOPERM => "The account or key doesn't permit what was requested.",
+        EX_CONFIG => "The home directory couldn't be determined, or the configuration is malformed or incomplete.",
+        _ => "",
+    }
+}
@@ -0,0 +1,118 @@
+// This is free and unencumbered software released into the public domain.
+
+//! The step log of an account migration, which lets `migrate` continue an
+//! interrupted migration where it left off.
+//!
+//! The log of a migration from an account is stored as JSON in
+//! `~/.asimov/accounts/migrations/<network>/<account>.json` while the
+//! migration is in progress, and is removed once it completes.
+
+use crate::{network_name::NetworkName, registry};
+use chrono::{DateTime, Utc};
+use near_api::{near_primitives::hash::CryptoHash, AccountId};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A step of a migration, in the order they are taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MigrationStep {
+    /// Register the new account, sponsored by the old one.
+    Register,
+    /// Transfer the balance of the old account to the new one.
+    Transfer,
+    /// Add the function-call keys of the old account to the new one.
+    CopyKeys,
+    /// Delete the old account, with the new one as beneficiary.
+    Delete,
+    /// Move the old account's registry entry and configuration to the new one.
+    UpdateRegistry,
+}
+
+impl MigrationStep {
+    pub const ALL: [Self; 5] = [
+        Self::Register,
+        Self::Transfer,
+        Self::CopyKeys,
+        Self::Delete,
+        Self::UpdateRegistry,
+    ];
+}
+
+impl std::fmt::Display for MigrationStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Register => "register",
+            Self::Transfer => "transfer",
+            Self::CopyKeys => "copy-keys",
+            Self::Delete => "delete",
+            Self::UpdateRegistry => "update-registry",
+        })
+    }
+}
+
+/// A migration in progress.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Migration {
+    pub old: AccountId,
+    pub new: AccountId,
+    pub copy_keys: bool,
+    pub started: DateTime<Utc>,
+    /// The steps completed so far.
+    #[serde(default)]
+    pub completed: Vec<MigrationStep>,
+    /// The hash of the balance transfer, recorded before it is sent so that
+    /// a resumed migration can tell whether it went through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_hash: Option<CryptoHash>,
+}
+
+impl Migration {
+    pub fn new(old: AccountId, new: AccountId, copy_keys: bool) -> Self {
+        Self {
+            old,
+            new,
+            copy_keys,
+            started: Utc::now(),
+            completed: Vec::new(),
+            transfer_hash: None,
+        }
+    }
+
+    /// Returns the steps that remain to be taken, in order.
+    pub fn remaining(&self) -> Vec<MigrationStep> {
+        MigrationStep::ALL
+            .into_iter()
+            .filter(|step| self.copy_keys || *step != MigrationStep::CopyKeys)
+            .filter(|step| !self.completed.contains(step))
+            .collect()
+    }
+
+    /// Reads the log of a migration, returning `None` if there is none.
+    pub fn load(path: &Path) -> std::io::Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Writes the log of the migration.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        registry::write_atomically(path, contents.as_bytes())
+    }
+}
+
+/// Returns the path of the log of a migration from an account.
+pub fn path(network_name: NetworkName, old: &AccountId) -> Option<PathBuf> {
+    Some(
+        dirs::home_dir()?
+            .join(".asimov")
+            .join("accounts")
+            .join("migrations")
+            .join(network_name.as_str())
+            .join(format!("{old}.json")),
+    )
+}
@@ -0,0 +1,499 @@
+// This is free and unencumbered software released into the public domain.
+
+//! A local stand-in for a NEAR JSON-RPC node and faucet, for end-to-end tests.
+//!
+//! [`MockRpc`] serves the state of a [`FakeChain`] over HTTP, so that the
+//! binary can be run with `--rpc-url` pointing at it and exercise the same
+//! near-api code paths as against a real node. Tests script the state through
+//! the chain before and after running commands.
+//!
+//! Only the methods the commands use are implemented: `query` (for
+//! `view_account`, `view_access_key`, `view_access_key_list` and
+//! `call_function`), `block`,
+//! `broadcast_tx_commit`, `send_tx` and `tx`, plus a faucet at `/faucet` that
+//! creates accounts and sends tokens to existing ones.
+
+use crate::{
+    chain::{Chain, FakeChain},
+    network_name::NetworkName,
+    rpc::RpcError,
+    tx::{self, TxError, WaitUntil},
+};
+use near_api::{
+    near_primitives::{
+        errors::TxExecutionError,
+        hash::CryptoHash,
+        transaction::SignedTransaction,
+        types::BlockHeight,
+        views::{
+            AccessKeyList, BlockHeaderView, BlockView, CallResult, QueryRequest, TxExecutionStatus,
+        },
+    },
+    AccountId,
+};
+use near_crypto::{KeyType, PublicKey, Signature};
+use near_jsonrpc_client::{
+    errors::{JsonRpcError, JsonRpcServerError},
+    methods::{
+        query::{RpcQueryError, RpcQueryRequest},
+        send_tx::{RpcSendTransactionRequest, RpcTransactionError},
+    },
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{io, net::SocketAddr, sync::Arc, thread::JoinHandle};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+
+/// The height reported for the fake network's only block.
+const BLOCK_HEIGHT: BlockHeight = 1;
+
+/// A JSON-RPC server on a local port, running on its own thread until dropped.
+pub struct MockRpc {
+    address: SocketAddr,
+    chain: Arc<FakeChain>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockRpc {
+    /// Starts serving the chain on a free local port.
+    pub fn start(chain: Arc<FakeChain>) -> io::Result<Self> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+
+        let (shutdown, stopped) = oneshot::channel();
+        let thread = std::thread::spawn({
+            let chain = chain.clone();
+            move || {
+                runtime.block_on(async move {
+                    let listener = TcpListener::from_std(listener).expect("should be in a runtime");
+                    tokio::select! {
+                        _ = serve(listener, chain) => {}
+                        _ = stopped => {}
+                    }
+                })
+            }
+        });
+
+        Ok(Self {
+            address,
+            chain,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the URL to pass as `--rpc-url`.
+    pub fn url(&self) -> String {
+        format!("http://{}/", self.address)
+    }
+
+    /// Returns the URL of the faucet.
+    pub fn faucet_url(&self) -> String {
+        format!("http://{}/faucet", self.address)
+    }
+
+    /// Returns the chain being served.
+    pub fn chain(&self) -> &FakeChain {
+        &self.chain
+    }
+}
+
+impl Drop for MockRpc {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+async fn serve(listener: TcpListener, chain: Arc<FakeChain>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let chain = chain.clone();
+        tokio::spawn(async move {
+            if let Err(error) = handle(stream, &chain).await {
+                eprintln!("mock RPC: {error}");
+            }
+        });
+    }
+}
+
+/// Answers a single HTTP request and closes the connection.
+async fn handle(stream: TcpStream, chain: &FakeChain) -> io::Result<()> {
+    let mut stream = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    stream.read_line(&mut request_line).await?;
+    let path = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or("/")
+        .to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body).await?;
+
+    let (status, response) = match serde_json::from_slice::<Value>(&body) {
+        Ok(request) if path == "/faucet" => match faucet(chain, request).await {
+            Ok(outcome) => ("200 OK", outcome),
+            Err(error) => ("400 Bad Request", json!({ "error": error })),
+        },
+        Ok(request) => ("200 OK", rpc(chain, request).await),
+        Err(error) => ("400 Bad Request", json!({ "error": error.to_string() })),
+    };
+
+    let response = response.to_string();
+    let stream = stream.get_mut();
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .as_bytes(),
+        )
+        .await?;
+    stream.shutdown().await
+}
+
+/// Answers a faucet request, which either creates an account or sends
+/// tokens to an existing one.
+async fn faucet(chain: &FakeChain, request: Value) -> Result<Value, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged, rename_all = "camelCase")]
+    enum Request {
+        #[serde(rename_all = "camelCase")]
+        CreateAccount {
+            new_account_id: AccountId,
+            new_account_public_key: PublicKey,
+        },
+        #[serde(rename_all = "camelCase")]
+        SendTokens { account_id: AccountId },
+    }
+    let request: Request = serde_json::from_value(request).map_err(|error| error.to_string())?;
+    let network_name = |account_id: &AccountId| {
+        NetworkName::try_from(account_id).map_err(|_| format!("unknown network for {account_id}"))
+    };
+    let outcome = match request {
+        Request::CreateAccount {
+            new_account_id,
+            new_account_public_key,
+        } => {
+            chain
+                .request_faucet(
+                    network_name(&new_account_id)?,
+                    &new_account_id,
+                    &new_account_public_key,
+                )
+                .await
+        }
+        Request::SendTokens { account_id } => {
+            chain
+                .request_tokens(network_name(&account_id)?, &account_id)
+                .await
+        }
+    }
+    .map_err(|error| error.to_string())?;
+    serde_json::to_value(outcome).map_err(|error| error.to_string())
+}
+
+/// Answers a JSON-RPC request.
+async fn rpc(chain: &FakeChain, request: Value) -> Value {
+    let id = request["id"].clone();
+    let params = request["params"].clone();
+    let result = match request["method"].as_str().unwrap_or_default() {
+        "query" => query(chain, params).await,
+        "block" => Ok(block(chain)),
+        "broadcast_tx_commit" => broadcast_tx_commit(chain, params).await,
+        "send_tx" => send_tx(chain, params).await,
+        "tx" => tx_status(chain, params).await,
+        method => Err(json!({
+            "code": -32601,
+            "message": "Method not found",
+            "data": method,
+            "name": "REQUEST_VALIDATION_ERROR",
+            "cause": { "name": "METHOD_NOT_FOUND", "info": { "method_name": method } },
+        })),
+    };
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    }
+}
+
+async fn query(chain: &FakeChain, params: Value) -> Result<Value, Value> {
+    let request: RpcQueryRequest = parse_params(params)?;
+    let block_hash = chain.block_hash();
+    let unknown_account = |account_id: &AccountId| {
+        handler_error(RpcQueryError::UnknownAccount {
+            requested_account_id: account_id.clone(),
+            block_height: BLOCK_HEIGHT,
+            block_hash,
+        })
+    };
+
+    let mut response = match &request.request {
+        QueryRequest::ViewAccount { account_id } => to_result(
+            chain
+                .view_account(network(account_id)?, account_id)
+                .await
+                .map_err(|_| unknown_account(account_id))?,
+        )?,
+        QueryRequest::ViewAccessKey {
+            account_id,
+            public_key,
+        } => {
+            let network_name = network(account_id)?;
+            if chain.view_account(network_name, account_id).await.is_err() {
+                return Err(unknown_account(account_id));
+            }
+            to_result(
+                chain
+                    .access_key(network_name, account_id, public_key)
+                    .await
+                    .map_err(|_| {
+                        handler_error(RpcQueryError::UnknownAccessKey {
+                            public_key: public_key.clone(),
+                            block_height: BLOCK_HEIGHT,
+                            block_hash,
+                        })
+                    })?,
+            )?
+        }
+        QueryRequest::ViewAccessKeyList { account_id } => to_result(AccessKeyList {
+            keys: chain
+                .list_keys(network(account_id)?, account_id)
+                .await
+                .map_err(|_| unknown_account(account_id))?,
+        })?,
+        QueryRequest::CallFunction {
+            account_id,
+            method_name,
+            args,
+        } => {
+            let network_name = network(account_id)?;
+            if chain.view_account(network_name, account_id).await.is_err() {
+                return Err(unknown_account(account_id));
+            }
+            let view = chain
+                .call_view(network_name, account_id, method_name, args.to_vec())
+                .await
+                .map_err(|error| {
+                    handler_error(RpcQueryError::ContractExecutionError {
+                        vm_error: error.to_string(),
+                        block_height: BLOCK_HEIGHT,
+                        block_hash,
+                    })
+                })?;
+            to_result(CallResult {
+                result: view.result,
+                logs: view.logs,
+            })?
+        }
+        request => return Err(internal_error(format!("unsupported query {request:?}"))),
+    };
+
+    // The view is flattened into the response along with the block:
+    response["block_height"] = json!(BLOCK_HEIGHT);
+    response["block_hash"] = to_result(block_hash)?;
+    Ok(response)
+}
+
+fn block(chain: &FakeChain) -> Value {
+    let hash = chain.block_hash();
+    let header = BlockHeaderView {
+        height: BLOCK_HEIGHT,
+        prev_height: None,
+        epoch_id: CryptoHash::default(),
+        next_epoch_id: CryptoHash::default(),
+        hash,
+        prev_hash: CryptoHash::default(),
+        prev_state_root: CryptoHash::default(),
+        block_body_hash: None,
+        chunk_receipts_root: CryptoHash::default(),
+        chunk_headers_root: CryptoHash::default(),
+        chunk_tx_root: CryptoHash::default(),
+        outcome_root: CryptoHash::default(),
+        chunks_included: 0,
+        challenges_root: CryptoHash::default(),
+        timestamp: 0,
+        timestamp_nanosec: 0,
+        random_value: CryptoHash::default(),
+        validator_proposals: vec![],
+        chunk_mask: vec![],
+        gas_price: 100_000_000,
+        block_ordinal: Some(BLOCK_HEIGHT),
+        rent_paid: 0,
+        validator_reward: 0,
+        total_supply: 0,
+        challenges_result: vec![],
+        last_final_block: hash,
+        last_ds_final_block: hash,
+        next_bp_hash: CryptoHash::default(),
+        block_merkle_root: CryptoHash::default(),
+        epoch_sync_data_hash: None,
+        approvals: vec![],
+        signature: Signature::empty(KeyType::ED25519),
+        latest_protocol_version: 0,
+        chunk_endorsements: None,
+    };
+    serde_json::to_value(BlockView {
+        author: "test.near".parse().unwrap(),
+        header,
+        chunks: vec![],
+    })
+    .expect("blocks should serialize")
+}
+
+async fn broadcast_tx_commit(chain: &FakeChain, params: Value) -> Result<Value, Value> {
+    let [encoded]: [String; 1] = parse_params(params)?;
+    let signed: SignedTransaction = tx::decode(&encoded).map_err(invalid_params)?;
+    let network_name = network(signed.transaction.signer_id())?;
+    let outcome = chain
+        .broadcast(network_name, signed, WaitUntil::Final)
+        .await
+        .map_err(tx_error)?;
+    to_result(outcome.outcome)
+}
+
+async fn send_tx(chain: &FakeChain, params: Value) -> Result<Value, Value> {
+    let request: RpcSendTransactionRequest = parse_params(params)?;
+    let network_name = network(request.signed_transaction.transaction.signer_id())?;
+    let outcome = chain
+        .broadcast(
+            network_name,
+            request.signed_transaction,
+            wait_until(request.wait_until),
+        )
+        .await
+        .map_err(tx_error)?;
+    tx_response(outcome)
+}
+
+async fn tx_status(chain: &FakeChain, params: Value) -> Result<Value, Value> {
+    #[derive(Deserialize)]
+    struct Request {
+        tx_hash: CryptoHash,
+        sender_account_id: AccountId,
+        #[serde(default)]
+        wait_until: TxExecutionStatus,
+    }
+    let request: Request = parse_params(params)?;
+    let outcome = chain
+        .tx_status(
+            network(&request.sender_account_id)?,
+            request.tx_hash,
+            &request.sender_account_id,
+            wait_until(request.wait_until),
+        )
+        .await
+        .map_err(tx_error)?;
+    tx_response(outcome)
+}
+
+/// Serializes an outcome the way `send_tx` and `tx` return it.
+fn tx_response(outcome: tx::TxOutcome) -> Result<Value, Value> {
+    let mut response = match outcome.outcome {
+        Some(outcome) => to_result(outcome)?,
+        None => json!({}),
+    };
+    response["final_execution_status"] = to_result(outcome.status)?;
+    Ok(response)
+}
+
+fn wait_until(status: TxExecutionStatus) -> WaitUntil {
+    match status {
+        TxExecutionStatus::None | TxExecutionStatus::Included => WaitUntil::Included,
+        TxExecutionStatus::ExecutedOptimistic | TxExecutionStatus::Executed => WaitUntil::Executed,
+        TxExecutionStatus::IncludedFinal | TxExecutionStatus::Final => WaitUntil::Final,
+    }
+}
+
+fn network(account_id: &AccountId) -> Result<NetworkName, Value> {
+    NetworkName::try_from(account_id)
+        .map_err(|_| invalid_params(format!("unknown network for {account_id}")))
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, Value> {
+    serde_json::from_value(params).map_err(|error| invalid_params(error.to_string()))
+}
+
+fn to_result(value: impl serde::Serialize) -> Result<Value, Value> {
+    serde_json::to_value(value).map_err(|error| internal_error(error.to_string()))
+}
+
+/// Returns the error a node returns for a transaction that failed to be
+/// sent or looked up.
+fn tx_error(error: TxError) -> Value {
+    match error {
+        TxError::Send(RpcError::Failed(JsonRpcError::ServerError(
+            JsonRpcServerError::HandlerError(error),
+        ))) => match &error {
+            // The context of an invalid transaction is only sent as data:
+            RpcTransactionError::InvalidTransaction { context } => {
+                let mut response = handler_error(&error);
+                response["data"] = json!({
+                    "TxExecutionError": TxExecutionError::InvalidTxError(context.clone()),
+                });
+                response
+            }
+            _ => handler_error(&error),
+        },
+        error => internal_error(error.to_string()),
+    }
+}
+
+fn handler_error(cause: impl serde::Serialize) -> Value {
+    let cause = serde_json::to_value(cause).expect("errors should serialize");
+    json!({
+        "code": -32000,
+        "message": "Server error",
+        "data": cause.to_string(),
+        "name": "HANDLER_ERROR",
+        "cause": cause,
+    })
+}
+
+fn invalid_params(message: impl ToString) -> Value {
+    let message = message.to_string();
+    json!({
+        "code": -32602,
+        "message": "Invalid params",
+        "data": message,
+        "name": "REQUEST_VALIDATION_ERROR",
+        "cause": { "name": "PARSE_ERROR", "info": { "error_message": message } },
+    })
+}
+
+fn internal_error(message: String) -> Value {
+    json!({
+        "code": -32000,
+        "message": "Server error",
+        "data": message,
+        "name": "INTERNAL_ERROR",
+        "cause": { "name": "INTERNAL_ERROR", "info": { "error_message": message } },
+    })
+}
@@ -0,0 +1,73 @@
+// This is free and unencumbered software released into the public domain.
+
+use near_api::NearToken;
+use serde::{Deserialize, Serialize};
+
+/// The metadata of a NEP-141 fungible token, as returned by `ft_metadata`
+/// (NEP-148), without the fields this program doesn't use.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FungibleTokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    /// How many of the smallest units make one token, as a power of ten.
+    pub decimals: u8,
+}
+
+/// The storage balance of an account with a contract, as specified by
+/// NEP-145.
+#[derive(Clone, Debug, Deserialize)]
+pub struct StorageBalance {
+    pub total: NearToken,
+}
+
+/// The bounds of the storage balance of a contract, as specified by NEP-145.
+#[derive(Clone, Debug, Deserialize)]
+pub struct StorageBalanceBounds {
+    /// The deposit that registers an account with the contract.
+    pub min: NearToken,
+}
+
+/// Formats an amount in the smallest units of a token as a decimal number
+/// of tokens, without trailing zeros, e.g. `1.5` for `1500000` with six
+/// decimals.
+pub fn format_amount(amount: u128, decimals: u8) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    match fraction.trim_end_matches('0') {
+        "" => whole.into(),
+        fraction => format!("{whole}.{fraction}"),
+    }
+}
+
+/// Parses a decimal number of tokens, e.g. `1.5`, into the smallest units
+/// of a token with the given decimals.
+pub fn parse_amount(input: &str, decimals: u8) -> Result<u128, String> {
+    let invalid = || format!("invalid amount `{input}`: expected a number like 1.5");
+    let (whole, fraction) = input.trim().split_once('.').unwrap_or((input.trim(), ""));
+    if whole.is_empty() && fraction.is_empty()
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "invalid amount `{input}`: the token has only {decimals} decimals"
+        ));
+    }
+
+    let digits = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+    match digits.trim_start_matches('0') {
+        "" => Ok(0),
+        digits => digits
+            .parse()
+            .map_err(|_| format!("invalid amount `{input}`: too large")),
+    }
+}
@@ -0,0 +1,51 @@
+// This is free and unencumbered software released into the public domain.
+
+use near_api::AccountId;
+use serde::{Deserialize, Serialize};
+
+/// The metadata of a NEP-171 non-fungible token contract, as returned by
+/// `nft_metadata` (NEP-177), without the fields this program doesn't use.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NftContractMetadata {
+    pub name: String,
+    pub symbol: String,
+    /// The URL that relative media links of the tokens are resolved against.
+    #[serde(default)]
+    pub base_uri: Option<String>,
+}
+
+/// A non-fungible token, as returned by `nft_tokens_for_owner` (NEP-181).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Token {
+    pub token_id: String,
+    pub owner_id: AccountId,
+    #[serde(default)]
+    pub metadata: Option<TokenMetadata>,
+}
+
+/// The metadata of a non-fungible token (NEP-177), without the fields this
+/// program doesn't use.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub media: Option<String>,
+}
+
+/// Returns the link to the media of a token, resolving a relative one
+/// against the base URI of its contract.
+pub fn media_url(base_uri: Option<&str>, media: &str) -> String {
+    match base_uri {
+        Some(base_uri) if !media.contains("://") && !media.starts_with("data:") => {
+            format!(
+                "{}/{}",
+                base_uri.trim_end_matches('/'),
+                media.trim_start_matches('/')
+            )
+        }
+        _ => media.into(),
+    }
+}
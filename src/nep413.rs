@@ -0,0 +1,99 @@
+// This is free and unencumbered software released into the public domain.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use near_api::{
+    errors::SignerError, near_primitives::hash::hash, signer::NEP413Payload, AccountId, SignerTrait,
+};
+use near_crypto::{PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+
+/// The prefix that keeps NEP-413 messages from being valid transactions.
+const PREFIX: u32 = (1 << 31) + 413;
+
+/// A NEP-413 signed message, in the JSON shape used by NEAR wallets,
+/// along with the payload needed to verify it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedMessage {
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+    /// The signature as base64 raw bytes, without the key type.
+    pub signature: String,
+    pub message: String,
+    pub recipient: String,
+    /// The 32-byte nonce as base64.
+    pub nonce: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+}
+
+impl SignedMessage {
+    /// Returns the payload that was signed.
+    pub fn payload(&self) -> Result<NEP413Payload, String> {
+        Ok(NEP413Payload {
+            message: self.message.clone(),
+            nonce: parse_nonce(&self.nonce)?,
+            recipient: self.recipient.clone(),
+            callback_url: self.callback_url.clone(),
+        })
+    }
+
+    /// Checks that the signature matches the payload and public key.
+    pub fn verify(&self) -> Result<bool, String> {
+        let bytes = BASE64
+            .decode(&self.signature)
+            .map_err(|error| format!("invalid signature: {error}"))?;
+        let signature = Signature::from_parts(self.public_key.key_type(), &bytes)
+            .map_err(|error| format!("invalid signature: {error}"))?;
+        let payload = self.payload()?;
+        Ok(signature.verify(&message_hash(&payload), &self.public_key))
+    }
+}
+
+/// Signs the payload on behalf of the account with the given key.
+pub async fn sign(
+    signer: &(impl SignerTrait + Sync),
+    account_id: AccountId,
+    public_key: PublicKey,
+    payload: NEP413Payload,
+) -> Result<SignedMessage, SignerError> {
+    let signature = signer
+        .sign_message_nep413(account_id.clone(), public_key.clone(), payload.clone())
+        .await?;
+    Ok(SignedMessage {
+        account_id,
+        public_key,
+        signature: BASE64.encode(signature_bytes(&signature)),
+        message: payload.message,
+        recipient: payload.recipient,
+        nonce: BASE64.encode(payload.nonce),
+        callback_url: payload.callback_url,
+    })
+}
+
+/// Returns the hash that is signed for a payload.
+pub fn message_hash(payload: &NEP413Payload) -> [u8; 32] {
+    let mut bytes = PREFIX.to_le_bytes().to_vec();
+    borsh::to_writer(&mut bytes, payload).expect("payloads should serialize");
+    hash(&bytes).0
+}
+
+/// Returns the raw bytes of a signature.
+fn signature_bytes(signature: &Signature) -> Vec<u8> {
+    // The Borsh encoding is the key type followed by the raw bytes:
+    borsh::to_vec(signature).expect("signatures should serialize")[1..].to_vec()
+}
+
+/// Parses a 32-byte nonce from base64.
+pub fn parse_nonce(input: &str) -> Result<[u8; 32], String> {
+    BASE64
+        .decode(input.trim())
+        .map_err(|error| format!("invalid base64: {error}"))?
+        .try_into()
+        .map_err(|bytes: Vec<u8>| format!("expected 32 bytes, got {}", bytes.len()))
+}
+
+/// Returns a random nonce.
+pub fn random_nonce() -> [u8; 32] {
+    rand::random()
+}
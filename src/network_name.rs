@@ -1,8 +1,11 @@
 // This is free and unencumbered software released into the public domain.
 
+use clientele::crates::clap::ValueEnum;
 use near_api::{AccountId, NetworkConfig};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
 pub enum NetworkName {
     Testnet,
     Mainnet,
@@ -36,7 +39,9 @@ impl TryFrom<&AccountId> for NetworkName {
         match value.as_str().split(".").last() {
             Some("near") => Ok(Self::Mainnet),
             Some("testnet") => Ok(Self::Testnet),
-            _ => Err(NetworkNameError::UnknownNetwork),
+            _ => crate::config::get()
+                .network
+                .ok_or(NetworkNameError::UnknownNetwork),
         }
     }
 }
@@ -0,0 +1,101 @@
+// This is free and unencumbered software released into the public domain.
+
+//! Questions asked on the terminal, for commands that run interactively.
+//!
+//! Questions are printed to standard error and answered a line at a time, so
+//! that tests can answer them from a buffer.
+
+use crate::AccountError;
+use color_print::{ceprint, ceprintln};
+use std::io::BufRead;
+
+pub struct Prompt<'a> {
+    input: &'a mut dyn BufRead,
+}
+
+impl<'a> Prompt<'a> {
+    pub fn new(input: &'a mut dyn BufRead) -> Self {
+        Self { input }
+    }
+
+    /// Asks a question, returning the trimmed answer or the default if the
+    /// answer is empty.
+    pub fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String, AccountError> {
+        loop {
+            match default {
+                Some(default) => ceprint!("<s,c>?</> {question} [{default}] "),
+                None => ceprint!("<s,c>?</> {question} "),
+            }
+            let answer = self.read_line()?;
+            match (answer.as_str(), default) {
+                ("", Some(default)) => return Ok(default.into()),
+                ("", None) => continue,
+                (answer, _) => return Ok(answer.into()),
+            }
+        }
+    }
+
+    /// Asks a question until the answer parses, printing why it didn't.
+    pub fn ask_with<T>(
+        &mut self,
+        question: &str,
+        default: Option<&str>,
+        mut parse: impl FnMut(&str) -> Result<T, String>,
+    ) -> Result<T, AccountError> {
+        loop {
+            let answer = self.ask(question, default)?;
+            match parse(&answer) {
+                Ok(value) => return Ok(value),
+                Err(reason) => ceprintln!("<s,y>!</> {reason}"),
+            }
+        }
+    }
+
+    /// Asks to choose one of a list of choices by number, returning its index.
+    pub fn choose(
+        &mut self,
+        question: &str,
+        choices: &[String],
+        default: usize,
+    ) -> Result<usize, AccountError> {
+        ceprintln!("<s,c>?</> {question}");
+        for (index, choice) in choices.iter().enumerate() {
+            ceprintln!("  <s>{}</>) {choice}", index + 1);
+        }
+        let default = (default + 1).to_string();
+        self.ask_with("Number", Some(&default), |answer| {
+            match answer.parse::<usize>() {
+                Ok(number) if (1..=choices.len()).contains(&number) => Ok(number - 1),
+                _ => Err(format!("choose a number from 1 to {}", choices.len())),
+            }
+        })
+    }
+
+    /// Asks a yes-or-no question.
+    pub fn confirm(&mut self, question: &str, default: bool) -> Result<bool, AccountError> {
+        let hint = if default { "Y/n" } else { "y/N" };
+        loop {
+            ceprint!("<s,c>?</> {question} [{hint}] ");
+            match self.read_line()?.to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => ceprintln!("<s,y>!</> answer yes or no"),
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> Result<String, AccountError> {
+        let mut line = String::new();
+        let read = self
+            .input
+            .read_line(&mut line)
+            .map_err(|error| AccountError::Input("failed to read the answer".into(), error))?;
+        if read == 0 {
+            // The input was closed before the question was answered:
+            eprintln!();
+            return Err(AccountError::Usage("cancelled".into()));
+        }
+        Ok(line.trim().to_string())
+    }
+}
@@ -0,0 +1,464 @@
+// This is free and unencumbered software released into the public domain.
+
+//! The local registries of accounts, with a file per account in
+//! `near/<network>/` below the directory of each registry:
+//!
+//! - The user registry in `~/.asimov/accounts/`.
+//! - A project registry in the nearest `.asimov/accounts/` above the
+//!   current directory, if there is one, whose accounts take precedence.
+//!
+//! The format of each registry is versioned by the `VERSION` file in its
+//! directory:
+//!
+//! 1. Without a marker: account files are either empty or hold JSON
+//!    metadata, and the files of deleted accounts are renamed with a
+//!    leading dot.
+//! 2. Account files always hold JSON metadata, and the files of deleted
+//!    accounts are moved to `near/<network>/.deleted/`.
+//!
+//! This program reads and writes both, and `registry migrate` upgrades the
+//! first to the second. A registry of a newer version is refused.
+//!
+//! Processes that change a registry hold an exclusive lock on the `.lock`
+//! file in its directory while they read and write, and replace files by
+//! renaming a temporary file over them, so that concurrent processes sharing
+//! a registry, even with different home directories, neither lose changes
+//! nor see partial files.
+
+use crate::network_name::NetworkName;
+use chrono::{DateTime, Utc};
+use clientele::crates::clap::ValueEnum;
+use near_api::{AccountId, NearToken};
+use near_crypto::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Write as _,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Which registry an account is kept in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// The registry of the project in or above the current directory.
+    Project,
+    /// The registry in the home directory.
+    #[default]
+    User,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::User => "user",
+        }
+    }
+
+    /// Returns the directory of the registry of this scope. Without a
+    /// project registry, the project scope is the current directory's.
+    pub fn path(&self) -> Option<PathBuf> {
+        match self {
+            Self::Project => project_path().or_else(|| {
+                Some(
+                    std::env::current_dir()
+                        .ok()?
+                        .join(".asimov")
+                        .join("accounts"),
+                )
+            }),
+            Self::User => accounts_path(),
+        }
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// Returns the directory of the user registry of all networks, and of the
+/// other files of this program.
+pub fn accounts_path() -> Option<PathBuf> {
+    Some(dirs::home_dir()?.join(".asimov").join("accounts"))
+}
+
+/// Returns the directory of the project registry: the nearest
+/// `.asimov/accounts` in or above the current directory, other than the
+/// user registry.
+pub fn project_path() -> Option<PathBuf> {
+    let user_path = accounts_path();
+    std::env::current_dir()
+        .ok()?
+        .ancestors()
+        .map(|dir| dir.join(".asimov").join("accounts"))
+        .find(|path| path.is_dir() && Some(path) != user_path.as_ref())
+}
+
+/// Returns the path of the registry directory of a network in a scope.
+pub fn network_path(scope: Scope, network_name: NetworkName) -> Option<PathBuf> {
+    Some(scope.path()?.join("near").join(network_name.as_str()))
+}
+
+/// Returns the scope of the registry that holds an account, if any. An
+/// account in both is taken from the project registry.
+pub fn account_scope(network_name: NetworkName, account_id: &AccountId) -> Option<Scope> {
+    [Scope::Project, Scope::User].into_iter().find(|&scope| {
+        scoped_account_path(scope, network_name, account_id).is_some_and(|path| path.is_file())
+    })
+}
+
+/// Returns the path of the registry file of an account, in the registry
+/// that holds it or else the user registry.
+pub fn account_path(network_name: NetworkName, account_id: &AccountId) -> Option<PathBuf> {
+    let scope = account_scope(network_name, account_id).unwrap_or_default();
+    scoped_account_path(scope, network_name, account_id)
+}
+
+/// Returns the path of the registry file of an account in a scope.
+pub fn scoped_account_path(
+    scope: Scope,
+    network_name: NetworkName,
+    account_id: &AccountId,
+) -> Option<PathBuf> {
+    Some(network_path(scope, network_name)?.join(account_id.as_str()))
+}
+
+/// Returns where the registry file of a deleted account is kept.
+pub fn deleted_path(network_name: NetworkName, account_id: &AccountId) -> Option<PathBuf> {
+    let path = account_path(network_name, account_id)?;
+    Some(path.parent()?.join(".deleted").join(account_id.as_str()))
+}
+
+/// The format version of the registry that this program writes.
+pub const VERSION: u32 = 2;
+
+/// The format version of a registry without a version marker.
+pub const UNMARKED_VERSION: u32 = 1;
+
+/// The error of a registry whose format is newer than this program knows.
+#[derive(Debug)]
+pub struct UnsupportedVersion(pub u32);
+
+impl std::fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the account registry has format version {}, but this version of asimov-account only supports up to version {VERSION}; upgrade asimov-account to use it",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedVersion {}
+
+/// Returns the path of the version marker of the user registry.
+pub fn version_path() -> Option<PathBuf> {
+    Some(accounts_path()?.join("VERSION"))
+}
+
+/// Returns the format version of the user registry, or `None` if there is
+/// no registry yet.
+pub fn version() -> std::io::Result<Option<u32>> {
+    scoped_version(Scope::User)
+}
+
+/// Returns the format version of the registry of a scope, or `None` if
+/// there is no registry yet.
+pub fn scoped_version(scope: Scope) -> std::io::Result<Option<u32>> {
+    let Some(dir) = scope.path() else {
+        return Err(std::io::ErrorKind::NotFound.into());
+    };
+    version_of(&dir)
+}
+
+fn version_of(dir: &Path) -> std::io::Result<Option<u32>> {
+    let path = dir.join("VERSION");
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => contents.trim().parse().map(Some).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unreadable version marker {}", path.display()),
+            )
+        }),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            Ok(dir.join("near").exists().then_some(UNMARKED_VERSION))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Checks that this program understands the format of the user registry,
+/// and of the project registry if there is one.
+pub fn check_version() -> std::io::Result<Option<u32>> {
+    if let Some(dir) = project_path() {
+        check_version_of(&dir)?;
+    }
+    check_version_of(&accounts_path().ok_or(std::io::ErrorKind::NotFound)?)
+}
+
+fn check_version_of(dir: &Path) -> std::io::Result<Option<u32>> {
+    match version_of(dir)? {
+        Some(version) if version > VERSION => {
+            Err(std::io::Error::other(UnsupportedVersion(version)))
+        }
+        version => Ok(version),
+    }
+}
+
+/// Records the format version of the registry of a scope.
+pub fn set_version(scope: Scope, version: u32) -> std::io::Result<()> {
+    let Some(dir) = scope.path() else {
+        return Err(std::io::ErrorKind::NotFound.into());
+    };
+    write_atomically(&dir.join("VERSION"), format!("{version}\n").as_bytes())
+}
+
+/// Creates the registry of a scope, in the current format, unless it
+/// already exists. Returns its directory.
+pub fn create(scope: Scope) -> std::io::Result<PathBuf> {
+    let Some(dir) = scope.path() else {
+        return Err(std::io::ErrorKind::NotFound.into());
+    };
+    if check_version_of(&dir)?.is_none() {
+        write_atomically(&dir.join("VERSION"), format!("{VERSION}\n").as_bytes())?;
+    }
+    Ok(dir)
+}
+
+/// What is known locally about an account, stored as JSON in its registry
+/// file. An empty file has no metadata.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AccountMetadata {
+    /// The function-call access keys granted with `grant`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<KeyMetadata>,
+    /// Short names that may be given instead of the account's name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Free-form labels for finding the account in listings.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// The staking pools the account has staked with using `stake`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pools: Vec<AccountId>,
+    /// When the account was added to the registry, unknown for entries
+    /// written by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+}
+
+/// The scope of a function-call access key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyMetadata {
+    pub public_key: PublicKey,
+    pub contract: AccountId,
+    /// The methods the key may call, or all of them if empty.
+    #[serde(default)]
+    pub methods: Vec<String>,
+    /// The allowance the key was granted, or `None` if unlimited.
+    #[serde(default)]
+    pub allowance: Option<NearToken>,
+    pub granted: DateTime<Utc>,
+}
+
+impl AccountMetadata {
+    /// Reads the metadata from an account's registry file.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        if contents.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Writes the metadata to an account's registry file, replacing it
+    /// atomically. The registry should be locked while reading and writing.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        write_atomically(path, contents.as_bytes())
+    }
+
+    /// Adds the keys, aliases, labels, pools, and creation time of other
+    /// metadata that this lacks.
+    pub fn merge(&mut self, other: Self) {
+        self.created = self.created.or(other.created);
+        for key in other.keys {
+            if self.key(&key.public_key).is_none() {
+                self.keys.push(key);
+            }
+        }
+        for alias in other.aliases {
+            if !self.aliases.contains(&alias) {
+                self.aliases.push(alias);
+            }
+        }
+        for label in other.labels {
+            if !self.labels.contains(&label) {
+                self.labels.push(label);
+            }
+        }
+        for pool in other.pools {
+            if !self.pools.contains(&pool) {
+                self.pools.push(pool);
+            }
+        }
+    }
+
+    pub fn key(&self, public_key: &PublicKey) -> Option<&KeyMetadata> {
+        self.keys.iter().find(|key| key.public_key == *public_key)
+    }
+}
+
+/// Returns the accounts in the registries of a network, skipping deleted
+/// ones.
+pub fn accounts(network_name: NetworkName) -> std::io::Result<Vec<AccountId>> {
+    let mut accounts = scoped_accounts(Scope::Project, network_name)?;
+    accounts.extend(scoped_accounts(Scope::User, network_name)?);
+    accounts.sort();
+    accounts.dedup();
+    Ok(accounts)
+}
+
+/// Returns the accounts in the registry of a network in a scope, skipping
+/// deleted ones.
+pub fn scoped_accounts(scope: Scope, network_name: NetworkName) -> std::io::Result<Vec<AccountId>> {
+    check_version()?;
+    let Some(dir) = network_path(scope, network_name) else {
+        return Err(std::io::ErrorKind::NotFound.into());
+    };
+    let mut accounts = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+            // Deleted accounts in the first version, and temporary files, are
+            // hidden with a leading dot, which is not a valid name:
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<AccountId>().ok())
+            .collect::<Vec<_>>(),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(error) => return Err(error),
+    };
+    accounts.sort();
+    Ok(accounts)
+}
+
+/// Returns the aliases of the accounts in the registries of all networks.
+pub fn aliases() -> BTreeMap<String, AccountId> {
+    [NetworkName::Testnet, NetworkName::Mainnet]
+        .into_iter()
+        .flat_map(|network_name| {
+            accounts(network_name)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |account_id| (network_name, account_id))
+        })
+        .flat_map(|(network_name, account_id)| {
+            let metadata = account_path(network_name, &account_id)
+                .and_then(|path| AccountMetadata::load(&path).ok())
+                .unwrap_or_default();
+            metadata
+                .aliases
+                .into_iter()
+                .map(move |alias| (alias, account_id.clone()))
+        })
+        .collect()
+}
+
+/// Checks that an alias can't be mistaken for an account name, which
+/// always has a dot unless it is a top-level account.
+pub fn check_alias(alias: &str) -> Result<(), String> {
+    let valid = !alias.is_empty()
+        && alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!(
+            "invalid alias `{alias}`: use letters, digits, `-` and `_`, without dots"
+        ));
+    }
+    Ok(())
+}
+
+/// Parses an account name, or an alias of an account in the registry.
+pub fn resolve(name: &str) -> Result<AccountId, String> {
+    if check_alias(name).is_ok() {
+        if let Some(account_id) = aliases().remove(name) {
+            return Ok(account_id);
+        }
+    }
+    name.parse().map_err(|error| format!("{error}"))
+}
+
+/// An exclusive lock on one or more registries, released when dropped.
+#[derive(Debug)]
+pub struct RegistryLock {
+    files: Vec<File>,
+}
+
+/// Waits until no other process is changing the registry of a scope, and
+/// locks it, after checking that this program understands its format.
+///
+/// The lock is taken once around each change; taking it again while it is
+/// held would wait forever.
+pub fn lock(scope: Scope) -> std::io::Result<RegistryLock> {
+    let Some(dir) = scope.path() else {
+        return Err(std::io::ErrorKind::NotFound.into());
+    };
+    std::fs::create_dir_all(&dir)?;
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join(".lock"))?;
+    // Called through the trait, as `File::lock` is newer than our minimum Rust version:
+    fs4::FileExt::lock(&file)?;
+
+    check_version_of(&dir)?;
+    // A new registry starts out in the current format:
+    create(scope)?;
+    Ok(RegistryLock { files: vec![file] })
+}
+
+/// Locks the project registry, if there is one, and the user registry, for
+/// changes that may touch both. They are always locked in that order.
+pub fn lock_all() -> std::io::Result<RegistryLock> {
+    let mut files = Vec::new();
+    if project_path().is_some() {
+        files.extend(lock(Scope::Project)?.files);
+    }
+    files.extend(lock(Scope::User)?.files);
+    Ok(RegistryLock { files })
+}
+
+/// Replaces a file by writing a temporary file next to it and renaming it
+/// over the original, so that readers see either the old or new contents.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(std::io::ErrorKind::InvalidInput.into());
+    };
+    std::fs::create_dir_all(dir)?;
+    // The leading dot keeps the temporary file out of account listings, and
+    // the process and a counter keep concurrent writers apart:
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let temp_path = dir.join(format!(
+        ".{}.{}-{}.tmp",
+        name.to_string_lossy(),
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+    if result.is_err() {
+        std::fs::remove_file(&temp_path).ok();
+    }
+    result
+}
@@ -0,0 +1,257 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::network_name::NetworkName;
+use clientele::crates::{clap::Args, duration_str};
+use near_api::{
+    errors::{ExecuteTransactionError, FaucetError, KeyStoreError, QueryError},
+    NetworkConfig, RPCEndpoint, RetryMethod,
+};
+use near_jsonrpc_client::{
+    errors::{JsonRpcError, JsonRpcServerError},
+    methods::query::RpcQueryError,
+};
+use reqwest::Url;
+use std::{future::Future, time::Duration};
+
+/// Timeout, retry, and backoff policy for RPC and faucet calls.
+#[derive(Clone, Debug, Args)]
+pub struct RpcOptions {
+    /// The maximum time to wait for a single RPC or faucet request. For example `30s` or `2m`.
+    #[clap(long, value_name = "DURATION", default_value = "30s", value_parser = parse_duration, global = true)]
+    pub rpc_timeout: Duration,
+
+    /// The number of attempts to make for a request that fails with a transient error.
+    #[clap(long, value_name = "COUNT", default_value_t = 5, value_parser = clap::value_parser!(u8).range(1..), global = true)]
+    pub rpc_retries: u8,
+
+    /// The delay before the first retry, doubled after every further attempt. For example `500ms`.
+    #[clap(long, value_name = "DURATION", default_value = "500ms", value_parser = parse_duration, global = true)]
+    pub rpc_backoff: Duration,
+
+    /// The JSON-RPC endpoint to use instead of the network's, for example a local node.
+    #[clap(long, value_name = "URL", value_parser = parse_rpc_url, global = true)]
+    pub rpc_url: Option<RPCEndpoint>,
+
+    /// The faucet to use on testnet instead of the default, for example a local stand-in.
+    #[clap(long, value_name = "URL", global = true)]
+    pub faucet_url: Option<Url>,
+}
+
+impl Default for RpcOptions {
+    fn default() -> Self {
+        Self {
+            rpc_timeout: Duration::from_secs(30),
+            rpc_retries: 5,
+            rpc_backoff: Duration::from_millis(500),
+            rpc_url: None,
+            faucet_url: None,
+        }
+    }
+}
+
+impl RpcOptions {
+    /// Returns the network configuration with this retry policy applied to every RPC endpoint.
+    ///
+    /// The `--rpc-url` option, or else the RPC URL set for the network in the
+    /// configuration, replaces the default endpoints. Likewise, the
+    /// `--faucet-url` option or the configured faucet replaces testnet's.
+    ///
+    /// near-api retries failed JSON-RPC requests (including broadcasts of an
+    /// already signed transaction) internally, so this is where the backoff
+    /// for those requests is configured.
+    pub fn network_config(&self, network_name: NetworkName) -> NetworkConfig {
+        let mut config = network_name.config();
+        if let Some(endpoint) = self
+            .rpc_url
+            .clone()
+            .or_else(|| crate::config::get().rpc_endpoint(network_name))
+        {
+            config.rpc_endpoints = vec![endpoint];
+        }
+        if network_name == NetworkName::Testnet {
+            if let Some(faucet_url) = self
+                .faucet_url
+                .clone()
+                .or_else(|| crate::config::get().faucet_url())
+            {
+                config.faucet_url = Some(faucet_url);
+            }
+        }
+        for endpoint in &mut config.rpc_endpoints {
+            endpoint.retries = self.rpc_retries;
+            endpoint.retry_method = RetryMethod::ExponentialBackoff {
+                initial_sleep: self.rpc_backoff,
+                factor: 2,
+            };
+        }
+        config
+    }
+
+    /// Returns the delay to wait before the given (zero-based) retry.
+    pub fn backoff(&self, retry: u8) -> Duration {
+        self.rpc_backoff
+            .saturating_mul(2u32.saturating_pow(retry as u32))
+    }
+
+    /// Runs an idempotent request, retrying it with exponential backoff when it
+    /// times out or fails with a transient error.
+    pub async fn retry<T, E, F, Fut>(&self, request: F) -> Result<T, RpcError<E>>
+    where
+        E: Transient,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        self.retry_if(request, RpcError::is_transient).await
+    }
+
+    /// Runs a request, retrying it with exponential backoff when it fails
+    /// with an error that the given check accepts.
+    ///
+    /// Use this for requests that may only be repeated for some errors, such
+    /// as ones that show the request never reached the server.
+    pub async fn retry_if<T, E, F, Fut>(
+        &self,
+        mut request: F,
+        retryable: impl Fn(&RpcError<E>) -> bool,
+    ) -> Result<T, RpcError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut retry = 0;
+        loop {
+            let error = match self.send(request()).await {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+            if !retryable(&error) || retry + 1 >= self.rpc_retries {
+                return Err(error);
+            }
+            tokio::time::sleep(self.backoff(retry)).await;
+            retry += 1;
+        }
+    }
+
+    /// Runs a request exactly once, failing if it takes longer than the timeout.
+    ///
+    /// Use this for requests that must not be repeated blindly, such as
+    /// signing and sending a new transaction.
+    pub async fn send<T, E>(
+        &self,
+        request: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, RpcError<E>> {
+        match tokio::time::timeout(self.rpc_timeout, request).await {
+            Ok(result) => result.map_err(RpcError::Failed),
+            Err(_) => Err(RpcError::Timeout(self.rpc_timeout)),
+        }
+    }
+}
+
+pub(crate) fn parse_duration(input: &str) -> Result<Duration, String> {
+    duration_str::parse(input)
+}
+
+fn parse_rpc_url(input: &str) -> Result<RPCEndpoint, String> {
+    Ok(RPCEndpoint::new(
+        input
+            .parse()
+            .map_err(|error| format!("invalid URL: {error}"))?,
+    ))
+}
+
+/// An error from a request made under [`RpcOptions`].
+#[derive(Debug)]
+pub enum RpcError<E> {
+    /// The request did not complete within the configured timeout.
+    Timeout(Duration),
+    /// The request completed with an error.
+    Failed(E),
+}
+
+impl<E: Transient> RpcError<E> {
+    /// Whether retrying the request might succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Timeout(_) => true,
+            Self::Failed(error) => error.is_transient(),
+        }
+    }
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for RpcError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout(timeout) => write!(f, "request timed out after {timeout:?}"),
+            Self::Failed(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for RpcError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Timeout(_) => None,
+            Self::Failed(error) => Some(error),
+        }
+    }
+}
+
+/// Classifies errors that are worth retrying.
+pub trait Transient {
+    fn is_transient(&self) -> bool;
+}
+
+impl Transient for FaucetError {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::FaucetIsNotDefined(_) => false,
+            Self::SendError(error) => {
+                error.is_timeout()
+                    || error.is_connect()
+                    || error.is_request()
+                    || error
+                        .status()
+                        .is_some_and(|status| status.is_server_error() || status.as_u16() == 429)
+            }
+        }
+    }
+}
+
+// near-api has already retried JSON-RPC requests against each endpoint by the
+// time these errors surface, so only our own timeouts are retried for them.
+
+impl<M: near_jsonrpc_client::methods::RpcMethod> Transient for QueryError<M>
+where
+    M::Error: std::fmt::Debug + std::fmt::Display,
+{
+    fn is_transient(&self) -> bool {
+        false
+    }
+}
+
+impl Transient for KeyStoreError {
+    fn is_transient(&self) -> bool {
+        false
+    }
+}
+
+impl Transient for ExecuteTransactionError {
+    fn is_transient(&self) -> bool {
+        false
+    }
+}
+
+impl Transient for JsonRpcError<RpcQueryError> {
+    fn is_transient(&self) -> bool {
+        use RpcQueryError::*;
+        match self {
+            Self::TransportError(_) => true,
+            Self::ServerError(JsonRpcServerError::InternalError { .. }) => true,
+            Self::ServerError(JsonRpcServerError::ResponseStatusError(_)) => true,
+            Self::ServerError(JsonRpcServerError::HandlerError(error)) => {
+                matches!(error, NoSyncedBlocks | InternalError { .. })
+            }
+            Self::ServerError(_) => false,
+        }
+    }
+}
@@ -0,0 +1,334 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{
+    chain::Chain,
+    network_name::NetworkName,
+    rpc::{RpcError, RpcOptions, Transient},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use borsh::{BorshDeserialize, BorshSerialize};
+use clientele::crates::clap::{Args, ValueEnum};
+use color_print::cprintln;
+use near_api::{
+    advanced::ExecuteSignedTransaction,
+    errors::ExecuteTransactionError,
+    near_primitives::{
+        hash::CryptoHash,
+        transaction::SignedTransaction,
+        types::Nonce,
+        views::{
+            ExecutionOutcomeWithIdView, ExecutionStatusView, FinalExecutionOutcomeView,
+            FinalExecutionStatus, TxExecutionStatus,
+        },
+    },
+    AccountId, NearGas, NearToken, NetworkConfig,
+};
+use near_crypto::PublicKey;
+use near_jsonrpc_client::{
+    errors::{JsonRpcError, JsonRpcServerError},
+    methods::{
+        send_tx::RpcSendTransactionRequest,
+        tx::{
+            RpcTransactionError, RpcTransactionResponse, RpcTransactionStatusRequest,
+            TransactionInfo,
+        },
+    },
+    JsonRpcClient,
+};
+
+/// How far a transaction must have progressed before a command returns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum WaitUntil {
+    /// The transaction is included in a block, which may not be final yet.
+    Included,
+    /// The transaction and all its receipts have been executed.
+    #[default]
+    Executed,
+    /// The transaction and all its receipts have been executed in final blocks.
+    Final,
+}
+
+impl WaitUntil {
+    pub fn status(&self) -> TxExecutionStatus {
+        match self {
+            Self::Included => TxExecutionStatus::Included,
+            Self::Executed => TxExecutionStatus::ExecutedOptimistic,
+            Self::Final => TxExecutionStatus::Final,
+        }
+    }
+}
+
+/// Options for signing a transaction without sending it.
+#[derive(Clone, Debug, Default, Args)]
+pub struct SignOptions {
+    /// Print the signed transaction as base64 instead of sending it. See also `broadcast`.
+    #[clap(long)]
+    pub sign_only: bool,
+
+    /// The public key of the credentials in the keychain to sign with.
+    #[clap(long, value_name = "KEY")]
+    pub public_key: Option<PublicKey>,
+
+    /// The nonce to sign with, i.e. the current nonce of the access key plus one. Signs without contacting the network.
+    #[clap(long, value_name = "NONCE", requires_all = ["sign_only", "public_key", "block_hash"])]
+    pub nonce: Option<Nonce>,
+
+    /// The hash of a recent block to sign with. Signs without contacting the network.
+    #[clap(long, value_name = "HASH", requires_all = ["sign_only", "public_key", "nonce"])]
+    pub block_hash: Option<CryptoHash>,
+}
+
+/// The state of a transaction as reported by the network.
+#[derive(Clone, Debug)]
+pub struct TxOutcome {
+    pub hash: CryptoHash,
+    pub status: TxExecutionStatus,
+    /// The execution outcome, once the transaction has been executed.
+    pub outcome: Option<FinalExecutionOutcomeView>,
+}
+
+impl TxOutcome {
+    fn new(hash: CryptoHash, response: RpcTransactionResponse) -> Self {
+        Self {
+            hash,
+            status: response.final_execution_status,
+            outcome: response
+                .final_execution_outcome
+                .map(|outcome| outcome.into_outcome()),
+        }
+    }
+
+    /// Returns the execution failure, if the transaction failed.
+    pub fn failure(&self) -> Option<String> {
+        match &self.outcome.as_ref()?.status {
+            FinalExecutionStatus::Failure(error) => Some(error.to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TxError {
+    Sign(RpcError<ExecuteTransactionError>),
+    Send(RpcError<JsonRpcError<RpcTransactionError>>),
+}
+
+impl std::fmt::Display for TxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sign(error) => write!(f, "failed to sign transaction: {error}"),
+            Self::Send(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for TxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Sign(error) => Some(error),
+            Self::Send(error) => Some(error),
+        }
+    }
+}
+
+impl Transient for JsonRpcError<RpcTransactionError> {
+    fn is_transient(&self) -> bool {
+        use RpcTransactionError::*;
+        match self {
+            Self::TransportError(_) => true,
+            Self::ServerError(JsonRpcServerError::InternalError { .. }) => true,
+            Self::ServerError(JsonRpcServerError::ResponseStatusError(_)) => true,
+            Self::ServerError(JsonRpcServerError::HandlerError(error)) => matches!(
+                error,
+                TimeoutError
+                    | InternalError { .. }
+                    | UnknownTransaction { .. }
+                    | DoesNotTrackShard
+                    | RequestRouted { .. }
+            ),
+            Self::ServerError(_) => false,
+        }
+    }
+}
+
+/// Returns a JSON-RPC client for the first endpoint of the network.
+pub fn client(network_config: &NetworkConfig) -> JsonRpcClient {
+    let endpoint = &network_config.rpc_endpoints[0];
+    let client = JsonRpcClient::connect(endpoint.url.as_str());
+    match &endpoint.api_key {
+        Some(api_key) => client.header(near_jsonrpc_client::auth::ApiKey::from(api_key.clone())),
+        None => client,
+    }
+}
+
+/// Signs the transaction using the current nonce and block hash from the network.
+pub async fn sign(
+    rpc: &RpcOptions,
+    network_config: &NetworkConfig,
+    transaction: ExecuteSignedTransaction,
+) -> Result<SignedTransaction, TxError> {
+    Ok(rpc
+        .send(transaction.presign_with(network_config))
+        .await
+        .map_err(TxError::Sign)?
+        .tr
+        .signed()
+        .expect("the transaction should be signed"))
+}
+
+/// Signs the transaction with the given nonce and block hash, without contacting the network.
+pub async fn sign_offline(
+    transaction: ExecuteSignedTransaction,
+    public_key: PublicKey,
+    nonce: Nonce,
+    block_hash: CryptoHash,
+) -> Result<SignedTransaction, TxError> {
+    Ok(transaction
+        .presign_offline(public_key, block_hash.into(), nonce)
+        .await
+        .map_err(|error| TxError::Sign(RpcError::Failed(error.into())))?
+        .tr
+        .signed()
+        .expect("the transaction should be signed"))
+}
+
+/// Signs the transaction as requested by the options: offline if they carry
+/// a nonce and block hash, otherwise with the current ones from the network.
+pub async fn sign_with(
+    options: &SignOptions,
+    chain: &dyn Chain,
+    network_name: NetworkName,
+    transaction: ExecuteSignedTransaction,
+) -> Result<SignedTransaction, TxError> {
+    match (&options.public_key, options.nonce, options.block_hash) {
+        (Some(public_key), Some(nonce), Some(block_hash)) => {
+            sign_offline(transaction, public_key.clone(), nonce, block_hash).await
+        }
+        _ => chain.sign(network_name, transaction).await,
+    }
+}
+
+/// Encodes a transaction (signed or not) in the base64 Borsh format used by NEAR tools.
+pub fn encode(transaction: &impl BorshSerialize) -> String {
+    BASE64.encode(borsh::to_vec(transaction).expect("transactions should serialize"))
+}
+
+/// Decodes a transaction (signed or not) from the base64 Borsh format used by NEAR tools.
+pub fn decode<T: BorshDeserialize>(input: &str) -> Result<T, String> {
+    let bytes = BASE64
+        .decode(input.trim())
+        .map_err(|error| format!("invalid base64: {error}"))?;
+    borsh::from_slice(&bytes).map_err(|error| format!("invalid transaction: {error}"))
+}
+
+/// Sends a signed transaction, waiting until it reaches the given status.
+///
+/// The signed transaction is retried as-is on transient errors, so it is
+/// never executed twice.
+pub async fn broadcast(
+    rpc: &RpcOptions,
+    network_config: &NetworkConfig,
+    signed: SignedTransaction,
+    wait_until: WaitUntil,
+) -> Result<TxOutcome, TxError> {
+    let hash = signed.get_hash();
+    let client = client(network_config);
+
+    let response = rpc
+        .retry(|| {
+            client.call(RpcSendTransactionRequest {
+                signed_transaction: signed.clone(),
+                wait_until: wait_until.status(),
+            })
+        })
+        .await
+        .map_err(TxError::Send)?;
+
+    Ok(TxOutcome::new(hash, response))
+}
+
+/// Polls the status of a transaction until it reaches the given status.
+pub async fn status(
+    rpc: &RpcOptions,
+    network_config: &NetworkConfig,
+    hash: CryptoHash,
+    sender: AccountId,
+    wait_until: WaitUntil,
+) -> Result<TxOutcome, TxError> {
+    let client = client(network_config);
+
+    let response = rpc
+        .retry(|| {
+            client.call(RpcTransactionStatusRequest {
+                transaction_info: TransactionInfo::TransactionId {
+                    tx_hash: hash,
+                    sender_account_id: sender.clone(),
+                },
+                wait_until: wait_until.status(),
+            })
+        })
+        .await
+        .map_err(TxError::Send)?;
+
+    Ok(TxOutcome::new(hash, response))
+}
+
+/// Prints the status, execution outcome, and receipts of a transaction.
+pub fn print_outcome(outcome: &TxOutcome) {
+    cprintln!("<s>Transaction</> {}", outcome.hash);
+    cprintln!("  <s>Status:</>   {:?}", outcome.status);
+
+    let Some(outcome) = &outcome.outcome else {
+        return;
+    };
+    cprintln!("  <s>Signer:</>   {}", outcome.transaction.signer_id);
+    cprintln!("  <s>Receiver:</> {}", outcome.transaction.receiver_id);
+    match &outcome.status {
+        FinalExecutionStatus::NotStarted => cprintln!("  <s>Result:</>   <y>not started</>"),
+        FinalExecutionStatus::Started => cprintln!("  <s>Result:</>   <y>started</>"),
+        FinalExecutionStatus::Failure(error) => {
+            cprintln!("  <s>Result:</>   <r>failure:</> {error}")
+        }
+        FinalExecutionStatus::SuccessValue(value) => {
+            cprintln!(
+                "  <s>Result:</>   <g>success</> {}",
+                String::from_utf8_lossy(value)
+            )
+        }
+    }
+    print_execution("  ", &outcome.transaction_outcome);
+
+    if outcome.receipts_outcome.is_empty() {
+        return;
+    }
+    cprintln!("<s>Receipts:</>");
+    for receipt in &outcome.receipts_outcome {
+        cprintln!("  <s>Receipt</> {}", receipt.id);
+        cprintln!("    <s>Executor:</> {}", receipt.outcome.executor_id);
+        match &receipt.outcome.status {
+            ExecutionStatusView::Unknown => cprintln!("    <s>Result:</>   <y>unknown</>"),
+            ExecutionStatusView::Failure(error) => {
+                cprintln!("    <s>Result:</>   <r>failure:</> {error}")
+            }
+            ExecutionStatusView::SuccessValue(value) => {
+                cprintln!(
+                    "    <s>Result:</>   <g>success</> {}",
+                    String::from_utf8_lossy(value)
+                )
+            }
+            ExecutionStatusView::SuccessReceiptId(id) => {
+                cprintln!("    <s>Result:</>   <g>success</>, continued in receipt {id}")
+            }
+        }
+        print_execution("    ", receipt);
+    }
+}
+
+fn print_execution(indent: &str, execution: &ExecutionOutcomeWithIdView) {
+    let gas = NearGas::from_gas(execution.outcome.gas_burnt);
+    let tokens = NearToken::from_yoctonear(execution.outcome.tokens_burnt);
+    cprintln!("{indent}<s>Burnt:</>    {gas}, {tokens}");
+    for log in &execution.outcome.logs {
+        cprintln!("{indent}<s>Log:</>      {log}");
+    }
+}
@@ -0,0 +1,189 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, transfer_transaction, Home};
+use asimov_account_cli::{
+    agent::{self, Agent, AgentOptions, Confirm, Request, Response},
+    chain::{Chain as _, FakeChain},
+    commands::{agent, transfer},
+    nep413,
+    network_name::NetworkName,
+    tx::{self, SignOptions, WaitUntil},
+    AccountError,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use near_api::{near_primitives::hash::CryptoHash, signer::NEP413Payload, NearToken};
+use near_crypto::{KeyType, SecretKey};
+use std::{
+    os::unix::fs::{MetadataExt as _, PermissionsExt as _},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::net::UnixListener;
+
+#[test]
+fn serves_the_keys_of_accounts() {
+    let home = Home::enter();
+    let chain = Arc::new(FakeChain::new());
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+    let socket = home.join("agent.sock");
+
+    let options = AgentOptions {
+        socket: Some(socket.clone()),
+        lifetime: Some(Duration::from_secs(2)),
+        ..Default::default()
+    };
+    let agent = std::thread::spawn({
+        let chain = chain.clone();
+        let alice = alice.clone();
+        move || agent(vec![alice], &options, chain.as_ref(), &flags())
+    });
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let identities = loop {
+        if let Ok(identities) = runtime.block_on(agent::identities(&socket)) {
+            break identities;
+        }
+        assert!(!agent.is_finished(), "agent exited early");
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    assert_eq!(identities.len(), 1);
+    assert_eq!(identities[0].account_id, alice);
+    assert_eq!(identities[0].public_key, secret_key.public_key());
+    // Only the owner may connect, and the socket was bound in a private
+    // directory that is gone:
+    let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert_eq!(std::fs::read_dir(home.join("")).unwrap().count(), 1);
+
+    // The agent exits once its lifetime is over:
+    agent.join().unwrap().unwrap();
+    assert!(!socket.exists());
+}
+
+#[test]
+fn fails_without_credentials() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let options = AgentOptions {
+        socket: Some(home.join("agent.sock")),
+        ..Default::default()
+    };
+
+    let error = agent(vec![account("alice.testnet")], &options, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Rpc(..)), "{error:?}");
+
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+    chain.forget_credentials(NetworkName::Testnet, &alice, &secret_key.public_key());
+    let error = agent(vec![alice], &options, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Usage(_)), "{error:?}");
+    assert!(!home.join("agent.sock").exists());
+}
+
+#[test]
+fn falls_back_to_the_keychain_when_the_agent_is_gone() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+
+    // The socket of an agent that has exited:
+    std::env::set_var(agent::SOCKET_VAR, home.join("agent.sock"));
+    let result = transfer(
+        alice,
+        bob.clone(),
+        NearToken::from_near(1),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    );
+    std::env::remove_var(agent::SOCKET_VAR);
+
+    result.unwrap();
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(2)));
+}
+
+#[test]
+fn signs_transactions_and_messages_over_the_socket() {
+    let home = Home::enter();
+    let alice = account("alice.testnet");
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+    let public_key = secret_key.public_key();
+    let socket = home.join("agent.sock");
+    let owner = std::fs::metadata(home.join("")).unwrap().uid();
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let request = |confirm, request| {
+        let mut keys = Agent::new(confirm, 0);
+        keys.add_key(alice.clone(), secret_key.clone());
+        runtime.block_on(async {
+            let _ = std::fs::remove_file(&socket);
+            let listener = UnixListener::bind(&socket).unwrap();
+            let served = tokio::spawn(keys.serve(listener, owner, Some(Duration::from_secs(2))));
+            let response = agent::request(&socket, &request).await.unwrap();
+            served.abort();
+            response
+        })
+    };
+
+    let transaction = transfer_transaction(
+        &alice,
+        &secret_key,
+        &account("bob.testnet"),
+        NearToken::from_near(1),
+        1,
+        CryptoHash::default(),
+    );
+    let hash = transaction.get_hash_and_size().0;
+    let response = request(
+        Confirm::Never,
+        Request::SignTransaction {
+            transaction: tx::encode(&transaction),
+        },
+    );
+    let Response::Signature { signature } = response else {
+        panic!("unexpected response: {response:?}");
+    };
+    assert!(signature.verify(hash.as_ref(), &public_key));
+
+    // Messages aren't confirmed under the `transactions` policy:
+    let payload = NEP413Payload {
+        message: "hello".into(),
+        nonce: [7; 32],
+        recipient: "app.example".into(),
+        callback_url: None,
+    };
+    let response = request(
+        Confirm::Transactions,
+        Request::SignMessage {
+            account_id: alice.clone(),
+            public_key: public_key.clone(),
+            message: payload.message.clone(),
+            recipient: payload.recipient.clone(),
+            nonce: BASE64.encode(payload.nonce),
+            callback_url: None,
+        },
+    );
+    let Response::Signature { signature } = response else {
+        panic!("unexpected response: {response:?}");
+    };
+    assert!(signature.verify(&nep413::message_hash(&payload), &public_key));
+
+    // A key the agent doesn't hold:
+    let response = request(
+        Confirm::Never,
+        Request::SignMessage {
+            account_id: account("bob.testnet"),
+            public_key,
+            message: payload.message,
+            recipient: payload.recipient,
+            nonce: BASE64.encode(payload.nonce),
+            callback_url: None,
+        },
+    );
+    assert!(matches!(response, Response::Error { .. }), "{response:?}");
+}
@@ -0,0 +1,57 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, registry_file, Home};
+use asimov_account_cli::{
+    commands::{alias_list, alias_set, alias_unset},
+    config::OutputFormat,
+    registry::{self, AccountMetadata},
+    AccountError,
+};
+
+#[test]
+fn resolves_aliases_to_accounts() {
+    let _home = Home::enter();
+    let worker = account("asimov-prod-ingest-worker-3.testnet");
+    add_to_registry(&worker);
+
+    alias_set("worker3", worker.clone(), &flags()).unwrap();
+
+    assert_eq!(registry::resolve("worker3"), Ok(worker.clone()));
+    assert_eq!(registry::resolve("alice.near"), Ok(account("alice.near")));
+    let metadata = AccountMetadata::load(&registry_file(&worker)).unwrap();
+    assert_eq!(metadata.aliases, ["worker3"]);
+    alias_list(OutputFormat::Text, &flags()).unwrap();
+
+    alias_unset("worker3", &flags()).unwrap();
+    assert_eq!(registry::resolve("worker3"), Ok(account("worker3")));
+    let result = alias_unset("worker3", &flags());
+    assert!(matches!(result, Err(AccountError::Usage(_))), "{result:?}");
+}
+
+#[test]
+fn moves_an_alias_to_another_account() {
+    let _home = Home::enter();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    add_to_registry(&alice);
+    add_to_registry(&bob);
+
+    alias_set("me", alice.clone(), &flags()).unwrap();
+    alias_set("me", bob.clone(), &flags()).unwrap();
+
+    assert_eq!(registry::resolve("me"), Ok(bob));
+    let metadata = AccountMetadata::load(&registry_file(&alice)).unwrap();
+    assert!(metadata.aliases.is_empty());
+}
+
+#[test]
+fn rejects_invalid_aliases_and_unknown_accounts() {
+    let _home = Home::enter();
+    let alice = account("alice.testnet");
+    add_to_registry(&alice);
+
+    let result = alias_set("alice.near", alice, &flags());
+    assert!(matches!(result, Err(AccountError::Usage(_))), "{result:?}");
+    let result = alias_set("bob", account("bob.testnet"), &flags());
+    assert!(matches!(result, Err(AccountError::Usage(_))), "{result:?}");
+}
@@ -0,0 +1,75 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, journal_entries, sign, transfer_transaction, Home};
+use asimov_account_cli::{
+    chain::FakeChain, commands::broadcast, journal::JournalStatus, tx, tx::WaitUntil, AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn sends_a_signed_transaction() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+    let signed = sign(
+        transfer_transaction(
+            &alice,
+            &secret_key,
+            &bob,
+            NearToken::from_near(2),
+            1,
+            chain.block_hash(),
+        ),
+        &secret_key,
+    );
+    let file = home.join("signed.txt");
+    std::fs::write(&file, tx::encode(&signed)).unwrap();
+
+    broadcast(&file, WaitUntil::Executed, &chain, &flags()).unwrap();
+
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(3)));
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(3)));
+    let entry = journal_entries("broadcast").pop().unwrap();
+    assert_eq!(entry.status, JournalStatus::Succeeded);
+    assert_eq!(entry.tx_hash, Some(signed.get_hash()));
+
+    // Sending the same transaction again doesn't apply it twice:
+    broadcast(&file, WaitUntil::Executed, &chain, &flags()).unwrap();
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(3)));
+}
+
+#[test]
+fn fails_for_a_used_nonce() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+    let transfer = |amount| {
+        let signed = sign(
+            transfer_transaction(
+                &alice,
+                &secret_key,
+                &bob,
+                NearToken::from_near(amount),
+                1,
+                chain.block_hash(),
+            ),
+            &secret_key,
+        );
+        let file = home.join(format!("signed-{amount}.txt"));
+        std::fs::write(&file, tx::encode(&signed)).unwrap();
+        file
+    };
+    let first = transfer(1);
+    let second = transfer(2);
+    broadcast(&first, WaitUntil::Executed, &chain, &flags()).unwrap();
+
+    let error = broadcast(&second, WaitUntil::Executed, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Transaction(..)), "{error:?}");
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(2)));
+}
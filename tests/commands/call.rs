@@ -0,0 +1,102 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, journal_entries, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{call, MethodArgs},
+    config::OutputFormat,
+    journal::JournalStatus,
+    tx::{SignOptions, WaitUntil},
+    AccountError,
+};
+use near_api::{NearGas, NearToken};
+
+fn json(args: &str) -> MethodArgs {
+    MethodArgs {
+        json: Some(args.into()),
+        args_base64: None,
+    }
+}
+
+#[test]
+fn calls_a_contract_method() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let usdt = account("usdt.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+    chain.add_fungible_token(&usdt, "USDT", 6);
+    chain.mint(&usdt, &alice, 3_000_000);
+
+    call(
+        alice.clone(),
+        usdt.clone(),
+        "storage_deposit".into(),
+        &json(r#"{"account_id": "bob.testnet"}"#),
+        NearToken::from_millinear(10),
+        NearGas::from_tgas(30),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        OutputFormat::Text,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    call(
+        alice.clone(),
+        usdt.clone(),
+        "ft_transfer".into(),
+        &json(r#"{"receiver_id": "bob.testnet", "amount": "1000000"}"#),
+        NearToken::from_yoctonear(1),
+        NearGas::from_tgas(30),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        OutputFormat::Json,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.token_balance(&usdt, &alice), Some(2_000_000));
+    assert_eq!(chain.token_balance(&usdt, &bob), Some(1_000_000));
+    let entries = journal_entries("call");
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Succeeded);
+}
+
+#[test]
+fn fails_when_the_call_fails() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let usdt = account("usdt.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_fungible_token(&usdt, "USDT", 6);
+    chain.mint(&usdt, &alice, 1_000_000);
+
+    // Without the yoctoNEAR that ft_transfer requires:
+    let error = call(
+        alice.clone(),
+        usdt.clone(),
+        "ft_transfer".into(),
+        &json(r#"{"receiver_id": "usdt.testnet", "amount": "1"}"#),
+        NearToken::from_yoctonear(0),
+        NearGas::from_tgas(30),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        OutputFormat::Text,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+
+    assert!(
+        matches!(error, AccountError::TransactionFailed(..)),
+        "{error:?}"
+    );
+    assert_eq!(chain.token_balance(&usdt, &alice), Some(1_000_000));
+    let entries = journal_entries("call");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Failed);
+}
@@ -0,0 +1,59 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, Home};
+use asimov_account_cli::{
+    commands::{completions, config_set},
+    completions::{account_names, sponsor_names, Shell},
+};
+use clap_complete::engine::CompletionCandidate;
+
+fn values(candidates: Vec<CompletionCandidate>) -> Vec<String> {
+    candidates
+        .iter()
+        .map(|candidate| candidate.get_value().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn completes_accounts_in_the_registry() {
+    let home = Home::enter();
+    assert!(account_names().is_empty());
+
+    add_to_registry(&account("bob.testnet"));
+    add_to_registry(&account("alice.testnet"));
+    add_to_registry(&account("alice.near"));
+    // Deleted accounts are hidden:
+    let deleted = add_to_registry(&account("carol.testnet"));
+    std::fs::rename(&deleted, deleted.with_file_name(".carol.testnet")).unwrap();
+
+    assert_eq!(
+        values(account_names()),
+        ["alice.near", "alice.testnet", "bob.testnet"]
+    );
+
+    config_set(
+        &home.join(".asimov/accounts/config.toml"),
+        "sponsor",
+        "sponsor.near",
+        &flags(),
+    )
+    .unwrap();
+    assert_eq!(
+        values(sponsor_names()),
+        ["alice.near", "alice.testnet", "bob.testnet", "sponsor.near"]
+    );
+}
+
+#[test]
+fn prints_completion_scripts() {
+    let _home = Home::enter();
+    for shell in [
+        Shell::Bash,
+        Shell::Zsh,
+        Shell::Fish,
+        Shell::Elvish,
+        Shell::Powershell,
+    ] {
+        completions(shell, &flags()).unwrap();
+    }
+}
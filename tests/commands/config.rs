@@ -0,0 +1,73 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    commands::{config_get, config_list, config_set, config_unset},
+    config::Config,
+    network_name::NetworkName,
+    AccountError,
+};
+
+#[test]
+fn sets_and_unsets_values() {
+    let home = Home::enter();
+    let path = home.join(".asimov/accounts/config.toml");
+
+    config_set(&path, "sponsor", "sponsor.testnet", &flags()).unwrap();
+    config_set(&path, "network", "testnet", &flags()).unwrap();
+    let config = Config::read(&path).unwrap();
+    assert_eq!(config.sponsor, Some(account("sponsor.testnet")));
+    assert_eq!(config.network, Some(NetworkName::Testnet));
+
+    config_unset(&path, "sponsor", &flags()).unwrap();
+    let config = Config::read(&path).unwrap();
+    assert_eq!(config.sponsor, None);
+    assert_eq!(config.network, Some(NetworkName::Testnet));
+
+    config_list(&path, &flags()).unwrap();
+}
+
+#[test]
+fn rejects_unknown_keys_and_invalid_values() {
+    let home = Home::enter();
+    let path = home.join(".asimov/accounts/config.toml");
+
+    let error = config_set(&path, "bogus", "value", &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Config(_)), "{error:?}");
+    let error = config_set(&path, "network", "bogus", &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Config(_)), "{error:?}");
+    let error = config_unset(&path, "bogus", &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Config(_)), "{error:?}");
+    assert!(!path.exists());
+
+    config_get("network", &flags()).unwrap();
+    let error = config_get("bogus", &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Config(_)), "{error:?}");
+}
+
+#[test]
+fn sets_token_lists() {
+    let home = Home::enter();
+    let path = home.join(".asimov/accounts/config.toml");
+
+    config_set(
+        &path,
+        "tokens.testnet",
+        "usdt.testnet, wrap.testnet",
+        &flags(),
+    )
+    .unwrap();
+    let config = Config::read(&path).unwrap();
+    assert_eq!(
+        config.tokens_on(NetworkName::Testnet),
+        [account("usdt.testnet"), account("wrap.testnet")]
+    );
+    assert!(config.tokens_on(NetworkName::Mainnet).is_empty());
+    assert_eq!(
+        config.get("tokens.testnet").unwrap().as_deref(),
+        Some("usdt.testnet,wrap.testnet")
+    );
+
+    let error = config_set(&path, "tokens.mainnet", "not an account", &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Config(_)), "{error:?}");
+}
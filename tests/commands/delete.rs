@@ -0,0 +1,85 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, journal_entries, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::delete,
+    journal::JournalStatus,
+    tx::{SignOptions, WaitUntil},
+    AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn deletes_the_account_and_pays_out_the_beneficiary() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+    let registry_file = add_to_registry(&alice);
+
+    delete(
+        alice.clone(),
+        bob.clone(),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), None);
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(6)));
+    assert!(!registry_file.exists());
+    assert!(registry_file
+        .with_file_name(".deleted/alice.testnet")
+        .is_file());
+    let entries = journal_entries("delete");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Succeeded);
+}
+
+#[test]
+fn only_signs_with_sign_only() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    let registry_file = add_to_registry(&alice);
+
+    let sign = SignOptions {
+        sign_only: true,
+        ..Default::default()
+    };
+    delete(
+        alice.clone(),
+        account("bob.testnet"),
+        WaitUntil::Executed,
+        &sign,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(5)));
+    assert!(registry_file.is_file());
+    assert!(journal_entries("delete").is_empty());
+}
+
+#[test]
+fn fails_without_credentials() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+
+    let error = delete(
+        account("alice.testnet"),
+        account("bob.testnet"),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Keychain(..)), "{error:?}");
+}
@@ -0,0 +1,66 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::account;
+use asimov_account_cli::{config::ConfigError, AccountError};
+use std::{error::Error as _, io::ErrorKind};
+
+#[test]
+fn maps_errors_to_exit_codes() {
+    let cases = [
+        (AccountError::UnknownNetwork(account("alice")), 65),
+        (AccountError::Usage("no".into()), 64),
+        (AccountError::NoHomeDir, 78),
+        (ConfigError::UnknownKey("bogus".into()).into(), 64),
+        (
+            AccountError::rpc("failed", std::io::Error::from(ErrorKind::TimedOut)),
+            69,
+        ),
+        (
+            AccountError::TransactionFailed("failed".into(), "no".into()),
+            69,
+        ),
+        (AccountError::AccountExists(account("alice.testnet")), 73),
+        (AccountError::NotPermitted("no".into()), 77),
+        (
+            AccountError::Input("failed".into(), ErrorKind::NotFound.into()),
+            66,
+        ),
+        (
+            AccountError::Read("failed".into(), ErrorKind::NotFound.into()),
+            74,
+        ),
+        (
+            AccountError::Write("failed".into(), ErrorKind::NotFound.into()),
+            73,
+        ),
+        (AccountError::Software("no".into()), 70),
+    ];
+    for (error, code) in cases {
+        assert_eq!(error.to_sysexits().as_u8(), code, "{error:?}");
+    }
+}
+
+#[test]
+fn describes_errors_with_their_context_and_source() {
+    let error = AccountError::Read(
+        "failed to read the registry".into(),
+        ErrorKind::PermissionDenied.into(),
+    );
+    assert!(
+        error
+            .to_string()
+            .starts_with("failed to read the registry: "),
+        "{error}"
+    );
+    let source = error.source().unwrap();
+    assert_eq!(
+        source.downcast_ref::<std::io::Error>().unwrap().kind(),
+        ErrorKind::PermissionDenied
+    );
+
+    let error = AccountError::from(ConfigError::InvalidValue("network".into(), "no".into()));
+    assert_eq!(error.to_string(), "invalid value for `network`: no");
+    assert!(error.source().unwrap().is::<ConfigError>());
+
+    assert!(AccountError::Usage("no".into()).source().is_none());
+}
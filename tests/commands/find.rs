@@ -0,0 +1,44 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    chain::{Chain as _, FakeChain},
+    commands::find,
+    network_name::NetworkName,
+    AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn finds_credentials_for_an_account() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    find(alice, &chain, &flags()).unwrap();
+}
+
+#[test]
+fn fails_without_credentials() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+    chain.forget_credentials(NetworkName::Testnet, &alice, &secret_key.public_key());
+
+    let error = find(alice, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Keychain(..)), "{error:?}");
+}
+
+#[test]
+fn fails_for_an_unknown_network() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+
+    let error = find(account("alice.example"), &chain, &flags()).unwrap_err();
+    assert!(
+        matches!(error, AccountError::UnknownNetwork(_)),
+        "{error:?}"
+    );
+}
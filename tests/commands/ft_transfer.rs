@@ -0,0 +1,117 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, journal_entries, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::ft_transfer,
+    journal::JournalStatus,
+    tx::{SignOptions, WaitUntil},
+    AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn transfers_tokens_and_registers_the_receiver() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let usdt = account("usdt.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+    chain.add_fungible_token(&usdt, "USDT", 6);
+    chain.mint(&usdt, &alice, 3_000_000);
+
+    ft_transfer(
+        alice.clone(),
+        usdt.clone(),
+        bob.clone(),
+        "1.25".into(),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.token_balance(&usdt, &alice), Some(1_750_000));
+    assert_eq!(chain.token_balance(&usdt, &bob), Some(1_250_000));
+    // The registration deposit and the yoctoNEAR of the transfer:
+    assert!(chain.balance(&alice) < Some(NearToken::from_millinear(4999)));
+    let entries = journal_entries("ft-transfer");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Succeeded);
+
+    // Once registered, the receiver isn't registered again:
+    ft_transfer(
+        alice.clone(),
+        usdt.clone(),
+        bob.clone(),
+        "0.75".into(),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    assert_eq!(chain.token_balance(&usdt, &alice), Some(1_000_000));
+    assert_eq!(chain.token_balance(&usdt, &bob), Some(2_000_000));
+}
+
+#[test]
+fn fails_with_insufficient_balance() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let usdt = account("usdt.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+    chain.add_fungible_token(&usdt, "USDT", 6);
+    chain.mint(&usdt, &alice, 1_000_000);
+
+    let error = ft_transfer(
+        alice.clone(),
+        usdt.clone(),
+        bob.clone(),
+        "2".into(),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+
+    assert!(
+        matches!(error, AccountError::TransactionFailed(..)),
+        "{error:?}"
+    );
+    // The registration is undone along with the transfer:
+    assert_eq!(chain.token_balance(&usdt, &alice), Some(1_000_000));
+    assert_eq!(chain.token_balance(&usdt, &bob), None);
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(5)));
+    let entries = journal_entries("ft-transfer");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Failed);
+}
+
+#[test]
+fn rejects_amounts_finer_than_the_token() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let usdt = account("usdt.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_fungible_token(&usdt, "USDT", 6);
+
+    let error = ft_transfer(
+        alice,
+        usdt,
+        account("bob.testnet"),
+        "0.0000001".into(),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Usage(_)), "{error:?}");
+}
@@ -0,0 +1,81 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, journal_entries, Home};
+use asimov_account_cli::{
+    chain::FakeChain, commands::fund, journal::JournalStatus, tx::WaitUntil, AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn funds_a_testnet_account_from_the_faucet() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    fund(
+        alice.clone(),
+        None,
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(11)));
+    let entries = journal_entries("fund");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Succeeded);
+    assert!(entries.last().unwrap().tx_hash.is_some());
+}
+
+#[test]
+fn funds_an_account_from_a_sponsor() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let sponsor = account("sponsor.near");
+    let alice = account("alice.near");
+    chain.add_account(&sponsor, NearToken::from_near(5));
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    fund(
+        alice.clone(),
+        Some(sponsor.clone()),
+        Some(NearToken::from_near(2)),
+        WaitUntil::Final,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(3)));
+    assert_eq!(chain.balance(&sponsor), Some(NearToken::from_near(3)));
+}
+
+#[test]
+fn fails_without_a_faucet_or_an_account() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    chain.add_account(&account("alice.near"), NearToken::from_near(1));
+
+    let result = fund(
+        account("alice.near"),
+        None,
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    );
+    assert!(matches!(result, Err(AccountError::Usage(_))), "{result:?}");
+
+    let result = fund(
+        account("bob.testnet"),
+        None,
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    );
+    assert!(matches!(result, Err(AccountError::Rpc(..))), "{result:?}");
+    assert!(journal_entries("fund").is_empty());
+}
@@ -0,0 +1,117 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, registry_file, Home};
+use asimov_account_cli::{
+    chain::FakeChain, commands::grant, registry::AccountMetadata, tx::WaitUntil, AccountError,
+};
+use near_api::{near_primitives::account::AccessKeyPermission, NearToken};
+
+#[test]
+fn adds_a_function_call_key() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let contract = account("app.testnet");
+    let full_access_key = chain
+        .add_account(&alice, NearToken::from_near(5))
+        .public_key();
+    let registry_file = add_to_registry(&alice);
+
+    grant(
+        alice.clone(),
+        contract.clone(),
+        vec!["vote".into()],
+        Some(NearToken::from_millinear(250)),
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    let keys = chain.keys(&alice).unwrap();
+    assert_eq!(keys.len(), 2);
+    let (public_key, access_key) = keys
+        .iter()
+        .find(|(public_key, _)| **public_key != full_access_key)
+        .unwrap();
+    let AccessKeyPermission::FunctionCall(permission) = &access_key.permission else {
+        panic!("expected a function-call key, got {access_key:?}");
+    };
+    assert_eq!(permission.receiver_id, contract.as_str());
+    assert_eq!(permission.method_names, ["vote"]);
+    assert_eq!(
+        permission.allowance,
+        Some(NearToken::from_millinear(250).as_yoctonear())
+    );
+    assert!(chain.credentials(&alice).contains(public_key));
+
+    let metadata = AccountMetadata::load(&registry_file).unwrap();
+    let key = metadata.key(public_key).unwrap();
+    assert_eq!(key.contract, contract);
+    assert_eq!(key.methods, ["vote"]);
+}
+
+#[test]
+fn fails_without_credentials() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+
+    let error = grant(
+        account("alice.testnet"),
+        account("app.testnet"),
+        vec![],
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Keychain(..)), "{error:?}");
+}
+
+#[test]
+fn keeps_an_unreadable_registry_file() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    let registry_file = add_to_registry(&alice);
+    std::fs::write(&registry_file, "not = [valid").unwrap();
+
+    let error = grant(
+        alice.clone(),
+        account("app.testnet"),
+        vec![],
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Read(..)), "{error:?}");
+    assert_eq!(
+        std::fs::read_to_string(&registry_file).unwrap(),
+        "not = [valid"
+    );
+}
+
+#[test]
+fn does_not_add_an_unregistered_account_to_the_registry() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+
+    grant(
+        alice.clone(),
+        account("app.testnet"),
+        vec![],
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    assert_eq!(chain.keys(&alice).unwrap().len(), 2);
+    assert!(!registry_file(&alice).exists());
+}
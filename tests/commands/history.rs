@@ -0,0 +1,108 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{history, transfer},
+    config::OutputFormat,
+    journal::{self, JournalEntry, JournalStatus},
+    tx::{SignOptions, WaitUntil},
+};
+use near_api::{near_primitives::hash::CryptoHash, NearToken};
+use std::io::Write as _;
+
+#[test]
+fn shows_recorded_transactions() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+
+    history(None, None, None, None, None, OutputFormat::Text, &flags()).unwrap();
+
+    transfer(
+        alice.clone(),
+        bob,
+        NearToken::from_near(2),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    history(None, None, None, None, None, OutputFormat::Text, &flags()).unwrap();
+    history(
+        Some(alice),
+        Some("transfer".into()),
+        Some("testnet".into()),
+        Some(JournalStatus::Succeeded),
+        Some(1),
+        OutputFormat::Json,
+        &flags(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn folds_each_run_into_its_latest_status() {
+    let alice = account("alice.testnet");
+    let hash = CryptoHash::hash_bytes(b"transfer");
+
+    let succeeded = JournalEntry::new("transfer", &alice, "testnet").with_tx_hash(Some(hash));
+    let interrupted = JournalEntry::new("transfer", &alice, "testnet");
+    // An entry from before runs were recorded, matched by its transaction:
+    let mut old = JournalEntry::new("delete", &alice, "testnet").with_tx_hash(Some(hash));
+    old.started = None;
+
+    let latest = journal::latest(vec![
+        succeeded.clone(),
+        interrupted.clone(),
+        old.clone(),
+        succeeded.succeeded(),
+        old.failed("no"),
+    ]);
+
+    let statuses = latest
+        .iter()
+        .map(|entry| (entry.command.as_str(), entry.status))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statuses,
+        [
+            ("transfer", JournalStatus::Succeeded),
+            ("transfer", JournalStatus::Pending),
+            ("delete", JournalStatus::Failed),
+        ]
+    );
+}
+
+#[test]
+fn appends_entries_and_skips_malformed_lines() {
+    let home = Home::enter();
+    let alice = account("alice.testnet");
+    let entry = JournalEntry::new("delete", &alice, "testnet");
+
+    journal::append(&entry).unwrap();
+    let path = journal::path().unwrap();
+    assert!(path.starts_with(home.join(".asimov/accounts")));
+    let first = std::fs::read_to_string(&path).unwrap();
+    // A line cut short by an interrupted write:
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(b"{\"timestamp\":\n")
+        .unwrap();
+    journal::append(&entry.failed("no beneficiary")).unwrap();
+
+    assert!(std::fs::read_to_string(&path).unwrap().starts_with(&first));
+    let entries = journal::read().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].status, JournalStatus::Pending);
+    assert_eq!(entries[1].status, JournalStatus::Failed);
+    assert_eq!(entries[1].error.as_deref(), Some("no beneficiary"));
+    assert_eq!(entries[1].started, entries[0].started);
+}
@@ -0,0 +1,93 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, registry_file, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::import,
+    registry::{AccountMetadata, Scope},
+    AccountError,
+};
+use near_api::NearToken;
+use std::os::unix::fs::MetadataExt as _;
+
+#[test]
+fn adds_the_account_to_the_registry() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    import(alice.clone(), Scope::User, &chain, &flags()).unwrap();
+    let created = AccountMetadata::load(&registry_file(&alice))
+        .unwrap()
+        .created;
+    assert!(created.is_some());
+
+    // Importing again leaves the existing file alone:
+    import(alice.clone(), Scope::User, &chain, &flags()).unwrap();
+    assert_eq!(
+        AccountMetadata::load(&registry_file(&alice))
+            .unwrap()
+            .created,
+        created
+    );
+}
+
+#[test]
+fn fails_for_an_account_that_does_not_exist() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+
+    let error = import(alice.clone(), Scope::User, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Keychain(..)), "{error:?}");
+    assert!(!registry_file(&alice).exists());
+}
+
+#[test]
+fn keeps_the_metadata_of_an_existing_entry() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+    let path = add_to_registry(&alice);
+    let metadata = AccountMetadata {
+        labels: vec!["ci".into()],
+        ..Default::default()
+    };
+    metadata.save(&path).unwrap();
+
+    let inode = std::fs::metadata(&path).unwrap().ino();
+    import(alice.clone(), Scope::User, &chain, &flags()).unwrap();
+    assert_eq!(AccountMetadata::load(&path).unwrap().labels, ["ci"]);
+    // The file isn't rewritten, which would change its timestamps:
+    assert_eq!(std::fs::metadata(&path).unwrap().ino(), inode);
+
+    // An entry that can't be read is reported rather than overwritten:
+    std::fs::write(&path, "{").unwrap();
+    let error = import(alice, Scope::User, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Read(..)), "{error:?}");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{");
+}
+
+#[test]
+fn adds_the_account_to_the_project_registry() {
+    let home = Home::enter();
+    let project = home.enter_project();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    import(alice.clone(), Scope::Project, &chain, &flags()).unwrap();
+
+    let path = project.join(".asimov/accounts/near/testnet/alice.testnet");
+    assert!(path.is_file());
+    assert!(project.join(".asimov/accounts/VERSION").is_file());
+    assert_eq!(registry_file(&alice), path);
+    assert!(!home
+        .join(".asimov/accounts/near/testnet/alice.testnet")
+        .exists());
+    // The lock is taken on the registry being written:
+    assert!(project.join(".asimov/accounts/.lock").is_file());
+    assert!(!home.join(".asimov/accounts/.lock").exists());
+}
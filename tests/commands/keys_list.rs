@@ -0,0 +1,33 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain, commands::keys_list, config::OutputFormat, AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn lists_the_keys_of_an_account() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    keys_list(alice.clone(), OutputFormat::Text, &chain, &flags()).unwrap();
+    keys_list(alice, OutputFormat::Json, &chain, &flags()).unwrap();
+}
+
+#[test]
+fn fails_for_an_account_that_does_not_exist() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+
+    let error = keys_list(
+        account("alice.testnet"),
+        OutputFormat::Text,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Rpc(..)), "{error:?}");
+}
@@ -0,0 +1,47 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, registry_file, Home};
+use asimov_account_cli::{
+    commands::{label_add, label_remove},
+    registry::AccountMetadata,
+    AccountError,
+};
+
+#[test]
+fn adds_and_removes_labels() {
+    let _home = Home::enter();
+    let alice = account("alice.testnet");
+    add_to_registry(&alice);
+
+    label_add(alice.clone(), vec!["ci".into(), "prod".into()], &flags()).unwrap();
+    label_add(alice.clone(), vec!["ci".into()], &flags()).unwrap();
+    let metadata = AccountMetadata::load(&registry_file(&alice)).unwrap();
+    assert_eq!(metadata.labels, ["ci", "prod"]);
+
+    label_remove(alice.clone(), vec!["ci".into()], &flags()).unwrap();
+    let metadata = AccountMetadata::load(&registry_file(&alice)).unwrap();
+    assert_eq!(metadata.labels, ["prod"]);
+
+    let result = label_add(alice, vec!["a b".into()], &flags());
+    assert!(matches!(result, Err(AccountError::Usage(_))), "{result:?}");
+}
+
+#[test]
+fn keeps_the_labels_added_by_concurrent_processes() {
+    let _home = Home::enter();
+    let alice = account("alice.testnet");
+    add_to_registry(&alice);
+
+    std::thread::scope(|scope| {
+        for index in 0..8 {
+            let alice = alice.clone();
+            scope.spawn(move || label_add(alice, vec![format!("job-{index}")], &flags()).unwrap());
+        }
+    });
+
+    let metadata = AccountMetadata::load(&registry_file(&alice)).unwrap();
+    assert_eq!(metadata.labels.len(), 8, "{:?}", metadata.labels);
+    // Temporary files are renamed into place, so none are left behind:
+    let files = std::fs::read_dir(registry_file(&alice).parent().unwrap()).unwrap();
+    assert_eq!(files.count(), 1);
+}
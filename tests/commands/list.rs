@@ -0,0 +1,102 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{list, ListOrder},
+    config::OutputFormat,
+    network_name::NetworkName,
+    registry::{self, Scope},
+};
+use near_api::NearToken;
+
+#[test]
+fn lists_registered_accounts() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let list = |output| {
+        list(
+            vec![],
+            None,
+            vec![],
+            false,
+            ListOrder::Name,
+            output,
+            &chain,
+            &flags(),
+        )
+    };
+
+    list(OutputFormat::Text).unwrap();
+
+    add_to_registry(&account("alice.testnet"));
+    add_to_registry(&account("alice.near"));
+    list(OutputFormat::Text).unwrap();
+    list(OutputFormat::Json).unwrap();
+    list(OutputFormat::Csv).unwrap();
+}
+
+#[test]
+fn lists_filtered_accounts_with_their_balances() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    chain.add_account(&account("alice.testnet"), NearToken::from_near(1));
+    add_to_registry(&account("alice.testnet"));
+    add_to_registry(&account("bob.testnet"));
+    add_to_registry(&account("alice.near"));
+
+    for output in [OutputFormat::Text, OutputFormat::Json, OutputFormat::Csv] {
+        list(
+            vec!["a*".into(), "bob.*".into()],
+            Some(NetworkName::Testnet),
+            vec![],
+            true,
+            ListOrder::Balance,
+            output,
+            &chain,
+            &flags(),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn lists_accounts_of_the_project_and_the_user() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    add_to_registry(&account("alice.testnet"));
+    add_to_registry(&account("bob.testnet"));
+    home.enter_project();
+    let project = registry::scoped_account_path(
+        Scope::Project,
+        NetworkName::Testnet,
+        &account("bob.testnet"),
+    )
+    .unwrap();
+    std::fs::create_dir_all(project.parent().unwrap()).unwrap();
+    std::fs::write(&project, "{}").unwrap();
+
+    assert_eq!(
+        registry::accounts(NetworkName::Testnet).unwrap(),
+        [account("alice.testnet"), account("bob.testnet")]
+    );
+    assert_eq!(
+        registry::account_scope(NetworkName::Testnet, &account("bob.testnet")),
+        Some(Scope::Project)
+    );
+    for output in [OutputFormat::Text, OutputFormat::Json, OutputFormat::Csv] {
+        for long in [false, true] {
+            list(
+                vec![],
+                None,
+                vec![],
+                long,
+                ListOrder::Name,
+                output,
+                &chain,
+                &flags(),
+            )
+            .unwrap();
+        }
+    }
+}
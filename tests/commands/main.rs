@@ -0,0 +1,158 @@
+// This is free and unencumbered software released into the public domain.
+
+//! Runs the commands against an in-memory network and keychain, each in a
+//! temporary home directory.
+
+mod agent;
+mod alias;
+mod broadcast;
+mod call;
+mod completions;
+mod config;
+mod delete;
+mod error;
+mod find;
+mod ft_transfer;
+mod fund;
+mod grant;
+mod history;
+mod import;
+mod keys_list;
+mod label;
+mod list;
+mod man;
+mod migrate;
+mod nfts;
+mod register;
+mod registry_migrate;
+mod revoke;
+mod sign_message;
+mod sign_tx;
+mod stake;
+mod tokens;
+mod transfer;
+mod tx_status;
+mod verify_message;
+mod view;
+
+use asimov_account_cli::{
+    journal::{self, JournalEntry},
+    network_name::NetworkName,
+    registry,
+};
+use clientele::{crates::clap::ColorChoice, StandardOptions};
+use near_api::{
+    near_primitives::{
+        action::{Action, TransferAction},
+        hash::CryptoHash,
+        transaction::{SignedTransaction, Transaction},
+    },
+    AccountId, NearToken,
+};
+use near_crypto::SecretKey;
+use std::{
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+use temp_dir::TempDir;
+
+/// Commands find the registries and journal through `HOME` and the current
+/// directory, so tests that change them must not run at the same time.
+static HOME_LOCK: Mutex<()> = Mutex::new(());
+
+/// A temporary directory set as `HOME` for as long as it's alive.
+pub struct Home {
+    dir: TempDir,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Home {
+    /// Creates a temporary directory and sets it as `HOME` and the current
+    /// directory, waiting for any other test using one to finish first.
+    pub fn enter() -> Self {
+        let lock = HOME_LOCK.lock().unwrap_or_else(|error| error.into_inner());
+        let dir = TempDir::new().unwrap();
+        std::env::set_var("HOME", dir.path());
+        std::env::set_current_dir(dir.path()).unwrap();
+        Self { dir, _lock: lock }
+    }
+
+    /// Creates a project directory with a registry in the home directory,
+    /// and makes a subdirectory of it the current directory.
+    pub fn enter_project(&self) -> PathBuf {
+        let project = self.join("project");
+        std::fs::create_dir_all(project.join(".asimov/accounts")).unwrap();
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::env::set_current_dir(project.join("src")).unwrap();
+        project
+    }
+
+    /// Returns the path of a file in the home directory.
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.dir.path().join(path)
+    }
+}
+
+pub fn flags() -> StandardOptions {
+    StandardOptions {
+        color: ColorChoice::Never,
+        debug: false,
+        license: false,
+        verbose: 0,
+        version: false,
+    }
+}
+
+pub fn account(account_id: &str) -> AccountId {
+    account_id.parse().unwrap()
+}
+
+/// Returns the registry file of an account.
+pub fn registry_file(account_id: &AccountId) -> PathBuf {
+    let network_name = NetworkName::try_from(account_id).unwrap();
+    registry::account_path(network_name, account_id).unwrap()
+}
+
+/// Creates an empty registry file for an account, as `import` would.
+pub fn add_to_registry(account_id: &AccountId) -> PathBuf {
+    let path = registry_file(account_id);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::File::create(&path).unwrap();
+    path
+}
+
+/// Returns the journal entries recorded by a command.
+pub fn journal_entries(command: &str) -> Vec<JournalEntry> {
+    journal::read()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| entry.command == command)
+        .collect()
+}
+
+/// Returns an unsigned transaction that transfers tokens.
+pub fn transfer_transaction(
+    signer_id: &AccountId,
+    secret_key: &SecretKey,
+    receiver_id: &AccountId,
+    amount: NearToken,
+    nonce: u64,
+    block_hash: CryptoHash,
+) -> Transaction {
+    let mut transaction = Transaction::new_v0(
+        signer_id.clone(),
+        secret_key.public_key(),
+        receiver_id.clone(),
+        nonce,
+        block_hash,
+    );
+    *transaction.actions_mut() = vec![Action::Transfer(TransferAction {
+        deposit: amount.as_yoctonear(),
+    })];
+    transaction
+}
+
+pub fn sign(transaction: Transaction, secret_key: &SecretKey) -> SignedTransaction {
+    let (hash, _) = transaction.get_hash_and_size();
+    SignedTransaction::new(secret_key.sign(hash.as_ref()), transaction)
+}
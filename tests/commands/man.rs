@@ -0,0 +1,138 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{flags, Home};
+use asimov_account_cli::{
+    commands::man,
+    man::{self, pages},
+    AccountError,
+};
+use clientele::crates::clap::{Arg, Command};
+use std::process;
+
+fn command() -> Command {
+    Command::new("asimov-account")
+        .about("ASIMOV Account Command-Line Interface (CLI)")
+        .subcommand(
+            Command::new("transfer")
+                .about("Transfer NEAR tokens to another account")
+                .arg(Arg::new("name").value_name("NAME")),
+        )
+        .subcommand(
+            Command::new("keys")
+                .about("Manage the access keys of an account")
+                .subcommand(Command::new("list").about("List the access keys of an account")),
+        )
+}
+
+#[test]
+fn renders_pages_with_examples_and_exit_statuses() {
+    let pages = pages(command());
+    let names = pages
+        .iter()
+        .map(|page| (page.path.as_str(), page.filename()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            ("", "asimov-account.1".to_string()),
+            ("transfer", "asimov-account-transfer.1".to_string()),
+            ("keys", "asimov-account-keys.1".to_string()),
+            ("keys list", "asimov-account-keys-list.1".to_string()),
+        ]
+    );
+
+    let mut roff = Vec::new();
+    pages[1].render(&mut roff).unwrap();
+    let roff = String::from_utf8(roff).unwrap();
+    assert!(roff.contains(".SH NAME"), "{roff}");
+    assert!(roff.contains(".SH OPTIONS"), "{roff}");
+    assert!(roff.contains(".SH EXAMPLES"), "{roff}");
+    assert!(roff.contains("transfer alice.near bob.near"), "{roff}");
+    assert!(roff.contains(".SH \"EXIT STATUS\""), "{roff}");
+    assert!(roff.contains("\\fB0 (EX_OK)\\fR"), "{roff}");
+    assert!(roff.contains("\\fB75 (EX_TEMPFAIL)\\fR"), "{roff}");
+    // Transfers don't read input files:
+    assert!(!roff.contains("EX_NOINPUT"), "{roff}");
+}
+
+#[test]
+fn writes_all_pages_into_a_directory() {
+    let home = Home::enter();
+    let dir = home.join("man/man1");
+
+    man(command(), vec![], Some(&dir), &flags()).unwrap();
+    let mut files = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "asimov-account-keys-list.1",
+            "asimov-account-keys.1",
+            "asimov-account-transfer.1",
+            "asimov-account.1",
+        ]
+    );
+
+    man(
+        command(),
+        vec!["keys".into(), "list".into()],
+        None,
+        &flags(),
+    )
+    .unwrap();
+    assert!(matches!(
+        man(command(), vec!["nope".into()], None, &flags()),
+        Err(AccountError::Usage(_))
+    ));
+}
+
+/// Returns the paths of the subcommands of the binary below the given one,
+/// recursively, as listed by its help.
+fn subcommand_paths(path: &[&str]) -> Vec<String> {
+    let output = process::Command::new(env!("CARGO_BIN_EXE_asimov-account"))
+        .args(path)
+        .arg("--help")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let help = String::from_utf8(output.stdout).unwrap();
+    let Some((_, commands)) = help.split_once("Commands:\n") else {
+        return Vec::new();
+    };
+    commands
+        .lines()
+        .take_while(|line| !line.is_empty())
+        // Descriptions that wrap are indented further than the names:
+        .filter_map(|line| line.strip_prefix("  "))
+        .filter(|line| !line.starts_with(' '))
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|name| *name != "help")
+        .flat_map(|name| {
+            let path = [path, &[name]].concat();
+            let mut paths = vec![path.join(" ")];
+            paths.extend(subcommand_paths(&path));
+            paths
+        })
+        .collect()
+}
+
+#[test]
+fn documents_every_command() {
+    let paths = subcommand_paths(&[]);
+    assert!(paths.contains(&"keys list".to_string()), "{paths:?}");
+
+    // The binary itself, then its subcommands:
+    let undocumented = std::iter::once(String::new())
+        .chain(paths)
+        .filter(|path| {
+            man::examples(path).is_empty() || man::specific_exit_statuses(path).is_none()
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        undocumented.is_empty(),
+        "commands without examples or exit statuses: {undocumented:?}"
+    );
+}
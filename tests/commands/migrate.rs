@@ -0,0 +1,216 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, journal_entries, registry_file, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{grant, migrate, stake},
+    config::Config,
+    migration::{self, Migration, MigrationStep},
+    network_name::NetworkName,
+    registry::AccountMetadata,
+    tx::{SignOptions, WaitUntil},
+    AccountError,
+};
+use near_api::{near_primitives::account::AccessKeyPermission, NearToken};
+
+#[test]
+fn migrates_an_account_with_its_keys() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let alicia = account("alicia.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    add_to_registry(&alice);
+    grant(
+        alice.clone(),
+        account("app.testnet"),
+        vec![],
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    let config_path = home.join(".asimov/accounts/config.toml");
+    let mut config = Config::default();
+    config.set("sponsor", alice.as_str()).unwrap();
+    config.write(&config_path).unwrap();
+
+    migrate(
+        alice.clone(),
+        alicia.clone(),
+        true,
+        WaitUntil::Executed,
+        &config_path,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), None);
+    assert_eq!(chain.balance(&alicia), Some(NearToken::from_near(5)));
+
+    let keys = chain.keys(&alicia).unwrap();
+    assert_eq!(keys.len(), 2);
+    let (function_call_key, _) = keys
+        .iter()
+        .find(|(_, key)| matches!(key.permission, AccessKeyPermission::FunctionCall(_)))
+        .unwrap();
+    assert!(chain.credentials(&alicia).contains(function_call_key));
+
+    let metadata = AccountMetadata::load(&registry_file(&alicia)).unwrap();
+    assert!(metadata.key(function_call_key).is_some());
+    assert!(!registry_file(&alice).exists());
+
+    assert_eq!(Config::read(&config_path).unwrap().sponsor, Some(alicia));
+    let log_path = migration::path(NetworkName::Testnet, &alice).unwrap();
+    assert!(!log_path.exists());
+    assert_eq!(journal_entries("migrate").len(), 6);
+}
+
+#[test]
+fn moves_only_the_aliases_and_labels_without_copying_keys() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let alicia = account("alicia.testnet");
+    let pool = account("pool.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_staking_pool(&pool);
+    add_to_registry(&alice);
+    grant(
+        alice.clone(),
+        account("app.testnet"),
+        vec![],
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    let mut metadata = AccountMetadata::load(&registry_file(&alice)).unwrap();
+    metadata.aliases = vec!["main".into()];
+    metadata.labels = vec!["ci".into()];
+    metadata.pools = vec![pool];
+    metadata.save(&registry_file(&alice)).unwrap();
+
+    migrate(
+        alice.clone(),
+        alicia.clone(),
+        false,
+        WaitUntil::Executed,
+        &home.join("config.toml"),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    let metadata = AccountMetadata::load(&registry_file(&alicia)).unwrap();
+    assert_eq!(metadata.aliases, ["main"]);
+    assert_eq!(metadata.labels, ["ci"]);
+    assert!(metadata.keys.is_empty());
+    assert!(metadata.pools.is_empty());
+    assert!(metadata.created.is_some());
+}
+
+#[test]
+fn refuses_to_delete_an_account_with_stake() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let alicia = account("alicia.testnet");
+    let pool = account("pool.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_staking_pool(&pool);
+    add_to_registry(&alice);
+    stake(
+        alice.clone(),
+        pool.clone(),
+        NearToken::from_near(2),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    let error = migrate(
+        alice.clone(),
+        alicia.clone(),
+        false,
+        WaitUntil::Executed,
+        &home.join("config.toml"),
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+
+    assert!(matches!(error, AccountError::NotPermitted(_)), "{error:?}");
+    assert!(error.to_string().contains("pool.testnet"));
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(3)));
+    assert_eq!(chain.balance(&alicia), None);
+    assert!(registry_file(&alice).is_file());
+}
+
+#[test]
+fn resumes_an_interrupted_migration() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let alicia = account("alicia.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&alicia, NearToken::from_millinear(10));
+
+    let log_path = migration::path(NetworkName::Testnet, &alice).unwrap();
+    let mut log = Migration::new(alice.clone(), alicia.clone(), false);
+    log.completed.push(MigrationStep::Register);
+    log.save(&log_path).unwrap();
+
+    migrate(
+        alice.clone(),
+        alicia.clone(),
+        false,
+        WaitUntil::Executed,
+        &home.join("config.toml"),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), None);
+    assert_eq!(
+        chain.balance(&alicia),
+        Some(NearToken::from_millinear(5010))
+    );
+    assert!(journal_entries("register").is_empty());
+    assert!(!log_path.exists());
+}
+
+#[test]
+fn refuses_to_migrate_to_another_account_than_the_log() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+
+    let log_path = migration::path(NetworkName::Testnet, &alice).unwrap();
+    Migration::new(alice.clone(), account("alicia.testnet"), false)
+        .save(&log_path)
+        .unwrap();
+
+    for new in ["bob.testnet", "alice.near", "alice.testnet"] {
+        let result = migrate(
+            alice.clone(),
+            account(new),
+            false,
+            WaitUntil::Executed,
+            &home.join("config.toml"),
+            &chain,
+            &flags(),
+        );
+        assert!(matches!(result, Err(AccountError::Usage(_))), "{result:?}");
+    }
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(5)));
+    assert!(log_path.exists());
+}
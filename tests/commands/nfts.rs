@@ -0,0 +1,98 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{nft_holdings, nfts},
+    config::OutputFormat,
+    nep171,
+    network_name::NetworkName,
+    AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn lists_tokens_across_contracts() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let badges = account("badges.testnet");
+    let art = account("art.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+    chain.add_nft_contract(&badges, "Badges", Some("https://badges.example"));
+    chain.add_nft_contract(&art, "Art", None);
+    for index in 0..60 {
+        let token_id = index.to_string();
+        chain.mint_nft(&badges, &alice, &token_id, "Badge", "badge.png");
+    }
+    chain.mint_nft(&art, &account("bob.testnet"), "1", "Not Alice's", "");
+
+    for output in [OutputFormat::Text, OutputFormat::Json, OutputFormat::Csv] {
+        nfts(
+            alice.clone(),
+            vec![badges.clone(), art.clone()],
+            output,
+            &chain,
+            &flags(),
+        )
+        .unwrap();
+    }
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let holdings = |contract_id| {
+        runtime
+            .block_on(nft_holdings(
+                &chain,
+                NetworkName::Testnet,
+                &alice,
+                contract_id,
+            ))
+            .unwrap()
+    };
+    // All pages of the contract are fetched:
+    let badge_holdings = holdings(&badges);
+    assert_eq!(badge_holdings.len(), 60);
+    let mut token_ids = badge_holdings
+        .iter()
+        .map(|holding| holding.token_id.parse::<usize>().unwrap())
+        .collect::<Vec<_>>();
+    token_ids.sort();
+    assert_eq!(token_ids, (0..60).collect::<Vec<_>>());
+    assert_eq!(badge_holdings[0].title.as_deref(), Some("Badge"));
+    assert_eq!(
+        badge_holdings[0].media.as_deref(),
+        Some("https://badges.example/badge.png")
+    );
+    assert!(holdings(&art).is_empty());
+}
+
+#[test]
+fn fails_for_an_account_without_an_nft_contract() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    let error = nfts(
+        alice,
+        vec![account("bob.testnet")],
+        OutputFormat::Text,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Rpc(..)), "{error:?}");
+}
+
+#[test]
+fn resolves_media_against_the_base_uri() {
+    assert_eq!(
+        nep171::media_url(Some("https://badges.example/"), "/1.png"),
+        "https://badges.example/1.png"
+    );
+    assert_eq!(
+        nep171::media_url(Some("https://badges.example"), "ipfs://bafy"),
+        "ipfs://bafy"
+    );
+    assert_eq!(nep171::media_url(None, "1.png"), "1.png");
+}
@@ -0,0 +1,294 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, journal_entries, registry_file, Home};
+use asimov_account_cli::{
+    chain::{Chain as _, FakeChain},
+    commands::{register, register_interactive},
+    journal::JournalStatus,
+    network_name::NetworkName,
+    registry::Scope,
+    tx::WaitUntil,
+    AccountError,
+};
+use near_api::NearToken;
+use near_crypto::{KeyType, SecretKey};
+
+#[test]
+fn registers_a_testnet_account_with_the_faucet() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+
+    register(
+        alice.clone(),
+        None,
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(10)));
+    let keys = chain.keys(&alice).unwrap();
+    assert_eq!(keys.len(), 1);
+    assert_eq!(
+        chain.credentials(&alice),
+        keys.into_keys().collect::<Vec<_>>()
+    );
+    assert!(registry_file(&alice).is_file());
+
+    let entries = journal_entries("register");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Succeeded);
+    assert!(entries.last().unwrap().tx_hash.is_some());
+}
+
+#[test]
+fn registers_a_sub_account_with_a_sponsor() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let sponsor = account("sponsor.near");
+    let bob = account("bob.sponsor.near");
+    chain.add_account(&sponsor, NearToken::from_near(5));
+
+    register(
+        bob.clone(),
+        Some(sponsor.clone()),
+        Some(NearToken::from_near(2)),
+        WaitUntil::Final,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(2)));
+    assert_eq!(chain.balance(&sponsor), Some(NearToken::from_near(3)));
+    assert_eq!(chain.credentials(&bob).len(), 1);
+    assert!(registry_file(&bob).is_file());
+}
+
+#[test]
+fn registers_a_top_level_account_with_a_sponsor() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let sponsor = account("sponsor.testnet");
+    let carol = account("carol.testnet");
+    chain.add_account(&sponsor, NearToken::from_near(5));
+
+    register(
+        carol.clone(),
+        Some(sponsor),
+        Some(NearToken::from_near(1)),
+        WaitUntil::Executed,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&carol), Some(NearToken::from_near(1)));
+}
+
+#[test]
+fn forgets_the_credentials_when_registration_fails() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let sponsor = account("sponsor.near");
+    let bob = account("bob.sponsor.near");
+    chain.add_account(&sponsor, NearToken::from_near(1));
+
+    let error = register(
+        bob.clone(),
+        Some(sponsor.clone()),
+        Some(NearToken::from_near(2)),
+        WaitUntil::Executed,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+
+    assert!(
+        matches!(error, AccountError::TransactionFailed(..)),
+        "{error:?}"
+    );
+    assert_eq!(chain.balance(&bob), None);
+    assert!(chain.credentials(&bob).is_empty());
+    assert!(!registry_file(&bob).exists());
+    let entries = journal_entries("register");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Failed);
+}
+
+#[test]
+fn resumes_when_the_account_exists_with_credentials() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    register(
+        alice.clone(),
+        None,
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    assert!(registry_file(&alice).is_file());
+}
+
+#[test]
+fn fails_when_the_account_exists_without_credentials() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+    chain.forget_credentials(NetworkName::Testnet, &alice, &secret_key.public_key());
+
+    let error = register(
+        alice.clone(),
+        None,
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::AccountExists(_)), "{error:?}");
+    assert!(!registry_file(&alice).exists());
+}
+
+#[test]
+fn fails_when_the_account_exists_with_someone_elses_key() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+    chain.forget_credentials(NetworkName::Testnet, &alice, &secret_key.public_key());
+    // Leftover credentials whose key isn't an access key of the account:
+    chain.add_credentials(&alice, SecretKey::from_random(KeyType::ED25519));
+
+    let error = register(
+        alice.clone(),
+        None,
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::AccountExists(_)), "{error:?}");
+    assert!(!registry_file(&alice).exists());
+}
+
+#[test]
+fn requires_a_sponsor_on_mainnet() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+
+    let error = register(
+        account("alice.near"),
+        None,
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Usage(_)), "{error:?}");
+
+    let error = register(
+        account("alice.near"),
+        Some(account("sponsor.near")),
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Usage(_)), "{error:?}");
+}
+
+#[test]
+fn registers_interactively_with_a_sponsor_from_the_registry() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let sponsor = account("sponsor.near");
+    chain.add_account(&sponsor, NearToken::from_near(5));
+    chain.add_account(&account("alice.near"), NearToken::from_near(1));
+    add_to_registry(&sponsor);
+
+    let answers = [
+        "2",      // mainnet
+        "alice",  // already taken
+        "bob",    // available
+        "1",      // sponsor.near
+        "9 NEAR", // more than the sponsor has
+        "2 NEAR", "",  // register
+        "y", // the seed phrase is backed up
+    ];
+    let mut input = answers.join("\n").into_bytes();
+    input.push(b'\n');
+    register_interactive(
+        None,
+        None,
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &mut input.as_slice(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    let bob = account("bob.near");
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(2)));
+    assert_eq!(chain.balance(&sponsor), Some(NearToken::from_near(3)));
+    assert_eq!(chain.credentials(&bob).len(), 1);
+    assert!(registry_file(&bob).is_file());
+}
+
+#[test]
+fn cancels_registering_interactively_without_a_backup_of_the_seed_phrase() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+
+    // The testnet faucet, register, and not backed up:
+    let result = register_interactive(
+        Some(alice.clone()),
+        None,
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &mut "\n\n\n".as_bytes(),
+        &chain,
+        &flags(),
+    );
+    assert!(matches!(result, Err(AccountError::Usage(_))), "{result:?}");
+
+    // The input ends before the questions are answered:
+    let result = register_interactive(
+        Some(alice.clone()),
+        None,
+        None,
+        WaitUntil::Executed,
+        Scope::User,
+        &mut "1\n".as_bytes(),
+        &chain,
+        &flags(),
+    );
+    assert!(matches!(result, Err(AccountError::Usage(_))), "{result:?}");
+
+    assert_eq!(chain.balance(&alice), None);
+    assert!(chain.credentials(&alice).is_empty());
+    assert!(!registry_file(&alice).exists());
+}
@@ -0,0 +1,123 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, Home};
+use asimov_account_cli::{
+    commands::{label_add, registry_migrate},
+    network_name::NetworkName,
+    registry::{self, Scope},
+    AccountError,
+};
+
+#[test]
+fn upgrades_a_registry_without_a_version() {
+    let home = Home::enter();
+    let alice = add_to_registry(&account("alice.testnet"));
+    let tombstone = alice.with_file_name(".bob.testnet");
+    std::fs::write(&tombstone, "").unwrap();
+    let leftover = alice.with_file_name(".alice.testnet.1-0.tmp");
+    std::fs::write(&leftover, "{").unwrap();
+    assert_eq!(registry::version().unwrap(), Some(1));
+
+    registry_migrate(&flags()).unwrap();
+
+    assert_eq!(registry::version().unwrap(), Some(registry::VERSION));
+    assert_eq!(std::fs::read_to_string(&alice).unwrap().trim(), "{}");
+    assert!(!tombstone.exists());
+    assert!(alice.with_file_name(".deleted/bob.testnet").is_file());
+    assert!(!leftover.exists());
+
+    let backups = home.join(".asimov/accounts/backups");
+    let backup = std::fs::read_dir(backups).unwrap().next().unwrap().unwrap();
+    assert!(backup.file_name().to_str().unwrap().starts_with("near-v1-"));
+    let backed_up = backup.path().join("testnet");
+    assert!(backed_up.join("alice.testnet").is_file());
+    assert!(backed_up.join(".bob.testnet").is_file());
+
+    // Migrating again changes nothing:
+    registry_migrate(&flags()).unwrap();
+}
+
+#[test]
+fn upgrades_the_project_registry_too() {
+    let home = Home::enter();
+    let user_alice = add_to_registry(&account("alice.testnet"));
+    let project = home.enter_project().join(".asimov/accounts");
+    let bob = project.join("near/testnet/bob.testnet");
+    std::fs::create_dir_all(bob.parent().unwrap()).unwrap();
+    std::fs::write(&bob, "").unwrap();
+    assert_eq!(registry::scoped_version(Scope::Project).unwrap(), Some(1));
+
+    registry_migrate(&flags()).unwrap();
+
+    for scope in [Scope::Project, Scope::User] {
+        assert_eq!(
+            registry::scoped_version(scope).unwrap(),
+            Some(registry::VERSION)
+        );
+    }
+    assert_eq!(std::fs::read_to_string(&bob).unwrap().trim(), "{}");
+    assert_eq!(std::fs::read_to_string(&user_alice).unwrap().trim(), "{}");
+
+    // Each registry is backed up in its own directory:
+    for (dir, name) in [
+        (project, "bob.testnet"),
+        (home.join(".asimov/accounts"), "alice.testnet"),
+    ] {
+        let backup = std::fs::read_dir(dir.join("backups"))
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(backup.path().join("testnet").join(name).is_file());
+    }
+}
+
+#[test]
+fn leaves_the_registry_alone_if_a_file_is_invalid() {
+    let _home = Home::enter();
+    let alice = add_to_registry(&account("alice.testnet"));
+    std::fs::write(&alice, "not json").unwrap();
+
+    let result = registry_migrate(&flags());
+
+    assert!(matches!(result, Err(AccountError::InvalidData(_))));
+    assert_eq!(registry::version().unwrap(), Some(1));
+    assert_eq!(std::fs::read_to_string(&alice).unwrap(), "not json");
+}
+
+#[test]
+fn marks_a_new_registry_with_the_current_version() {
+    let _home = Home::enter();
+    assert_eq!(registry::version().unwrap(), None);
+
+    drop(registry::lock(Scope::User).unwrap());
+
+    assert_eq!(registry::version().unwrap(), Some(registry::VERSION));
+}
+
+#[test]
+fn refuses_a_newer_registry() {
+    let _home = Home::enter();
+    let alice = account("alice.testnet");
+    add_to_registry(&alice);
+    std::fs::write(registry::version_path().unwrap(), "99\n").unwrap();
+
+    let result = label_add(alice, vec!["ci".into()], &flags());
+
+    let error = result.unwrap_err();
+    assert!(
+        matches!(error, AccountError::UnsupportedRegistry(99)),
+        "{error:?}"
+    );
+    assert!(error.to_string().contains("version 99"));
+    assert_eq!(error.to_sysexits().as_u8(), 65);
+    assert!(registry::accounts(NetworkName::Testnet).is_err());
+    assert!(registry_migrate(&flags()).is_err());
+
+    // Other errors of the same kind, e.g. from the filesystem, aren't data errors:
+    let error = AccountError::read_registry(
+        "failed to read accounts directory",
+        std::io::ErrorKind::Unsupported.into(),
+    );
+    assert_eq!(error.to_sysexits().as_u8(), 74);
+}
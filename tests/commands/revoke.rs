@@ -0,0 +1,117 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{grant, revoke},
+    registry::AccountMetadata,
+    tx::WaitUntil,
+    AccountError,
+};
+use near_api::NearToken;
+use near_crypto::{KeyType, SecretKey};
+
+#[test]
+fn removes_a_granted_key() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    let registry_file = add_to_registry(&alice);
+    grant(
+        alice.clone(),
+        account("app.testnet"),
+        vec![],
+        None,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    let public_key = AccountMetadata::load(&registry_file).unwrap().keys[0]
+        .public_key
+        .clone();
+
+    revoke(
+        alice.clone(),
+        public_key.clone(),
+        false,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert!(!chain.keys(&alice).unwrap().contains_key(&public_key));
+    assert!(!chain.credentials(&alice).contains(&public_key));
+    assert!(AccountMetadata::load(&registry_file)
+        .unwrap()
+        .key(&public_key)
+        .is_none());
+}
+
+#[test]
+fn fails_for_a_key_the_account_does_not_have() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    let public_key = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    let error = revoke(
+        alice.clone(),
+        public_key.clone(),
+        false,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Rpc(..)), "{error:?}");
+
+    let error = revoke(
+        alice,
+        public_key,
+        true,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(error, AccountError::TransactionFailed(..)),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn refuses_a_full_access_key_without_force() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(5));
+    let public_key = secret_key.public_key();
+
+    let error = revoke(
+        alice.clone(),
+        public_key.clone(),
+        false,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::NotPermitted(_)), "{error:?}");
+    assert!(chain.keys(&alice).unwrap().contains_key(&public_key));
+
+    revoke(
+        alice.clone(),
+        public_key.clone(),
+        true,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    assert!(!chain.keys(&alice).unwrap().contains_key(&public_key));
+}
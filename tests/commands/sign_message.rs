@@ -0,0 +1,93 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain, commands::sign_message, keychain, nep413, network_name::NetworkName,
+    AccountError,
+};
+use near_api::{signer::NEP413Payload, NearToken};
+
+#[test]
+fn signs_a_message() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+
+    for base64 in [false, true] {
+        sign_message(
+            alice.clone(),
+            "hello".into(),
+            "app.example".into(),
+            Some([7; 32]),
+            None,
+            Some(secret_key.public_key()),
+            base64,
+            &chain,
+            &flags(),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn fails_without_credentials() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+
+    let error = sign_message(
+        account("alice.testnet"),
+        "hello".into(),
+        "app.example".into(),
+        None,
+        None,
+        None,
+        false,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Keychain(..)), "{error:?}");
+}
+
+#[test]
+fn signs_a_payload_that_verifies() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+
+    // As `sign-message` does, with the credentials in the keychain:
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let credentials = runtime
+        .block_on(keychain::credentials(
+            &chain,
+            NetworkName::Testnet,
+            &alice,
+            None,
+            &flags(),
+        ))
+        .unwrap();
+    let payload = NEP413Payload {
+        message: "hello".into(),
+        nonce: [7; 32],
+        recipient: "app.example".into(),
+        callback_url: None,
+    };
+    let mut signed = runtime
+        .block_on(nep413::sign(
+            &credentials,
+            alice.clone(),
+            secret_key.public_key(),
+            payload,
+        ))
+        .unwrap();
+
+    assert_eq!(signed.account_id, alice);
+    assert_eq!(nep413::parse_nonce(&signed.nonce).unwrap(), [7; 32]);
+    assert_eq!(signed.verify(), Ok(true));
+    signed.recipient = "other.example".into();
+    assert_eq!(signed.verify(), Ok(false));
+
+    assert!(nep413::parse_nonce("AAAA").is_err());
+}
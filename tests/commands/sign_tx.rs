@@ -0,0 +1,72 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, transfer_transaction, Home};
+use asimov_account_cli::{chain::FakeChain, commands::sign_tx, tx, AccountError};
+use near_api::NearToken;
+use near_crypto::{KeyType, SecretKey};
+
+#[test]
+fn signs_an_unsigned_transaction() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(5));
+    let transaction = transfer_transaction(
+        &alice,
+        &secret_key,
+        &account("bob.testnet"),
+        NearToken::from_near(1),
+        0,
+        Default::default(),
+    );
+    let file = home.join("unsigned.txt");
+    std::fs::write(&file, tx::encode(&transaction)).unwrap();
+
+    sign_tx(&file, 1, chain.block_hash(), None, &chain, &flags()).unwrap();
+    sign_tx(
+        &file,
+        1,
+        chain.block_hash(),
+        Some(secret_key.public_key()),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    // Signing doesn't send anything:
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(5)));
+}
+
+#[test]
+fn fails_without_credentials_for_the_key() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let transaction = transfer_transaction(
+        &alice,
+        &SecretKey::from_random(KeyType::ED25519),
+        &account("bob.testnet"),
+        NearToken::from_near(1),
+        0,
+        Default::default(),
+    );
+    let file = home.join("unsigned.txt");
+    std::fs::write(&file, tx::encode(&transaction)).unwrap();
+
+    let error = sign_tx(&file, 1, chain.block_hash(), None, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Keychain(..)), "{error:?}");
+}
+
+#[test]
+fn fails_for_invalid_input() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let file = home.join("unsigned.txt");
+
+    let error = sign_tx(&file, 1, chain.block_hash(), None, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Input(..)), "{error:?}");
+
+    std::fs::write(&file, "not a transaction").unwrap();
+    let error = sign_tx(&file, 1, chain.block_hash(), None, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::InvalidData(_)), "{error:?}");
+}
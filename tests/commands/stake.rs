@@ -0,0 +1,120 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, add_to_registry, flags, journal_entries, registry_file, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{stake, stake_status, unstake, withdraw},
+    config::OutputFormat,
+    journal::JournalStatus,
+    registry::AccountMetadata,
+    tx::{SignOptions, WaitUntil},
+    AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn stakes_and_remembers_the_pool() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let pool = account("pool.testnet");
+    chain.add_account(&alice, NearToken::from_near(10));
+    chain.add_staking_pool(&pool);
+    add_to_registry(&alice);
+
+    stake(
+        alice.clone(),
+        pool.clone(),
+        NearToken::from_near(4),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(6)));
+    assert_eq!(chain.balance(&pool), Some(NearToken::from_near(4)));
+    let metadata = AccountMetadata::load(&registry_file(&alice)).unwrap();
+    assert_eq!(metadata.pools, [pool]);
+    let entries = journal_entries("stake");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Succeeded);
+
+    // Without --pool, the status shows the pool staked with:
+    stake_status(alice, vec![], OutputFormat::Json, &chain, &flags()).unwrap();
+}
+
+#[test]
+fn withdraws_only_after_unstaking_finishes() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let pool = account("pool.testnet");
+    chain.add_account(&alice, NearToken::from_near(10));
+    chain.add_staking_pool(&pool);
+
+    stake(
+        alice.clone(),
+        pool.clone(),
+        NearToken::from_near(4),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    unstake(
+        alice.clone(),
+        pool.clone(),
+        Some(NearToken::from_near(3)),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    let error = withdraw(
+        alice.clone(),
+        pool.clone(),
+        None,
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(error, AccountError::TransactionFailed(..)),
+        "{error:?}"
+    );
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(6)));
+    let entries = journal_entries("withdraw");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Failed);
+
+    chain.finish_unstaking(&pool);
+    withdraw(
+        alice.clone(),
+        pool.clone(),
+        None,
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(9)));
+    assert_eq!(chain.balance(&pool), Some(NearToken::from_near(1)));
+}
+
+#[test]
+fn requires_pools_for_status_without_stakes() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(10));
+    add_to_registry(&alice);
+
+    let error = stake_status(alice, vec![], OutputFormat::Text, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Usage(_)), "{error:?}");
+}
@@ -0,0 +1,103 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{token_balance, tokens},
+    config::OutputFormat,
+    nep141,
+    network_name::NetworkName,
+    AccountError,
+};
+use near_api::NearToken;
+
+#[test]
+fn lists_token_balances() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let usdt = account("usdt.testnet");
+    let wnear = account("wrap.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+    chain.add_fungible_token(&usdt, "USDT", 6);
+    chain.add_fungible_token(&wnear, "wNEAR", 24);
+    chain.mint(&usdt, &alice, 1_500_000);
+
+    for output in [OutputFormat::Text, OutputFormat::Json, OutputFormat::Csv] {
+        tokens(
+            alice.clone(),
+            vec![usdt.clone(), wnear.clone()],
+            output,
+            &chain,
+            &flags(),
+        )
+        .unwrap();
+    }
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let balance = |token_id| {
+        runtime
+            .block_on(token_balance(
+                &chain,
+                NetworkName::Testnet,
+                &alice,
+                token_id,
+            ))
+            .unwrap()
+    };
+    let usdt_balance = balance(&usdt);
+    assert_eq!(usdt_balance.metadata.symbol, "USDT");
+    assert_eq!(usdt_balance.balance, 1_500_000);
+    assert_eq!(usdt_balance.amount(), "1.5");
+    let wnear_balance = balance(&wnear);
+    assert_eq!(wnear_balance.metadata.decimals, 24);
+    assert_eq!(wnear_balance.balance, 0);
+}
+
+#[test]
+fn fails_for_an_account_without_a_token_contract() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    let error = tokens(
+        alice,
+        vec![account("bob.testnet")],
+        OutputFormat::Text,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Rpc(..)), "{error:?}");
+}
+
+#[test]
+fn requires_tokens_without_configured_ones() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+
+    let error = tokens(alice, vec![], OutputFormat::Text, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::Usage(_)), "{error:?}");
+}
+
+#[test]
+fn formats_and_parses_amounts() {
+    assert_eq!(nep141::format_amount(1_500_000, 6), "1.5");
+    assert_eq!(nep141::format_amount(42, 6), "0.000042");
+    assert_eq!(nep141::format_amount(3_000_000, 6), "3");
+    assert_eq!(nep141::format_amount(7, 0), "7");
+
+    assert_eq!(nep141::parse_amount("1.5", 6), Ok(1_500_000));
+    assert_eq!(nep141::parse_amount("0.000042", 6), Ok(42));
+    assert_eq!(nep141::parse_amount(".5", 1), Ok(5));
+    assert_eq!(nep141::parse_amount("3", 24), Ok(3 * 10u128.pow(24)));
+    assert!(nep141::parse_amount("0.0000001", 6).is_err());
+    assert!(nep141::parse_amount("1,5", 6).is_err());
+    assert!(nep141::parse_amount("-1", 6).is_err());
+    assert!(nep141::parse_amount(".", 6).is_err());
+    assert!(nep141::parse_amount("1e40", 38).is_err());
+    assert!(nep141::parse_amount("999999999", 32).is_err());
+}
@@ -0,0 +1,148 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, journal_entries, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::transfer,
+    journal::JournalStatus,
+    keychain,
+    network_name::NetworkName,
+    tx::{self, SignOptions, WaitUntil},
+    AccountError,
+};
+use near_api::{near_primitives::hash::CryptoHash, NearToken, Tokens};
+use near_crypto::{KeyType, SecretKey};
+
+#[test]
+fn transfers_tokens() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+
+    transfer(
+        alice.clone(),
+        bob.clone(),
+        NearToken::from_near(2),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(3)));
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(3)));
+    let entries = journal_entries("transfer");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Succeeded);
+}
+
+#[test]
+fn fails_with_insufficient_balance() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+    chain.add_account(&bob, NearToken::from_near(1));
+
+    let error = transfer(
+        alice.clone(),
+        bob.clone(),
+        NearToken::from_near(2),
+        WaitUntil::Executed,
+        &SignOptions::default(),
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+
+    assert!(
+        matches!(error, AccountError::TransactionFailed(..)),
+        "{error:?}"
+    );
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(1)));
+    assert_eq!(chain.balance(&bob), Some(NearToken::from_near(1)));
+    let entries = journal_entries("transfer");
+    assert_eq!(entries.last().unwrap().status, JournalStatus::Failed);
+}
+
+#[test]
+fn signs_offline_with_sign_only() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(5));
+
+    let sign = SignOptions {
+        sign_only: true,
+        public_key: Some(secret_key.public_key()),
+        nonce: Some(1),
+        block_hash: Some(chain.block_hash()),
+    };
+    transfer(
+        alice.clone(),
+        account("bob.testnet"),
+        NearToken::from_near(2),
+        WaitUntil::Executed,
+        &sign,
+        &chain,
+        &flags(),
+    )
+    .unwrap();
+
+    assert_eq!(chain.balance(&alice), Some(NearToken::from_near(5)));
+    assert_eq!(
+        chain.keys(&alice).unwrap()[&secret_key.public_key()].nonce,
+        0
+    );
+}
+
+#[test]
+fn signs_with_the_given_nonce_and_block_hash() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    // The account is unknown to the chain, as to an air-gapped machine:
+    let alice = account("alice.testnet");
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+    let public_key = secret_key.public_key();
+    chain.add_credentials(&alice, secret_key);
+    let block_hash = CryptoHash::hash_bytes(b"recent block");
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let signer = runtime
+        .block_on(keychain::signer(
+            &chain,
+            NetworkName::Testnet,
+            &alice,
+            Some(&public_key),
+            &flags(),
+        ))
+        .unwrap();
+    let transaction = Tokens::account(alice)
+        .send_to(account("bob.testnet"))
+        .near(NearToken::from_near(2))
+        .with_signer(signer);
+    let sign = SignOptions {
+        sign_only: true,
+        public_key: Some(public_key.clone()),
+        nonce: Some(7),
+        block_hash: Some(block_hash),
+    };
+    let signed = runtime
+        .block_on(tx::sign_with(
+            &sign,
+            &chain,
+            NetworkName::Testnet,
+            transaction,
+        ))
+        .unwrap();
+
+    assert_eq!(signed.transaction.nonce(), 7);
+    assert_eq!(*signed.transaction.block_hash(), block_hash);
+    assert!(signed
+        .signature
+        .verify(signed.get_hash().as_ref(), &public_key));
+}
@@ -0,0 +1,86 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, sign, transfer_transaction, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{broadcast, tx_status},
+    tx::{self, WaitUntil},
+    AccountError,
+};
+use near_api::{near_primitives::hash::CryptoHash, NearToken};
+
+#[test]
+fn reports_a_sent_transaction() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+    let signed = sign(
+        transfer_transaction(
+            &alice,
+            &secret_key,
+            &bob,
+            NearToken::from_near(2),
+            1,
+            chain.block_hash(),
+        ),
+        &secret_key,
+    );
+    let file = home.join("signed.txt");
+    std::fs::write(&file, tx::encode(&signed)).unwrap();
+    broadcast(&file, WaitUntil::Included, &chain, &flags()).unwrap();
+
+    tx_status(signed.get_hash(), alice, WaitUntil::Final, &chain, &flags()).unwrap();
+}
+
+#[test]
+fn fails_for_another_sender() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(5));
+    chain.add_account(&bob, NearToken::from_near(1));
+    let signed = sign(
+        transfer_transaction(
+            &alice,
+            &secret_key,
+            &bob,
+            NearToken::from_near(2),
+            1,
+            chain.block_hash(),
+        ),
+        &secret_key,
+    );
+    let file = home.join("signed.txt");
+    std::fs::write(&file, tx::encode(&signed)).unwrap();
+    broadcast(&file, WaitUntil::Executed, &chain, &flags()).unwrap();
+
+    let error = tx_status(
+        signed.get_hash(),
+        bob,
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Transaction(..)), "{error:?}");
+}
+
+#[test]
+fn fails_for_an_unknown_transaction() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+
+    let error = tx_status(
+        CryptoHash::hash_bytes(b"unknown"),
+        account("alice.testnet"),
+        WaitUntil::Executed,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Transaction(..)), "{error:?}");
+}
@@ -0,0 +1,81 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::verify_message,
+    nep413::{self, SignedMessage},
+    AccountError,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use near_api::{
+    signer::{secret_key::SecretKeySigner, NEP413Payload},
+    AccountId, NearToken,
+};
+use near_crypto::{KeyType, SecretKey};
+
+fn signed_message(account_id: AccountId, secret_key: SecretKey) -> SignedMessage {
+    let public_key = secret_key.public_key();
+    let payload = NEP413Payload {
+        message: "hello".into(),
+        nonce: [7; 32],
+        recipient: "app.example".into(),
+        callback_url: None,
+    };
+    tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(nep413::sign(
+            &SecretKeySigner::new(secret_key),
+            account_id,
+            public_key,
+            payload,
+        ))
+        .unwrap()
+}
+
+#[test]
+fn verifies_a_signed_message() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+    let json = serde_json::to_string(&signed_message(alice, secret_key)).unwrap();
+
+    let file = home.join("message.json");
+    std::fs::write(&file, &json).unwrap();
+    verify_message(&file, false, &chain, &flags()).unwrap();
+    verify_message(&file, true, &chain, &flags()).unwrap();
+
+    std::fs::write(&file, BASE64.encode(&json)).unwrap();
+    verify_message(&file, true, &chain, &flags()).unwrap();
+}
+
+#[test]
+fn fails_for_a_tampered_message() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let secret_key = chain.add_account(&alice, NearToken::from_near(1));
+    let mut signed = signed_message(alice, secret_key);
+    signed.message = "goodbye".into();
+
+    let file = home.join("message.json");
+    std::fs::write(&file, serde_json::to_string(&signed).unwrap()).unwrap();
+    let error = verify_message(&file, false, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::InvalidData(_)), "{error:?}");
+}
+
+#[test]
+fn fails_on_chain_for_a_key_of_another_account() {
+    let home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    chain.add_account(&alice, NearToken::from_near(1));
+    let signed = signed_message(alice, SecretKey::from_random(KeyType::ED25519));
+
+    let file = home.join("message.json");
+    std::fs::write(&file, serde_json::to_string(&signed).unwrap()).unwrap();
+    verify_message(&file, false, &chain, &flags()).unwrap();
+    let error = verify_message(&file, true, &chain, &flags()).unwrap_err();
+    assert!(matches!(error, AccountError::NotPermitted(_)), "{error:?}");
+}
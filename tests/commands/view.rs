@@ -0,0 +1,104 @@
+// This is free and unencumbered software released into the public domain.
+
+use crate::{account, flags, Home};
+use asimov_account_cli::{
+    chain::FakeChain,
+    commands::{view, MethodArgs, MethodResult},
+    config::OutputFormat,
+    AccountError,
+};
+
+#[test]
+fn calls_a_view_method() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let alice = account("alice.testnet");
+    let usdt = account("usdt.testnet");
+    chain.add_fungible_token(&usdt, "USDT", 6);
+    chain.mint(&usdt, &alice, 1_000_000);
+
+    let args = MethodArgs {
+        json: Some(r#"{"account_id": "alice.testnet"}"#.into()),
+        args_base64: None,
+    };
+    for output in [OutputFormat::Text, OutputFormat::Json] {
+        view(
+            usdt.clone(),
+            "ft_balance_of".into(),
+            &args,
+            output,
+            &chain,
+            &flags(),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+    let usdt = account("usdt.testnet");
+    chain.add_fungible_token(&usdt, "USDT", 6);
+
+    let args = MethodArgs {
+        json: Some("{account_id".into()),
+        args_base64: None,
+    };
+    let error = view(
+        usdt,
+        "ft_balance_of".into(),
+        &args,
+        OutputFormat::Text,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Usage(_)), "{error:?}");
+}
+
+#[test]
+fn fails_without_a_contract() {
+    let _home = Home::enter();
+    let chain = FakeChain::new();
+
+    let error = view(
+        account("nobody.testnet"),
+        "ft_metadata".into(),
+        &MethodArgs::default(),
+        OutputFormat::Text,
+        &chain,
+        &flags(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, AccountError::Rpc(..)), "{error:?}");
+}
+
+#[test]
+fn encodes_arguments() {
+    assert_eq!(MethodArgs::default().encode().unwrap(), b"{}");
+    let args = MethodArgs {
+        json: Some(r#"{ "amount": "1" }"#.into()),
+        args_base64: None,
+    };
+    assert_eq!(args.encode().unwrap(), br#"{"amount":"1"}"#);
+    let args = MethodArgs {
+        json: None,
+        args_base64: Some("AQID".into()),
+    };
+    assert_eq!(args.encode().unwrap(), [1, 2, 3]);
+}
+
+#[test]
+fn decodes_results() {
+    assert_eq!(MethodResult::decode(b""), MethodResult::None);
+    assert_eq!(
+        MethodResult::decode(br#""1000""#),
+        MethodResult::Json("1000".into())
+    );
+    // Borsh, e.g. a u32, isn't JSON:
+    assert_eq!(
+        MethodResult::decode(&[1, 0, 0, 0]),
+        MethodResult::Base64("AQAAAA==".into())
+    );
+}
@@ -0,0 +1,848 @@
+// This is free and unencumbered software released into the public domain.
+
+//! Runs the binary against a mock JSON-RPC node, exercising the near-api code
+//! paths that the in-memory chain replaces in the other tests.
+
+use asimov_account_cli::{
+    agent,
+    chain::FakeChain,
+    config,
+    migration::{Migration, MigrationStep},
+    mock_rpc::MockRpc,
+    nep413,
+    network_name::NetworkName,
+    registry::{AccountMetadata, KeyMetadata},
+    rpc::{RpcError, RpcOptions, Transient},
+    tx,
+};
+use near_api::{
+    near_primitives::{
+        account::{AccessKey, AccessKeyPermission, FunctionCallPermission},
+        action::{Action, AddKeyAction, FunctionCallAction, TransferAction},
+        transaction::{SignedTransaction, Transaction},
+    },
+    signer::{secret_key::SecretKeySigner, NEP413Payload},
+    Account, AccountId, NearToken,
+};
+use near_crypto::{KeyType, SecretKey};
+use std::{
+    io::{Read as _, Write as _},
+    path::Path,
+    process::Output,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use temp_dir::TempDir;
+
+/// Runs the binary with a fresh home directory against the mock node.
+fn run(home: &Path, mock: &MockRpc, args: &[&str]) -> Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_asimov-account"))
+        .args(args)
+        .args(["--rpc-url", &mock.url(), "--rpc-retries", "1"])
+        .env("HOME", home)
+        .current_dir(home)
+        .env_remove(agent::SOCKET_VAR)
+        .output()
+        .unwrap()
+}
+
+fn start() -> (TempDir, MockRpc) {
+    let home = TempDir::new().unwrap();
+    let mock = MockRpc::start(Arc::new(FakeChain::new())).unwrap();
+    (home, mock)
+}
+
+fn account(account_id: &str) -> AccountId {
+    account_id.parse().unwrap()
+}
+
+fn signed_transfer(
+    mock: &MockRpc,
+    signer_id: &AccountId,
+    secret_key: &SecretKey,
+    receiver_id: &AccountId,
+    amount: NearToken,
+    nonce: u64,
+) -> SignedTransaction {
+    let mut transaction = Transaction::new_v0(
+        signer_id.clone(),
+        secret_key.public_key(),
+        receiver_id.clone(),
+        nonce,
+        mock.chain().block_hash(),
+    );
+    *transaction.actions_mut() = vec![Action::Transfer(TransferAction {
+        deposit: amount.as_yoctonear(),
+    })];
+    let (hash, _) = transaction.get_hash_and_size();
+    SignedTransaction::new(secret_key.sign(hash.as_ref()), transaction)
+}
+
+#[test]
+fn lists_keys() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let secret_key = mock.chain().add_account(&alice, NearToken::from_near(1));
+
+    let output = run(home.path(), &mock, &["keys", "list", "alice.testnet"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains(&secret_key.public_key().to_string()),
+        "{stdout}"
+    );
+
+    let output = run(home.path(), &mock, &["keys", "list", "bob.testnet"]);
+    assert!(!output.status.success(), "{output:?}");
+}
+
+#[test]
+fn lists_function_call_keys_with_their_allowance() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let secret_key = mock.chain().add_account(&alice, NearToken::from_near(5));
+    let app_key = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    let mut transaction = Transaction::new_v0(
+        alice.clone(),
+        secret_key.public_key(),
+        alice.clone(),
+        1,
+        mock.chain().block_hash(),
+    );
+    *transaction.actions_mut() = vec![Action::AddKey(Box::new(AddKeyAction {
+        public_key: app_key.clone(),
+        access_key: AccessKey {
+            nonce: 0,
+            permission: AccessKeyPermission::FunctionCall(FunctionCallPermission {
+                allowance: Some(NearToken::from_millinear(250).as_yoctonear()),
+                receiver_id: "app.testnet".into(),
+                method_names: vec!["vote".into()],
+            }),
+        },
+    }))];
+    let (hash, _) = transaction.get_hash_and_size();
+    let signed = SignedTransaction::new(secret_key.sign(hash.as_ref()), transaction);
+    let file = home.path().join("signed.txt");
+    std::fs::write(&file, tx::encode(&signed)).unwrap();
+    let output = run(home.path(), &mock, &["broadcast", file.to_str().unwrap()]);
+    assert!(output.status.success(), "{output:?}");
+
+    // The registry records the allowance the key was granted:
+    let registry = home.path().join(".asimov/accounts/near/testnet");
+    std::fs::create_dir_all(&registry).unwrap();
+    let metadata = AccountMetadata {
+        keys: vec![KeyMetadata {
+            public_key: app_key.clone(),
+            contract: account("app.testnet"),
+            methods: vec!["vote".into()],
+            allowance: Some(NearToken::from_millinear(500)),
+            granted: chrono::Utc::now(),
+        }],
+        ..Default::default()
+    };
+    std::fs::write(
+        registry.join("alice.testnet"),
+        serde_json::to_string(&metadata).unwrap(),
+    )
+    .unwrap();
+
+    let output = run(home.path(), &mock, &["keys", "list", "alice.testnet"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let line = stdout
+        .lines()
+        .find(|line| line.contains(&app_key.to_string()))
+        .unwrap();
+    assert!(line.contains("to app.testnet (vote)"), "{stdout}");
+    assert!(line.contains("allowance 0.250 NEAR remaining"), "{stdout}");
+    assert!(stdout.contains("with allowance 0.500 NEAR"), "{stdout}");
+}
+
+#[test]
+fn resolves_aliases_in_arguments() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let secret_key = mock.chain().add_account(&alice, NearToken::from_near(1));
+    let registry = home.path().join(".asimov/accounts/near/testnet");
+    std::fs::create_dir_all(&registry).unwrap();
+    std::fs::File::create(registry.join("alice.testnet")).unwrap();
+
+    let output = run(home.path(), &mock, &["alias", "set", "me", "alice.testnet"]);
+    assert!(output.status.success(), "{output:?}");
+    let output = run(home.path(), &mock, &["keys", "list", "me"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains(&secret_key.public_key().to_string()),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn broadcasts_and_looks_up_transactions() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let secret_key = mock.chain().add_account(&alice, NearToken::from_near(5));
+    mock.chain().add_account(&bob, NearToken::from_near(1));
+
+    let signed = signed_transfer(&mock, &alice, &secret_key, &bob, NearToken::from_near(2), 1);
+    let file = home.path().join("signed.txt");
+    std::fs::write(&file, tx::encode(&signed)).unwrap();
+    let output = run(home.path(), &mock, &["broadcast", file.to_str().unwrap()]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(mock.chain().balance(&alice), Some(NearToken::from_near(3)));
+    assert_eq!(mock.chain().balance(&bob), Some(NearToken::from_near(3)));
+
+    let hash = signed.get_hash().to_string();
+    let output = run(
+        home.path(),
+        &mock,
+        &["tx-status", &hash, "--sender", "alice.testnet"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&hash), "{stdout}");
+    assert!(stdout.contains("Final"), "{stdout}");
+    assert!(stdout.contains("Burnt:"), "{stdout}");
+
+    // Only the status is known before the transaction is executed:
+    let output = run(
+        home.path(),
+        &mock,
+        &[
+            "tx-status",
+            &hash,
+            "--sender",
+            "alice.testnet",
+            "--wait-until",
+            "included",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Included"), "{stdout}");
+    assert!(!stdout.contains("Burnt:"), "{stdout}");
+
+    let output = run(
+        home.path(),
+        &mock,
+        &["tx-status", &hash, "--sender", "bob.testnet"],
+    );
+    assert!(!output.status.success(), "{output:?}");
+}
+
+#[test]
+fn rejects_invalid_transactions() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let bob = account("bob.testnet");
+    let secret_key = mock.chain().add_account(&alice, NearToken::from_near(5));
+    mock.chain().add_account(&bob, NearToken::from_near(1));
+
+    // Signed with a key the account doesn't have:
+    let other_key = SecretKey::from_random(KeyType::ED25519);
+    let signed = signed_transfer(&mock, &alice, &other_key, &bob, NearToken::from_near(2), 1);
+    let file = home.path().join("signed.txt");
+    std::fs::write(&file, tx::encode(&signed)).unwrap();
+    let output = run(home.path(), &mock, &["broadcast", file.to_str().unwrap()]);
+    assert!(!output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("AccessKeyNotFound"), "{stderr}");
+
+    // A transfer of more than the balance is included but fails:
+    let signed = signed_transfer(&mock, &alice, &secret_key, &bob, NearToken::from_near(9), 1);
+    std::fs::write(&file, tx::encode(&signed)).unwrap();
+    let output = run(home.path(), &mock, &["broadcast", file.to_str().unwrap()]);
+    assert!(!output.status.success(), "{output:?}");
+    assert_eq!(mock.chain().balance(&alice), Some(NearToken::from_near(5)));
+}
+
+#[test]
+fn verifies_messages_on_chain() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let secret_key = mock.chain().add_account(&alice, NearToken::from_near(1));
+
+    let sign = |secret_key: SecretKey| {
+        let public_key = secret_key.public_key();
+        let payload = NEP413Payload {
+            message: "hello".into(),
+            nonce: [7; 32],
+            recipient: "app.example".into(),
+            callback_url: None,
+        };
+        let signed = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(nep413::sign(
+                &SecretKeySigner::new(secret_key),
+                alice.clone(),
+                public_key,
+                payload,
+            ))
+            .unwrap();
+        let file = home.path().join("message.json");
+        std::fs::write(&file, serde_json::to_string(&signed).unwrap()).unwrap();
+        file
+    };
+
+    let file = sign(secret_key);
+    let output = run(
+        home.path(),
+        &mock,
+        &["verify-message", file.to_str().unwrap(), "--on-chain"],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    let file = sign(SecretKey::from_random(KeyType::ED25519));
+    let output = run(
+        home.path(),
+        &mock,
+        &["verify-message", file.to_str().unwrap(), "--on-chain"],
+    );
+    assert!(!output.status.success(), "{output:?}");
+}
+
+#[test]
+fn funds_accounts_from_a_configured_faucet() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    mock.chain().add_account(&alice, NearToken::from_near(1));
+
+    let output = run(
+        home.path(),
+        &mock,
+        &["fund", "alice.testnet", "--faucet-url", &mock.faucet_url()],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("11.00 NEAR"), "{stdout}");
+    assert_eq!(mock.chain().balance(&alice), Some(NearToken::from_near(11)));
+}
+
+#[test]
+fn prefers_flags_to_the_environment_to_the_configuration() {
+    let (home, mock) = start();
+    // A configuration file in another place than the default:
+    let config = home.path().join("asimov.toml");
+    std::fs::write(&config, "output = \"json\"\n").unwrap();
+    let list = |env: &[(&str, &str)], args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_asimov-account"))
+            .arg("list")
+            .args(args)
+            .args(["--rpc-url", &mock.url()])
+            .env("HOME", home.path())
+            .env(config::PATH_VAR, &config)
+            .envs(env.iter().copied())
+            .current_dir(home.path())
+            .env_remove(agent::SOCKET_VAR)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(list(&[], &[]), "{}\n");
+    let env = [("ASIMOV_ACCOUNT_OUTPUT", "csv")];
+    assert_eq!(list(&env, &[]), "name,network,scope\n");
+    assert_eq!(list(&env, &["--output", "text"]), "");
+}
+
+#[test]
+fn requires_a_configured_sponsor_amount_with_a_configured_sponsor() {
+    let (home, mock) = start();
+    let output = run(
+        home.path(),
+        &mock,
+        &["config", "set", "sponsor", "sponsor.testnet"],
+    );
+    assert!(output.status.success(), "{output:?}");
+
+    let output = run(home.path(), &mock, &["register", "bob.testnet"]);
+    assert_eq!(output.status.code(), Some(78), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("sponsor-amount"), "{stderr}");
+    assert!(!stderr.contains("--sponsor"), "{stderr}");
+}
+
+#[test]
+fn requires_a_mainnet_sponsor_without_a_terminal() {
+    let (home, mock) = start();
+    // Standard input isn't a terminal, so the wizard isn't offered:
+    let output = run(home.path(), &mock, &["register", "bob.near"]);
+    assert_eq!(output.status.code(), Some(64), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("requires a sponsor"), "{stderr}");
+}
+
+#[test]
+fn does_not_ask_a_failing_faucet_again() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    mock.chain().add_account(&alice, NearToken::from_near(1));
+
+    // A faucet that fails after receiving the request, so it might have sent
+    // the tokens:
+    let faucet = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let faucet_url = format!("http://{}/", faucet.local_addr().unwrap());
+    let done = Arc::new(AtomicBool::new(false));
+    let requests = std::thread::spawn({
+        let done = done.clone();
+        move || {
+            faucet.set_nonblocking(true).unwrap();
+            let mut requests = 0;
+            while !done.load(Ordering::Relaxed) {
+                match faucet.accept() {
+                    Ok((mut stream, _)) => {
+                        requests += 1;
+                        stream.set_nonblocking(false).unwrap();
+                        let mut buffer = [0; 4096];
+                        let _ = stream.read(&mut buffer);
+                        let _ = stream.write_all(
+                            b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n",
+                        );
+                    }
+                    Err(_) => std::thread::sleep(Duration::from_millis(10)),
+                }
+            }
+            requests
+        }
+    });
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_asimov-account"))
+        .args(["fund", "alice.testnet", "--faucet-url", &faucet_url])
+        .args(["--rpc-url", &mock.url(), "--rpc-retries", "3"])
+        .args(["--rpc-backoff", "10ms"])
+        .env("HOME", home.path())
+        .current_dir(home.path())
+        .env_remove(agent::SOCKET_VAR)
+        .output()
+        .unwrap();
+    done.store(true, Ordering::Relaxed);
+    assert!(!output.status.success(), "{output:?}");
+    assert_eq!(requests.join().unwrap(), 1);
+    assert_eq!(mock.chain().balance(&alice), Some(NearToken::from_near(1)));
+}
+
+#[tokio::test]
+async fn creates_accounts_with_the_faucet() {
+    let mock = MockRpc::start(Arc::new(FakeChain::new())).unwrap();
+    let alice = account("alice.testnet");
+    let public_key = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    let response = Account::create_account(alice.clone())
+        .sponsor_by_faucet_service()
+        .public_key(public_key.clone())
+        .unwrap()
+        .send_to_faucet(&mock.faucet_url().parse().unwrap())
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    assert_eq!(mock.chain().balance(&alice), Some(NearToken::from_near(10)));
+    assert!(mock.chain().keys(&alice).unwrap().contains_key(&public_key));
+}
+
+/// An error that is transient or not, as the test decides.
+#[derive(Debug)]
+struct TestError(bool);
+
+impl Transient for TestError {
+    fn is_transient(&self) -> bool {
+        self.0
+    }
+}
+
+#[tokio::test]
+async fn retries_transient_errors_with_backoff() {
+    let rpc = RpcOptions {
+        rpc_timeout: Duration::from_millis(50),
+        rpc_retries: 3,
+        rpc_backoff: Duration::from_millis(1),
+        ..Default::default()
+    };
+    assert_eq!(rpc.backoff(0), Duration::from_millis(1));
+    assert_eq!(rpc.backoff(2), Duration::from_millis(4));
+
+    // Succeeds on the last attempt after two transient errors:
+    let attempts = AtomicUsize::new(0);
+    let result = rpc
+        .retry(|| async {
+            match attempts.fetch_add(1, Ordering::Relaxed) {
+                0 | 1 => Err(TestError(true)),
+                _ => Ok(()),
+            }
+        })
+        .await;
+    assert!(result.is_ok());
+    assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+    // Gives up at once on a permanent error:
+    let attempts = AtomicUsize::new(0);
+    let result = rpc
+        .retry(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>(TestError(false))
+        })
+        .await;
+    assert!(matches!(result, Err(RpcError::Failed(TestError(false)))));
+    assert_eq!(attempts.load(Ordering::Relaxed), 1);
+
+    // Times out every attempt:
+    let attempts = AtomicUsize::new(0);
+    let result = rpc
+        .retry(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok::<(), TestError>(())
+        })
+        .await;
+    assert!(matches!(result, Err(RpcError::Timeout(_))));
+    assert_eq!(attempts.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::test]
+async fn serves_the_latest_block() {
+    let mock = MockRpc::start(Arc::new(FakeChain::new())).unwrap();
+    let rpc = RpcOptions {
+        rpc_url: Some(near_api::RPCEndpoint::new(mock.url().parse().unwrap())),
+        ..Default::default()
+    };
+
+    let block_hash = near_api::Chain::block_hash()
+        .fetch_from(&rpc.network_config(NetworkName::Testnet))
+        .await
+        .unwrap();
+    assert_eq!(block_hash.0, mock.chain().block_hash().0);
+}
+
+#[test]
+fn lists_accounts_with_their_balances() {
+    let (home, mock) = start();
+    let registry = home.path().join(".asimov/accounts/near/testnet");
+    std::fs::create_dir_all(&registry).unwrap();
+    for (name, balance) in [("alice.testnet", 2), ("bob.testnet", 5)] {
+        mock.chain()
+            .add_account(&account(name), NearToken::from_near(balance));
+        std::fs::File::create(registry.join(name)).unwrap();
+    }
+    std::fs::File::create(registry.join("carol.testnet")).unwrap();
+
+    let output = run(
+        home.path(),
+        &mock,
+        &[
+            "list", "--long", "--sort", "balance", "--output", "json", "a*", "b*",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names = rows
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| {
+            (
+                row["name"].as_str().unwrap(),
+                row["balance"].as_str().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            ("bob.testnet", "5000000000000000000000000"),
+            ("alice.testnet", "2000000000000000000000000"),
+        ]
+    );
+
+    let output = run(
+        home.path(),
+        &mock,
+        &["list", "--long", "--output", "csv", "carol.*"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some("name,network,scope,active,balance,staked,keys,created,aliases,labels")
+    );
+    assert!(
+        lines
+            .next()
+            .unwrap()
+            .starts_with("carol.testnet,testnet,user,false,,,account-not-found,"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn lists_accounts_without_a_reachable_node() {
+    let home = TempDir::new().unwrap();
+    let registry = home.path().join(".asimov/accounts/near/testnet");
+    std::fs::create_dir_all(&registry).unwrap();
+    std::fs::File::create(registry.join("alice.testnet")).unwrap();
+
+    // Nothing listens on the discard port, so every request fails:
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_asimov-account"))
+        .args(["-v", "list", "--long", "--output", "csv"])
+        .args(["--rpc-url", "http://127.0.0.1:9", "--rpc-retries", "1"])
+        .env("HOME", home.path())
+        .current_dir(home.path())
+        .env_remove(agent::SOCKET_VAR)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("alice.testnet,testnet,user,false,,,,"),
+        "{stdout}"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Failed to fetch") && stderr.contains("error sending request"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn keeps_a_migration_resumable_without_a_reachable_node() {
+    let home = TempDir::new().unwrap();
+    let registry = home.path().join(".asimov/accounts/near/testnet");
+    std::fs::create_dir_all(&registry).unwrap();
+    std::fs::write(registry.join("alice.testnet"), "{}\n").unwrap();
+    let log_path = home
+        .path()
+        .join(".asimov/accounts/migrations/testnet/alice.testnet.json");
+    let mut log = Migration::new(account("alice.testnet"), account("alicia.testnet"), false);
+    log.completed = vec![MigrationStep::Register, MigrationStep::Transfer];
+    log.save(&log_path).unwrap();
+
+    // Nothing listens on the discard port, so the old account can't be checked:
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_asimov-account"))
+        .args(["migrate", "alice.testnet", "alicia.testnet"])
+        .args(["--rpc-url", "http://127.0.0.1:9", "--rpc-retries", "1"])
+        .env("HOME", home.path())
+        .current_dir(home.path())
+        .env_remove(agent::SOCKET_VAR)
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{output:?}");
+
+    assert!(registry.join("alice.testnet").is_file());
+    let log = Migration::load(&log_path).unwrap().unwrap();
+    assert!(!log.completed.contains(&MigrationStep::Delete));
+}
+
+#[test]
+fn shows_staking_balances() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let pool = account("pool.testnet");
+    mock.chain().add_staking_pool(&pool);
+    let secret_key = mock.chain().add_account(&alice, NearToken::from_near(10));
+
+    let mut transaction = Transaction::new_v0(
+        alice.clone(),
+        secret_key.public_key(),
+        pool.clone(),
+        1,
+        mock.chain().block_hash(),
+    );
+    *transaction.actions_mut() = vec![Action::FunctionCall(Box::new(FunctionCallAction {
+        method_name: "deposit_and_stake".into(),
+        args: b"{}".to_vec(),
+        gas: 100_000_000_000_000,
+        deposit: NearToken::from_near(4).as_yoctonear(),
+    }))];
+    let (hash, _) = transaction.get_hash_and_size();
+    let signed = SignedTransaction::new(secret_key.sign(hash.as_ref()), transaction);
+    let file = home.path().join("signed.txt");
+    std::fs::write(&file, tx::encode(&signed)).unwrap();
+    let output = run(home.path(), &mock, &["broadcast", file.to_str().unwrap()]);
+    assert!(output.status.success(), "{output:?}");
+
+    let output = run(
+        home.path(),
+        &mock,
+        &[
+            "stake",
+            "status",
+            "alice.testnet",
+            "--pool",
+            "pool.testnet",
+            "--output",
+            "csv",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        "pool,staked,unstaked,withdrawable\npool.testnet,4000000000000000000000000,0,0\n"
+    );
+
+    let output = run(
+        home.path(),
+        &mock,
+        &["stake", "status", "alice.testnet", "--pool", "bob.testnet"],
+    );
+    assert!(!output.status.success(), "{output:?}");
+
+    // The long listing shows the balance in each pool the account staked with:
+    let registry = home.path().join(".asimov/accounts/near/testnet");
+    std::fs::create_dir_all(&registry).unwrap();
+    std::fs::write(
+        registry.join("alice.testnet"),
+        r#"{"pools": ["pool.testnet"]}"#,
+    )
+    .unwrap();
+    let output = run(home.path(), &mock, &["list", "--long", "--output", "json"]);
+    assert!(output.status.success(), "{output:?}");
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(rows[0]["stakes"][0]["pool"], "pool.testnet");
+    assert_eq!(
+        rows[0]["stakes"][0]["staked"],
+        NearToken::from_near(4).as_yoctonear().to_string()
+    );
+    let output = run(home.path(), &mock, &["list", "--long"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("pool.testnet: 4.00 NEAR staked"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn lists_configured_token_balances() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let usdt = account("usdt.testnet");
+    mock.chain().add_account(&alice, NearToken::from_near(1));
+    mock.chain().add_fungible_token(&usdt, "USDT", 6);
+    mock.chain().mint(&usdt, &alice, 1_500_000);
+
+    let output = run(
+        home.path(),
+        &mock,
+        &["config", "set", "tokens.testnet", "usdt.testnet"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let output = run(
+        home.path(),
+        &mock,
+        &["tokens", "alice.testnet", "--output", "json"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let tokens: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(tokens[0]["symbol"], "USDT");
+    assert_eq!(tokens[0]["balance"], "1500000");
+    assert_eq!(tokens[0]["amount"], "1.5");
+}
+
+#[test]
+fn quotes_token_symbols_in_csv() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let token = account("odd.testnet");
+    mock.chain().add_account(&alice, NearToken::from_near(1));
+    mock.chain().add_fungible_token(&token, "A,\"B\"", 0);
+    mock.chain().mint(&token, &alice, 7);
+
+    let output = run(
+        home.path(),
+        &mock,
+        &[
+            "tokens",
+            "alice.testnet",
+            "--token",
+            "odd.testnet",
+            "--output",
+            "csv",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "token,symbol,decimals,balance\nodd.testnet,\"A,\"\"B\"\"\",0,7\n"
+    );
+}
+
+#[test]
+fn lists_nfts_with_pagination() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let badges = account("badges.testnet");
+    mock.chain().add_account(&alice, NearToken::from_near(1));
+    mock.chain()
+        .add_nft_contract(&badges, "Badges", Some("https://badges.example"));
+    for index in 0..60 {
+        let token_id = format!("{index:02}");
+        mock.chain()
+            .mint_nft(&badges, &alice, &token_id, "Badge", "badge.png");
+    }
+
+    let output = run(
+        home.path(),
+        &mock,
+        &[
+            "nfts",
+            "alice.testnet",
+            "--contract",
+            "badges.testnet",
+            "--output",
+            "json",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let tokens: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(tokens.len(), 60);
+    assert_eq!(tokens[59]["token_id"], "59");
+    assert_eq!(tokens[0]["title"], "Badge");
+    assert_eq!(tokens[0]["media"], "https://badges.example/badge.png");
+}
+
+#[test]
+fn calls_a_view_method() {
+    let (home, mock) = start();
+    let alice = account("alice.testnet");
+    let usdt = account("usdt.testnet");
+    mock.chain().add_account(&alice, NearToken::from_near(1));
+    mock.chain().add_fungible_token(&usdt, "USDT", 6);
+    mock.chain().mint(&usdt, &alice, 1_500_000);
+
+    let output = run(
+        home.path(),
+        &mock,
+        &[
+            "view",
+            "usdt.testnet",
+            "ft_balance_of",
+            "--args",
+            r#"{"account_id": "alice.testnet"}"#,
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "\"1500000\"\n");
+
+    let output = run(
+        home.path(),
+        &mock,
+        &["view", "usdt.testnet", "ft_metadata", "--output", "json"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let view: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(view["result"]["encoding"], "json");
+    assert_eq!(view["result"]["value"]["symbol"], "USDT");
+    assert_eq!(view["logs"], serde_json::json!([]));
+}